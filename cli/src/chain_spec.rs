@@ -1,5 +1,5 @@
 // Copyright 2018-2019 Chainpool.
-use serde_json::json;
+use serde_json::{json, map::Map, Value};
 
 use telemetry::TelemetryEndpoints;
 
@@ -10,38 +10,129 @@ use super::genesis_config::{genesis, GenesisSpec};
 const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 const CHAINX_TELEMETRY_URL: &str = "ws://stats.chainx.org:1024/submit/";
 
+/// Property key for overriding the compiled boot nodes, e.g. `["/ip4/.../p2p/Qm..."]`.
+const BOOT_NODES_KEY: &str = "boot_nodes";
+/// Property key for overriding the compiled telemetry endpoints, each item is either
+/// an url or an `[url, verbosity]` pair, e.g. `["ws://127.0.0.1:1024/submit/", ["wss://...", 1]]`.
+const TELEMETRY_ENDPOINTS_KEY: &str = "telemetry_endpoints";
+
 /// Specialised `ChainSpec`.
 pub type ChainSpec = substrate_service::ChainSpec<GenesisConfig>;
 
+/// Custom properties of the `ChainSpec`.
+pub type Properties = Map<String, Value>;
+
+/// Resolve the boot nodes and telemetry endpoints from the `overrides`, the compiled defaults
+/// are used when the overrides don't provide them.
+///
+/// Only the boot nodes and telemetry endpoints could be overridden, the network and address
+/// types stay the compiled ones, and the override keys are not carried into the properties.
+fn resolve_spec_endpoints(
+    overrides: &Properties,
+    default_boot_nodes: Vec<String>,
+    default_telemetry: Vec<(String, u8)>,
+) -> Result<(Vec<String>, Vec<(String, u8)>), String> {
+    if let Some(key) = overrides
+        .keys()
+        .find(|key| key.as_str() != BOOT_NODES_KEY && key.as_str() != TELEMETRY_ENDPOINTS_KEY)
+    {
+        return Err(format!(
+            "`{}` can't be overridden, only `{}` and `{}` are allowed",
+            key, BOOT_NODES_KEY, TELEMETRY_ENDPOINTS_KEY
+        ));
+    }
+
+    let boot_nodes = match overrides.get(BOOT_NODES_KEY) {
+        Some(Value::Array(nodes)) => nodes
+            .iter()
+            .map(|node| {
+                node.as_str()
+                    .map(ToString::to_string)
+                    .ok_or_else(|| format!("invalid boot node in properties: {}", node))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(v) => return Err(format!("`{}` must be an array, got: {}", BOOT_NODES_KEY, v)),
+        None => default_boot_nodes,
+    };
+
+    let telemetry = match overrides.get(TELEMETRY_ENDPOINTS_KEY) {
+        Some(Value::Array(endpoints)) => endpoints
+            .iter()
+            .map(|endpoint| match endpoint {
+                Value::String(url) => Ok((url.clone(), 0)),
+                Value::Array(pair) if pair.len() == 2 => {
+                    match (pair[0].as_str(), pair[1].as_u64()) {
+                        (Some(url), Some(verbosity)) if verbosity <= u64::from(u8::max_value()) => {
+                            Ok((url.to_string(), verbosity as u8))
+                        }
+                        _ => Err(format!(
+                            "invalid telemetry endpoint in properties: {}",
+                            endpoint
+                        )),
+                    }
+                }
+                _ => Err(format!(
+                    "invalid telemetry endpoint in properties: {}",
+                    endpoint
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(v) => {
+            return Err(format!(
+                "`{}` must be an array, got: {}",
+                TELEMETRY_ENDPOINTS_KEY, v
+            ))
+        }
+        None => default_telemetry,
+    };
+
+    Ok((boot_nodes, telemetry))
+}
+
+fn properties(network_type: &str, address_type: u8, bitcoin_type: &str) -> Properties {
+    json!({
+        "network_type": network_type,
+        "address_type": address_type,
+        "bitcoin_type": bitcoin_type
+    })
+    .as_object()
+    .unwrap()
+    .to_owned()
+}
+
+fn telemetry_endpoints(endpoints: Vec<(String, u8)>) -> Option<TelemetryEndpoints> {
+    if endpoints.is_empty() {
+        None
+    } else {
+        Some(TelemetryEndpoints::new(endpoints))
+    }
+}
+
 /// Staging testnet config.
-pub fn mainnet_config() -> ChainSpec {
+pub fn mainnet_config(overrides: &Properties) -> Result<ChainSpec, String> {
     let boot_nodes = vec![
         "/ip4/47.96.134.203/tcp/31126/p2p/QmTZBuK6KCi5KXxJjsun5j6m46Gsj9BgSuo5MxaDfbGDJe".into(),
         "/ip4/47.96.97.52/tcp/31127/p2p/QmaiWDshcMMwEp5EbNKHhicqNQG6hWs6BquJqm3QTXgATW".into(),
         "/ip4/47.110.232.108/tcp/31129/p2p/QmZpqsZ5XMSMHThbiUFTUxCY3efjz2uGDGH3Jh3rAcKA8R".into(),
     ];
-    ChainSpec::from_genesis(
+    let (boot_nodes, telemetry) = resolve_spec_endpoints(
+        overrides,
+        boot_nodes,
+        vec![
+            (STAGING_TELEMETRY_URL.to_string(), 0),
+            (CHAINX_TELEMETRY_URL.to_string(), 0),
+        ],
+    )?;
+    Ok(ChainSpec::from_genesis(
         "ChainX",
         "chainx_mainnet",
         mainnet_config_genesis,
         boot_nodes,
-        Some(TelemetryEndpoints::new(vec![
-            (STAGING_TELEMETRY_URL.to_string(), 0),
-            (CHAINX_TELEMETRY_URL.to_string(), 0),
-        ])),
+        telemetry_endpoints(telemetry),
         Some("ChainX Mainnet"),
         None,
-        Some(
-            json!({
-                "network_type": "mainnet",
-                "address_type": 44,
-                "bitcoin_type": "mainnet"
-            })
-            .as_object()
-            .unwrap()
-            .to_owned(),
-        ),
-    )
+        Some(properties("mainnet", 44, "mainnet")),
+    ))
 }
 
 fn mainnet_config_genesis() -> GenesisConfig {
@@ -53,117 +144,153 @@ fn development_config_genesis() -> GenesisConfig {
 }
 
 /// Development config (single validator Alice)
-pub fn development_config() -> ChainSpec {
-    ChainSpec::from_genesis(
+pub fn development_config(overrides: &Properties) -> Result<ChainSpec, String> {
+    let (boot_nodes, telemetry) = resolve_spec_endpoints(
+        overrides,
+        vec![],
+        vec![(CHAINX_TELEMETRY_URL.to_string(), 0)],
+    )?;
+    Ok(ChainSpec::from_genesis(
         "ChainX Dev",
         "chainx_dev",
         development_config_genesis,
-        vec![],
-        Some(TelemetryEndpoints::new(vec![(
-            CHAINX_TELEMETRY_URL.to_string(),
-            0,
-        )])),
+        boot_nodes,
+        telemetry_endpoints(telemetry),
         Some("ChainX Dev"),
         None,
-        Some(
-            json!({
-                "network_type": "testnet",
-                "address_type": 42,
-                "bitcoin_type": "testnet"
-            })
-            .as_object()
-            .unwrap()
-            .to_owned(),
-        ),
-    )
+        Some(properties("testnet", 42, "testnet")),
+    ))
 }
 
 fn testnet_genesis() -> GenesisConfig {
     genesis(GenesisSpec::Testnet)
 }
 
-pub fn testnet_config() -> ChainSpec {
-    let boot_nodes = vec![];
-    ChainSpec::from_genesis(
+pub fn testnet_config(overrides: &Properties) -> Result<ChainSpec, String> {
+    let (boot_nodes, telemetry) = resolve_spec_endpoints(
+        overrides,
+        vec![],
+        vec![(CHAINX_TELEMETRY_URL.to_string(), 0)],
+    )?;
+    Ok(ChainSpec::from_genesis(
         "ChainX Testnet Taoism",
         "chainx_testnet_taoism",
         testnet_genesis,
         boot_nodes,
-        Some(TelemetryEndpoints::new(vec![(
-            CHAINX_TELEMETRY_URL.to_string(),
-            0,
-        )])),
+        telemetry_endpoints(telemetry),
         Some("ChainX Testnet Taoism"),
         None,
-        Some(
-            json!({
-                "network_type": "testnet",
-                "address_type": 42,
-                "bitcoin_type": "testnet"
-            })
-            .as_object()
-            .unwrap()
-            .to_owned(),
-        ),
-    )
+        Some(properties("testnet", 42, "testnet")),
+    ))
 }
 
 fn testnet_mohism_genesis() -> GenesisConfig {
     genesis(GenesisSpec::TestnetMohism)
 }
 
-pub fn testnet_mohism_config() -> ChainSpec {
-    let boot_nodes = vec![];
-    ChainSpec::from_genesis(
+pub fn testnet_mohism_config(overrides: &Properties) -> Result<ChainSpec, String> {
+    let (boot_nodes, telemetry) = resolve_spec_endpoints(
+        overrides,
+        vec![],
+        vec![(CHAINX_TELEMETRY_URL.to_string(), 0)],
+    )?;
+    Ok(ChainSpec::from_genesis(
         "ChainX Testnet Mohism",
         "chainx_testnet_mohism",
         testnet_mohism_genesis,
         boot_nodes,
-        Some(TelemetryEndpoints::new(vec![(
-            CHAINX_TELEMETRY_URL.to_string(),
-            0,
-        )])),
+        telemetry_endpoints(telemetry),
         Some("ChainX Testnet Mohism"),
         None,
-        Some(
-            json!({
-                "network_type": "testnet",
-                "address_type": 42,
-                "bitcoin_type": "testnet"
-            })
-            .as_object()
-            .unwrap()
-            .to_owned(),
-        ),
-    )
+        Some(properties("testnet", 42, "testnet")),
+    ))
 }
 
 fn testnet_confucianism_genesis() -> GenesisConfig {
     genesis(GenesisSpec::TestnetConfucianism)
 }
 
-pub fn testnet_confucianism_config() -> ChainSpec {
-    let boot_nodes = vec![];
-    ChainSpec::from_genesis(
+pub fn testnet_confucianism_config(overrides: &Properties) -> Result<ChainSpec, String> {
+    let (boot_nodes, telemetry) = resolve_spec_endpoints(
+        overrides,
+        vec![],
+        vec![(CHAINX_TELEMETRY_URL.to_string(), 0)],
+    )?;
+    Ok(ChainSpec::from_genesis(
         "ChainX Testnet Confucianism",
         "chainx_testnet_confucianism",
         testnet_confucianism_genesis,
         boot_nodes,
-        Some(TelemetryEndpoints::new(vec![(
-            CHAINX_TELEMETRY_URL.to_string(),
-            0,
-        )])),
+        telemetry_endpoints(telemetry),
         Some("ChainX Testnet Confucianism"),
         None,
-        Some(
-            json!({
-                "network_type": "testnet",
-                "address_type": 42,
-                "bitcoin_type": "mainnet"
-            })
+        Some(properties("testnet", 42, "mainnet")),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> (Vec<String>, Vec<(String, u8)>) {
+        (
+            vec!["/ip4/127.0.0.1/tcp/30333/p2p/QmDefault".to_string()],
+            vec![(CHAINX_TELEMETRY_URL.to_string(), 0)],
+        )
+    }
+
+    #[test]
+    fn compiled_endpoints_should_be_fallback() {
+        let (boot_nodes, telemetry) = defaults();
+        let (b, t) =
+            resolve_spec_endpoints(&Properties::new(), boot_nodes.clone(), telemetry.clone())
+                .unwrap();
+        assert_eq!(b, boot_nodes);
+        assert_eq!(t, telemetry);
+    }
+
+    #[test]
+    fn properties_telemetry_should_override_default() {
+        let (boot_nodes, telemetry) = defaults();
+        let overrides = json!({
+            "boot_nodes": ["/ip4/10.0.0.1/tcp/30333/p2p/QmPrivate"],
+            "telemetry_endpoints": ["ws://10.0.0.2:1024/submit/", ["ws://10.0.0.3:1024/submit/", 1]]
+        })
+        .as_object()
+        .unwrap()
+        .to_owned();
+        let (b, t) = resolve_spec_endpoints(&overrides, boot_nodes, telemetry).unwrap();
+        assert_eq!(b, vec!["/ip4/10.0.0.1/tcp/30333/p2p/QmPrivate".to_string()]);
+        assert_eq!(
+            t,
+            vec![
+                ("ws://10.0.0.2:1024/submit/".to_string(), 0),
+                ("ws://10.0.0.3:1024/submit/".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_endpoints_should_be_rejected() {
+        let (boot_nodes, telemetry) = defaults();
+        let overrides = json!({ "telemetry_endpoints": [["ws://10.0.0.2:1024/submit/", 256]] })
             .as_object()
             .unwrap()
-            .to_owned(),
-        ),
-    )
+            .to_owned();
+        assert!(resolve_spec_endpoints(&overrides, boot_nodes, telemetry).is_err());
+    }
+
+    #[test]
+    fn network_properties_should_not_be_overridden() {
+        let (boot_nodes, telemetry) = defaults();
+        for overrides in vec![
+            json!({ "network_type": "mainnet" }),
+            json!({ "address_type": 44, "boot_nodes": [] }),
+        ] {
+            let overrides = overrides.as_object().unwrap().to_owned();
+            assert!(
+                resolve_spec_endpoints(&overrides, boot_nodes.clone(), telemetry.clone()).is_err()
+            );
+        }
+    }
 }
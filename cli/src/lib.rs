@@ -52,16 +52,39 @@ pub enum ChainSpec {
     Mainnet,
 }
 
+/// The environment variable pointing to a json file, whose object overrides the `boot_nodes`
+/// and `telemetry_endpoints` of the chain spec, any other key is rejected.
+const SPEC_PROPERTIES_ENV: &str = "CHAINX_SPEC_PROPERTIES";
+
+fn load_properties_override() -> Result<chain_spec::Properties, String> {
+    let path = match std::env::var(SPEC_PROPERTIES_ENV) {
+        Ok(path) => path,
+        Err(_) => return Ok(chain_spec::Properties::new()),
+    };
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("read spec properties file {:}: {:?}", path, e))?;
+    match serde_json::from_str(&content)
+        .map_err(|e| format!("parse spec properties file {:}: {:?}", path, e))?
+    {
+        serde_json::Value::Object(properties) => Ok(properties),
+        _ => Err(format!(
+            "spec properties file {:} must be a json object",
+            path
+        )),
+    }
+}
+
 /// Get a chain config from a spec setting.
 impl ChainSpec {
     pub(crate) fn load(self) -> Result<chain_spec::ChainSpec, String> {
-        Ok(match self {
-            ChainSpec::Development => chain_spec::development_config(),
-            ChainSpec::Testnet => chain_spec::testnet_config(),
-            ChainSpec::TestnetMohism => chain_spec::testnet_mohism_config(),
-            ChainSpec::TestnetConfucianism => chain_spec::testnet_confucianism_config(),
-            ChainSpec::Mainnet => chain_spec::mainnet_config(),
-        })
+        let overrides = load_properties_override()?;
+        match self {
+            ChainSpec::Development => chain_spec::development_config(&overrides),
+            ChainSpec::Testnet => chain_spec::testnet_config(&overrides),
+            ChainSpec::TestnetMohism => chain_spec::testnet_mohism_config(&overrides),
+            ChainSpec::TestnetConfucianism => chain_spec::testnet_confucianism_config(&overrides),
+            ChainSpec::Mainnet => chain_spec::mainnet_config(&overrides),
+        }
     }
 
     pub(crate) fn from(s: &str) -> Option<Self> {
@@ -75,6 +98,12 @@ impl ChainSpec {
     }
 }
 
+/// The validator name must be registrable on-chain, i.e. satisfy `xaccounts::is_valid_name`.
+fn check_validator_name(name: &str) -> Result<(), String> {
+    xaccounts::is_valid_name(name.as_bytes())
+        .map_err(|e| format!("invalid validator name {:?}: {}", name, e))
+}
+
 fn load_spec(id: &str) -> Result<Option<chain_spec::ChainSpec>, String> {
     match ChainSpec::from(id) {
         Some(spec) => Ok(Some(spec.load()?)),
@@ -221,6 +250,7 @@ where
                 } else {
                     option_name.ok_or("if in AUTHORITY mode, must point the validator name!")?
                 };
+                check_validator_name(&name)?;
                 info!("Validator name: {:}", name);
                 set_validator_name(name);
             }
@@ -272,5 +302,27 @@ where
     // TODO [andre]: timeout this future #1318
     let _ = runtime.shutdown_on_idle().wait();
 
+    // persist the lines still buffered in the log appenders before the process exits
+    logger::flush();
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_validator_name_should_work() {
+        assert!(check_validator_name("Alice").is_ok());
+        assert!(check_validator_name("ab").is_ok());
+        assert!(check_validator_name("abcdefghijkl").is_ok());
+
+        // length out of [2, 12]
+        assert!(check_validator_name("").is_err());
+        assert!(check_validator_name("a").is_err());
+        assert!(check_validator_name("abcdefghijklm").is_err());
+        // not xss-proof
+        assert!(check_validator_name("<Alice>").is_err());
+    }
+}
@@ -17,6 +17,20 @@ use log4rs::{
 };
 
 pub fn init(spec: &str, params: ChainXParams) -> Result<(), String> {
+    let log_config = build_config(spec, &params)?;
+
+    log4rs::init_config(log_config).expect("Initializing log config shouldn't be fail");
+
+    Ok(())
+}
+
+/// Flush the buffered lines of all appenders, must be called before the process exits,
+/// otherwise the last lines kept in the rolling file writer would be lost.
+pub fn flush() {
+    log::logger().flush();
+}
+
+fn build_config(spec: &str, params: &ChainXParams) -> Result<config::Config, String> {
     let (directives, filter) = parse_spec(spec);
     let filter = filter.unwrap_or(LevelFilter::Info);
 
@@ -83,11 +97,45 @@ pub fn init(spec: &str, params: ChainXParams) -> Result<(), String> {
         config::Root::builder().appender("roll").build(filter)
     };
 
-    let log_config = tmp_builder
+    tmp_builder
         .build(root)
-        .expect("Construct log config failure");
+        .map_err(|e| format!("Construct log config failure: {:?}", e))
+}
 
-    log4rs::init_config(log_config).expect("Initializing log config shouldn't be fail");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::{Level, Log, Record};
 
-    Ok(())
+    #[test]
+    fn flush_should_persist_buffered_lines() {
+        let log_dir = std::env::temp_dir().join(format!("chainx-log-test-{}", std::process::id()));
+        let params = ChainXParams {
+            validator_name: None,
+            config: None,
+            default_log: false,
+            log_dir: log_dir.to_string_lossy().into_owned(),
+            log_name: "chainx.log".to_string(),
+            log_size: 1,
+            log_roll_count: 1,
+            log_console: false,
+            log_compression: false,
+            rpc_cache: false,
+        };
+        let logger = log4rs::Logger::new(build_config("info", &params).unwrap());
+
+        logger.log(
+            &Record::builder()
+                .args(format_args!("the last line before shutdown"))
+                .level(Level::Info)
+                .target("chainx")
+                .build(),
+        );
+        logger.flush();
+
+        let content = std::fs::read_to_string(log_dir.join("chainx.log")).unwrap();
+        assert!(content.contains("the last line before shutdown"));
+
+        let _ = std::fs::remove_dir_all(log_dir);
+    }
 }
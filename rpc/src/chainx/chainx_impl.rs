@@ -17,6 +17,7 @@ use runtime_primitives::traits::{
 
 // chainx
 use chainx_runtime::Call;
+use support::traits::Get;
 use xr_primitives::AddrStr;
 
 use xassets::{AssetLimit, AssetType, Chain, ChainT};
@@ -871,6 +872,24 @@ where
         ))
     }
 
+    fn fee_switcher(
+        &self,
+        hash: Option<<Block as BlockT>::Hash>,
+    ) -> Result<BTreeMap<String, bool>> {
+        let state = self.state_at(hash)?;
+        let key = <xfee_manager::Switcher<Runtime>>::key();
+        let switcher = Self::pickout::<BTreeMap<xfee_manager::CallSwitcher, bool>>(
+            &state,
+            &key,
+            Hasher::TWOX128,
+        )?
+        .unwrap_or_default();
+        Ok(xfee_manager::switch_store(&switcher)
+            .into_iter()
+            .map(|(k, v)| (format!("{:?}", k), v))
+            .collect())
+    }
+
     fn withdraw_tx(
         &self,
         chain: Chain,
@@ -1028,6 +1047,41 @@ where
         Ok(get_storage_result)
     }
 
+    fn contract_get_storage_multi(
+        &self,
+        address: AccountIdForRpc,
+        keys: Vec<H256>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<Option<Bytes>>> {
+        if keys.len() > xcontracts::MAX_STORAGE_KEYS_PER_QUERY {
+            return Err(Error::InvalidParams(format!(
+                "at most {} keys could be queried at once",
+                xcontracts::MAX_STORAGE_KEYS_PER_QUERY
+            )));
+        }
+
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().chain.best_hash));
+        let address: AccountId = address.unchecked_into();
+
+        let get_storage_result = api
+            .get_storage_multi(&at, address, keys.into_iter().map(Into::into).collect())
+            .map_err(|e|
+                // Handle general API calling errors.
+                Error::RuntimeErr(
+                    b"Runtime trapped while querying storage.".to_vec(),
+                    Some(format!("{:?}", e)),
+                ))?
+            .map_err(Error::ContractGetStorageError)?
+            .into_iter()
+            .map(|value| value.map(Bytes))
+            .collect();
+
+        Ok(get_storage_result)
+    }
+
     fn contract_xrc20_call(
         &self,
         call_request: XRC20CallRequest,
@@ -1125,8 +1179,95 @@ where
         }
         Ok(b)
     }
+
+    fn contract_tombstones(
+        &self,
+        offset: u32,
+        limit: u32,
+        hash: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<AccountIdForRpc>> {
+        let tombstones = self.tombstone_contracts(self.block_id_by_hash(hash)?, offset, limit)?;
+        Ok(tombstones.into_iter().map(Into::into).collect())
+    }
+
+    fn contract_decode_event(
+        &self,
+        address: AccountIdForRpc,
+        data: Bytes,
+        hash: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Value> {
+        let data = data.to_vec();
+        let decoded = self.decode_contract_event(
+            self.block_id_by_hash(hash)?,
+            address.unchecked_into(),
+            data.clone(),
+        )?;
+        Ok(parse_contract_event(decoded, data))
+    }
+
+    fn system_health(&self, hash: Option<<Block as BlockT>::Hash>) -> Result<SystemHealth> {
+        let state = self.state_at(hash)?;
+
+        // walk back from the best header to the latest confirmed one
+        let key = <xbitcoin::BestIndex<Runtime>>::key();
+        let btc_heights = match Self::pickout::<H256>(&state, &key, Hasher::TWOX128)? {
+            Some(best_hash) => {
+                let mut current = best_hash;
+                let mut best_height = None;
+                let mut confirmed_height = None;
+                for _ in 0..MAX_HEADERS_TO_CONFIRMATION {
+                    let key = <xbitcoin::BlockHeaderFor<Runtime>>::key_for(&current);
+                    match Self::pickout::<xbitcoin::BlockHeaderInfo>(
+                        &state,
+                        &key,
+                        Hasher::BLAKE2256,
+                    )? {
+                        Some(info) => {
+                            best_height.get_or_insert(info.height);
+                            if info.confirmed {
+                                confirmed_height = Some(info.height);
+                                break;
+                            }
+                            current = info.header.previous_header_hash;
+                        }
+                        None => break,
+                    }
+                }
+                best_height.map(|best| (best, confirmed_height.unwrap_or_default()))
+            }
+            None => None,
+        };
+
+        let key = <xcontracts::LastGasSpent<Runtime>>::key();
+        let gas_spent =
+            Self::pickout::<xcontracts::Gas>(&state, &key, Hasher::TWOX128)?.unwrap_or_default();
+        let block_gas_limit = <Runtime as xcontracts::Trait>::BlockGasLimit::get();
+
+        let key = <xsession::Validators<Runtime>>::key();
+        let validators = Self::pickout::<Vec<(AccountId, u64)>>(&state, &key, Hasher::TWOX128)?
+            .unwrap_or_default();
+
+        let key = <xfee_manager::Switcher<Runtime>>::key();
+        let switcher = Self::pickout::<BTreeMap<xfee_manager::CallSwitcher, bool>>(
+            &state,
+            &key,
+            Hasher::TWOX128,
+        )?
+        .unwrap_or_default();
+
+        Ok(SystemHealth::new(
+            btc_heights,
+            gas_spent,
+            block_gas_limit,
+            validators.len() as u32,
+            &switcher,
+        ))
+    }
 }
 
+/// Upper bound of the bitcoin headers visited when looking for the confirmed one.
+const MAX_HEADERS_TO_CONFIRMATION: usize = 256;
+
 fn into_pagedata<T>(src: Vec<T>, page_index: u32, page_size: u32) -> Result<Option<PageData<T>>> {
     if page_size == 0 {
         return Err(Error::PageSizeErr(page_size));
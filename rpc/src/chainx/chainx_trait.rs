@@ -179,6 +179,9 @@ pub trait ChainXApi<Number, Hash, AccountId, Balance, BlockNumber, SignedBlock>
     #[rpc(name = "chainx_getFeeWeightMap")]
     fn fee_weight_map(&self, hash: Option<Hash>) -> Result<Value>;
 
+    #[rpc(name = "chainx_getFeeSwitcher")]
+    fn fee_switcher(&self, hash: Option<Hash>) -> Result<BTreeMap<String, bool>>;
+
     #[rpc(name = "chainx_getWithdrawTx")]
     fn withdraw_tx(&self, chain: Chain, hash: Option<Hash>) -> Result<Option<WithdrawTxInfo>>;
 
@@ -208,6 +211,14 @@ pub trait ChainXApi<Number, Hash, AccountId, Balance, BlockNumber, SignedBlock>
         at: Option<Hash>,
     ) -> Result<Option<Bytes>>;
 
+    #[rpc(name = "chainx_contractGetStorageMulti")]
+    fn contract_get_storage_multi(
+        &self,
+        address: AccountId,
+        keys: Vec<H256>,
+        at: Option<Hash>,
+    ) -> Result<Vec<Option<Bytes>>>;
+
     #[rpc(name = "chainx_contractXRC20Call")]
     fn contract_xrc20_call(
         &self,
@@ -217,4 +228,23 @@ pub trait ChainXApi<Number, Hash, AccountId, Balance, BlockNumber, SignedBlock>
 
     #[rpc(name = "chainx_contractXRCTokenInfo")]
     fn contract_xrc_token_info(&self, at: Option<Hash>) -> Result<BTreeMap<String, Value>>;
+
+    #[rpc(name = "chainx_contractTombstones")]
+    fn contract_tombstones(
+        &self,
+        offset: u32,
+        limit: u32,
+        at: Option<Hash>,
+    ) -> Result<Vec<AccountIdForRpc>>;
+
+    #[rpc(name = "chainx_contractDecodeEvent")]
+    fn contract_decode_event(
+        &self,
+        address: AccountId,
+        data: Bytes,
+        at: Option<Hash>,
+    ) -> Result<Value>;
+
+    #[rpc(name = "chainx_getSystemHealth")]
+    fn system_health(&self, hash: Option<Hash>) -> Result<SystemHealth>;
 }
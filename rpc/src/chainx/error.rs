@@ -148,6 +148,11 @@ impl From<Error> for rpc::Error {
                         message: "The contract is a tombstone and doesn't have any storage.".into(),
                         data: None,
                     },
+                    TooManyKeys => rpc::Error {
+                        code: rpc::ErrorCode::ServerError(ERROR + 102),
+                        message: "Too many storage keys are queried at once.".into(),
+                        data: None,
+                    },
                 }
             }
             e => errors::internal(e),
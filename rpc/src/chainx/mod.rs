@@ -7,6 +7,8 @@ mod utils;
 mod chainx_impl;
 mod chainx_trait;
 mod error;
+#[cfg(test)]
+mod tests;
 mod types;
 
 use std::collections::btree_map::BTreeMap;
@@ -212,6 +214,10 @@ where
         fn trustee_props_for(who: AccountId) -> BTreeMap<Chain, GenericTrusteeIntentionProps>;
         fn trustee_session_info_for(chain: Chain, number: Option<u32>) -> Option<(u32, GenericAllSessionInfo<AccountId>)>;
         fn trustee_session_info() -> BTreeMap<xassets::Chain, GenericAllSessionInfo<AccountId>>;
+
+        // XContractsApi
+        fn tombstone_contracts(offset: u32, limit: u32) -> Vec<AccountId>;
+        fn decode_contract_event(address: AccountId, data: Vec<u8>) -> Option<(Vec<u8>, Vec<(Vec<u8>, xr_primitives::ContractEventFieldValue)>)>;
     }
 
     /////////////////////////////////////////////////////////////////////////
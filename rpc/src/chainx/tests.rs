@@ -0,0 +1,46 @@
+// Copyright 2018-2019 Chainpool.
+
+use serde_json::json;
+
+use xr_primitives::ContractEventFieldValue;
+
+use super::utils::parse_contract_event;
+
+#[test]
+fn parse_contract_event_should_work() {
+    let data = vec![1, 2, 3, 4, 5];
+
+    let decoded = Some((
+        b"Transfer".to_vec(),
+        vec![
+            (
+                b"to".to_vec(),
+                ContractEventFieldValue::AccountId([7u8; 32]),
+            ),
+            (
+                b"value".to_vec(),
+                ContractEventFieldValue::U128(u128::max_value()),
+            ),
+            (b"ok".to_vec(), ContractEventFieldValue::Bool(true)),
+        ],
+    ));
+    assert_eq!(
+        parse_contract_event(decoded, data.clone()),
+        json!({
+            "name": "Transfer",
+            "fields": {
+                "to": format!("0x{}", "07".repeat(32)),
+                "value": u128::max_value().to_string(),
+                "ok": true,
+            },
+        })
+    );
+}
+
+#[test]
+fn parse_undecoded_contract_event_should_fall_back_to_raw_hex() {
+    assert_eq!(
+        parse_contract_event(None, vec![1, 2, 3, 4, 5]),
+        json!({ "raw": "0x0102030405" })
+    );
+}
@@ -170,7 +170,7 @@ pub struct DepositLimit {
 pub struct WithdrawInfo {
     pub height: BlockNumber,
     ///id
-    pub id: u32,
+    pub id: u64,
     /// txid
     pub txid: String,
     /// withdraw-balance
@@ -240,7 +240,7 @@ pub struct WithdrawTxInfo {
     pub tx: String,
     /// sign_status
     pub sign_status: bool,
-    pub withdrawal_id_list: Vec<u32>,
+    pub withdrawal_id_list: Vec<u64>,
     pub trustee_list: Vec<(AccountId, bool)>,
 }
 impl WithdrawTxInfo {
@@ -340,3 +340,85 @@ pub struct XRC20CallRequest {
     pub selector: XRC20Selector,
     pub input_data: Bytes,
 }
+
+/// Aggregated liveness of the bridge, contracts and staking modules.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemHealth {
+    /// Blocks between the best bitcoin header and the latest confirmed one.
+    pub btc_confirmation_lag: u32,
+    pub gas_spent: u64,
+    pub block_gas_limit: u64,
+    /// `gas_spent / block_gas_limit` in permill.
+    pub gas_usage_permill: u32,
+    pub active_validator_count: u32,
+    /// Calls which are currently paused by the fee switcher.
+    pub paused_calls: Vec<String>,
+}
+
+impl SystemHealth {
+    /// `btc_heights` is `(best height, latest confirmed height)`, a missing
+    /// relay is treated as having no lag.
+    pub fn new(
+        btc_heights: Option<(u32, u32)>,
+        gas_spent: u64,
+        block_gas_limit: u64,
+        active_validator_count: u32,
+        switcher: &BTreeMap<xfee_manager::CallSwitcher, bool>,
+    ) -> Self {
+        let btc_confirmation_lag = btc_heights
+            .map(|(best, confirmed)| best.saturating_sub(confirmed))
+            .unwrap_or_default();
+        let gas_usage_permill = if block_gas_limit == 0 {
+            0
+        } else {
+            (u128::from(gas_spent) * 1_000 / u128::from(block_gas_limit)).min(1_000) as u32
+        };
+        let paused_calls = switcher
+            .iter()
+            .filter(|(_, paused)| **paused)
+            .map(|(call, _)| format!("{:?}", call))
+            .collect();
+        SystemHealth {
+            btc_confirmation_lag,
+            gas_spent,
+            block_gas_limit,
+            gas_usage_permill,
+            active_validator_count,
+            paused_calls,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        !self.paused_calls.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xfee_manager::CallSwitcher;
+
+    #[test]
+    fn system_health_should_reflect_the_state() {
+        let health = SystemHealth::new(None, 0, 0, 0, &BTreeMap::new());
+        assert_eq!(health, SystemHealth::default());
+        assert!(!health.is_paused());
+
+        let mut switcher = BTreeMap::new();
+        switcher.insert(CallSwitcher::XBTC, true);
+        switcher.insert(CallSwitcher::Spot, false);
+        let health = SystemHealth::new(
+            Some((576_010, 576_004)),
+            2_500_000,
+            10_000_000,
+            21,
+            &switcher,
+        );
+        assert_eq!(health.btc_confirmation_lag, 6);
+        assert_eq!(health.gas_usage_permill, 250);
+        assert_eq!(health.active_validator_count, 21);
+        assert_eq!(health.paused_calls, vec!["XBTC".to_string()]);
+        assert!(health.is_paused());
+    }
+}
@@ -13,7 +13,7 @@ use btc_keys::DisplayLayout;
 
 // chainx
 use chainx_primitives::AccountIdForRpc;
-use xr_primitives::generic::b58;
+use xr_primitives::{generic::b58, ContractEventFieldValue};
 
 use xbridge_common::{
     traits::IntoVecu8,
@@ -138,6 +138,37 @@ pub fn parse_trustee_session_info(
     }))
 }
 
+/// Convert the decoded contract event into json, or the raw hex of the payload if not decoded.
+pub fn parse_contract_event(
+    decoded: Option<(Vec<u8>, Vec<(Vec<u8>, ContractEventFieldValue)>)>,
+    data: Vec<u8>,
+) -> Value {
+    match decoded {
+        Some((name, fields)) => {
+            let fields = fields
+                .into_iter()
+                .map(|(field, value)| {
+                    let value = match value {
+                        ContractEventFieldValue::Bool(v) => json!(v),
+                        ContractEventFieldValue::U32(v) => json!(v),
+                        ContractEventFieldValue::U64(v) => json!(v),
+                        // u128 may exceed the precision of json number
+                        ContractEventFieldValue::U128(v) => json!(v.to_string()),
+                        ContractEventFieldValue::AccountId(v) => json!(Bytes(v.to_vec())),
+                        ContractEventFieldValue::Bytes(v) => json!(Bytes(v)),
+                    };
+                    (to_string!(&field), value)
+                })
+                .collect::<Map<_, _>>();
+            json!({
+                "name": to_string!(&name),
+                "fields": fields,
+            })
+        }
+        None => json!({ "raw": Bytes(data) }),
+    }
+}
+
 pub fn calculate_staking_dividend(
     record_v1: &xstaking::NominationRecordV1<Balance, BlockNumber>,
     intention_profs_v1: &xstaking::IntentionProfsV1<Balance, BlockNumber>,
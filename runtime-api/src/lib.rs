@@ -115,7 +115,10 @@ pub mod xbridge_api {
 pub mod xcontracts_api {
     use super::*;
     use xassets::Token;
-    use xr_primitives::{ContractExecResult, GetStorageResult, XRC20Selector};
+    use xr_primitives::{
+        ContractEventFieldValue, ContractExecResult, GetStorageMultiResult, GetStorageResult,
+        XRC20Selector,
+    };
 
     decl_runtime_apis! {
         /// The API to interact with contracts without using executive.
@@ -140,11 +143,27 @@ pub mod xcontracts_api {
             /// is returned.
             fn get_storage(address: AccountIdForApi, key: [u8; 32]) -> GetStorageResult;
 
+            /// Query the given storage keys in a given contract, the values are in the order of keys.
+            fn get_storage_multi(
+                address: AccountIdForApi,
+                keys: Vec<[u8; 32]>,
+            ) -> GetStorageMultiResult;
+
             fn xrc20_call(
                 token: Token,
                 selector: XRC20Selector,
                 data: Vec<u8>,
             ) -> ContractExecResult;
+
+            /// List the tombstone contracts which could be restored.
+            fn tombstone_contracts(offset: u32, limit: u32) -> Vec<AccountIdForApi>;
+
+            /// Decode the payload of a contract event by the layout registered for the contract,
+            /// returns the event name and the named fields.
+            fn decode_contract_event(
+                address: AccountIdForApi,
+                data: Vec<u8>,
+            ) -> Option<(Vec<u8>, Vec<(Vec<u8>, ContractEventFieldValue)>)>;
         }
     }
 }
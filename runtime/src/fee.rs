@@ -19,17 +19,25 @@ use xspot::Call as XSpotCall;
 use xstaking::Call as XStakingCall;
 use xtokens::Call as XTokensCall;
 
-use crate::Call;
+use chainx_primitives::AccountId;
 
-pub trait CheckFee {
+use crate::{Call, Runtime};
+
+pub trait CheckFee<AccountId> {
     fn check_fee(
         &self,
         switcher: BTreeMap<CallSwitcher, bool>,
         method_weight_map: BTreeMap<XString, u64>,
     ) -> Option<u64>;
+
+    /// Whether the fee power of this call could be discounted by the staking of the caller.
+    fn is_claim(&self) -> bool;
+
+    /// The number of the claims made by this call of `who`, the fee power is charged per claim.
+    fn claim_count(&self, who: &AccountId) -> u64;
 }
 
-impl CheckFee for Call {
+impl CheckFee<AccountId> for Call {
     /// Return fee_power, which is part of the total_fee.
     /// total_fee = base_fee * fee_power + byte_fee * bytes
     ///
@@ -84,6 +92,24 @@ impl CheckFee for Call {
         }
         call_weight_func(&self, &method_weight_map)
     }
+
+    fn is_claim(&self) -> bool {
+        match self {
+            Call::XStaking(XStakingCall::claim(..))
+            | Call::XTokens(XTokensCall::claim(..))
+            | Call::XTokens(XTokensCall::claim_everything(..)) => true,
+            _ => false,
+        }
+    }
+
+    fn claim_count(&self, who: &AccountId) -> u64 {
+        match self {
+            Call::XTokens(XTokensCall::claim_everything(..)) => {
+                u64::from(xtokens::Module::<Runtime>::claim_everything_count(who)).max(1)
+            }
+            _ => 1,
+        }
+    }
 }
 
 #[macro_export]
@@ -146,7 +172,9 @@ match_method_call_func! {
 
     XAssetsProcess, XAssetsProcessCall => (
         withdraw : 3,
+        withdraw_with_reference : 3,
         revoke_withdraw : 10,
+        amend_withdrawal : 3,
     );
 
     XBridgeOfBTC, XBitcoinCall => (
@@ -168,10 +196,15 @@ match_method_call_func! {
         register : 100_000,
         unnominate : 3,
         renominate : 800,
+        set_auto_kick : 10,
+        set_reward_split : 10,
+        set_auto_claim_on_unnominate : 10,
+        register_with_self_bond : 100_000,
     );
 
     XTokens, XTokensCall => (
         claim : 3,
+        claim_everything : 3,
     );
 
     XSpot, XSpotCall => (
@@ -195,6 +228,16 @@ match_method_call_func! {
         put_code : 250,
         call : 10,
         instantiate : 500,
+        put_code_and_instantiate : 750,
         convert_to_xrc20: 10,
+        call_sponsored : 10,
+        call_with_token : 10,
+        fund_contract_gas : 1,
+        withdraw_contract_gas : 1,
+        set_sponsor : 1,
+        set_gas_refund_policy : 1,
+        set_call_limit : 1,
+        set_contract_event_layout : 1,
+        claim_surcharge : 1,
     );
 }
@@ -46,7 +46,10 @@ use version::RuntimeVersion;
 use chainx_primitives;
 use runtime_api;
 use xgrandpa::fg_primitives::{self, ScheduledChange};
-pub use xr_primitives::{AddrStr, ContractExecResult, GetStorageError, GetStorageResult};
+pub use xr_primitives::{
+    AddrStr, ContractEventFieldValue, ContractExecResult, GetStorageError, GetStorageMultiResult,
+    GetStorageResult,
+};
 
 // chainx
 use chainx_primitives::{
@@ -163,6 +166,7 @@ impl xfee_manager::Trait for Runtime {
 impl xassets::Trait for Runtime {
     type Balance = Balance;
     type OnNewAccount = Indices;
+    type OnFreeBalanceZero = XContracts;
     type Event = Event;
     type OnAssetChanged = XTokens;
     type OnAssetRegisterOrRevoke = (XTokens, XSpot);
@@ -178,7 +182,9 @@ impl xfisher::Trait for Runtime {
     type CheckHeader = HeaderChecker;
 }
 
-impl xprocess::Trait for Runtime {}
+impl xprocess::Trait for Runtime {
+    type Event = Event;
+}
 
 impl xstaking::Trait for Runtime {
     type Event = Event;
@@ -264,6 +270,13 @@ impl
     }
 }
 
+pub struct AssetPowerComputor;
+impl xcontracts::AssetPowerFor<<Runtime as xassets::Trait>::Balance> for AssetPowerComputor {
+    fn asset_power(token: &xassets::Token) -> Option<<Runtime as xassets::Trait>::Balance> {
+        XTokens::asset_power(token)
+    }
+}
+
 impl xcontracts::Trait for Runtime {
     type Call = Call;
     type Event = Event;
@@ -271,13 +284,18 @@ impl xcontracts::Trait for Runtime {
     type ComputeDispatchFee = DispatchFeeComputor;
     type TrieIdGenerator = xcontracts::TrieIdFromParentCounter<Runtime>;
     type SignedClaimHandicap = xcontracts::DefaultSignedClaimHandicap;
+    type SurchargeReward = SurchargeReward;
     type TombstoneDeposit = TombstoneDeposit;
     type StorageSizeOffset = xcontracts::DefaultStorageSizeOffset;
     type RentByteFee = RentByteFee;
     type RentDepositOffset = RentDepositOffset;
     type MaxDepth = xcontracts::DefaultMaxDepth;
     type MaxValueSize = xcontracts::DefaultMaxValueSize;
+    type MaxWriteBytesPerCall = xcontracts::DefaultMaxWriteBytesPerCall;
+    type MaxRestoreDelta = xcontracts::DefaultMaxRestoreDelta;
     type BlockGasLimit = xcontracts::DefaultBlockGasLimit;
+    type MaxGasPerExtrinsic = xcontracts::DefaultMaxGasPerExtrinsic;
+    type AssetPower = AssetPowerComputor;
 }
 
 pub struct HeaderChecker;
@@ -354,7 +372,7 @@ construct_runtime!(
         // assets
         XAssets: xassets,
         XAssetsRecords: xrecords::{Module, Call, Storage, Event<T>},
-        XAssetsProcess: xprocess::{Module, Call, Storage},
+        XAssetsProcess: xprocess::{Module, Call, Storage, Event<T>},
         // mining
         XStaking: xstaking,
         XTokens: xtokens::{Module, Call, Storage, Event<T>, Config<T>},
@@ -675,6 +693,22 @@ impl_runtime_apis! {
                 match rpc_err {
                     xcontracts::GetStorageError::ContractDoesntExist => RpcGetStorageError::ContractDoesntExist,
                     xcontracts::GetStorageError::IsTombstone => RpcGetStorageError::IsTombstone,
+                    xcontracts::GetStorageError::TooManyKeys => RpcGetStorageError::TooManyKeys,
+                }
+            })
+        }
+
+        fn get_storage_multi(
+            address: AccountId,
+            keys: Vec<[u8; 32]>,
+        ) -> GetStorageMultiResult {
+            XContracts::get_storage_multi(address, keys).map_err(|rpc_err| {
+                use GetStorageError as RpcGetStorageError;
+                // Map the contract error into the RPC layer error.
+                match rpc_err {
+                    xcontracts::GetStorageError::ContractDoesntExist => RpcGetStorageError::ContractDoesntExist,
+                    xcontracts::GetStorageError::IsTombstone => RpcGetStorageError::IsTombstone,
+                    xcontracts::GetStorageError::TooManyKeys => RpcGetStorageError::TooManyKeys,
                 }
             })
         }
@@ -697,5 +731,16 @@ impl_runtime_apis! {
                 Err(e) => ContractExecResult::Error(e.reason.as_bytes().to_vec()),
             }
         }
+
+        fn tombstone_contracts(offset: u32, limit: u32) -> Vec<AccountId> {
+            XContracts::tombstone_contracts(offset, limit)
+        }
+
+        fn decode_contract_event(
+            address: AccountId,
+            data: Vec<u8>,
+        ) -> Option<(Vec<u8>, Vec<(Vec<u8>, ContractEventFieldValue)>)> {
+            XContracts::decode_contract_event(address, data)
+        }
     }
 }
@@ -76,7 +76,7 @@ impl<
 > ExecuteBlock<Block> for Executive<System, Block, Context, Payment, AllModules> where
     Block::Extrinsic: Checkable<Context> + Codec,
     <Block::Extrinsic as Checkable<Context>>::Checked: Applyable<Index=System::Index, AccountId=System::AccountId> + Accelerable<Index=System::Index, AccountId=System::AccountId>,
-    <<Block::Extrinsic as Checkable<Context>>::Checked as Applyable>::Call: Dispatchable + CheckFee,
+    <<Block::Extrinsic as Checkable<Context>>::Checked as Applyable>::Call: Dispatchable + CheckFee<System::AccountId>,
     <<<Block::Extrinsic as Checkable<Context>>::Checked as Applyable>::Call as Dispatchable>::Origin: From<Option<System::AccountId>>
 {
     fn execute_block(block: Block) {
@@ -93,7 +93,7 @@ impl<
 > Executive<System, Block, Context, Payment, AllModules> where
     Block::Extrinsic: Checkable<Context> + Codec,
     <Block::Extrinsic as Checkable<Context>>::Checked: Applyable<Index=System::Index, AccountId=System::AccountId> + Accelerable<Index=System::Index, AccountId=System::AccountId>,
-    <<Block::Extrinsic as Checkable<Context>>::Checked as Applyable>::Call: Dispatchable + CheckFee,
+    <<Block::Extrinsic as Checkable<Context>>::Checked as Applyable>::Call: Dispatchable + CheckFee<System::AccountId>,
     <<<Block::Extrinsic as Checkable<Context>>::Checked as Applyable>::Call as Dispatchable>::Origin: From<Option<System::AccountId>>
 {
     /// Start the execution of a particular block.
@@ -233,6 +233,12 @@ impl<
             let switcher = <xfee_manager::Module<System>>::switcher();
             let method_call_weight = <xfee_manager::Module<System>>::method_call_weight();
             if let Some(weight) = f.check_fee(switcher, method_call_weight) {
+                let weight = if f.is_claim() {
+                    let who = s.clone().unwrap();
+                    <xfee_manager::Module<System>>::claim_fee_power(&who, weight * f.claim_count(&who))
+                } else {
+                    weight
+                };
                 // pay any fees.
                 Payment::make_payment(&s.clone().unwrap(), encoded_len, weight, acc.into()).map_err(|_| internal::ApplyError::CantPay)?;
 
@@ -363,6 +369,12 @@ impl<
         let switcher = <xfee_manager::Module<System>>::switcher();
         let method_call_weight = <xfee_manager::Module<System>>::method_call_weight();
         if let Some(fee_power) = f.check_fee(switcher, method_call_weight) {
+            let fee_power = if f.is_claim() {
+                let who = s.clone().unwrap();
+                <xfee_manager::Module<System>>::claim_fee_power(&who, fee_power * f.claim_count(&who))
+            } else {
+                fee_power
+            };
             if Payment::check_payment(&s.clone().unwrap(), encoded_len, fee_power, acc.into()).is_err() {
                 TransactionValidity::Invalid(ApplyError::CantPay as i8)
             } else {
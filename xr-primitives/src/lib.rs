@@ -47,6 +47,10 @@ pub enum ContractExecResult {
 ///
 /// See [`ContractsApi::get_storage`] for more info.
 pub type GetStorageResult = Result<Option<Vec<u8>>, GetStorageError>;
+/// A result type of the get storage call with multiple keys.
+///
+/// See [`ContractsApi::get_storage_multi`] for more info.
+pub type GetStorageMultiResult = Result<Vec<Option<Vec<u8>>>, GetStorageError>;
 /// The possible errors that can happen querying the storage of a contract.
 #[derive(Eq, PartialEq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
@@ -55,6 +59,8 @@ pub enum GetStorageError {
     ContractDoesntExist,
     /// The specified contract is a tombstone and thus cannot have any storage.
     IsTombstone,
+    /// Too many keys are queried at once.
+    TooManyKeys,
 }
 
 #[cfg(feature = "std")]
@@ -75,3 +81,83 @@ pub enum XRC20Selector {
     Issue,
     Destroy,
 }
+
+#[derive(PartialEq, PartialOrd, Ord, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum XRC777Selector {
+    BalanceOf,
+    TotalSupply,
+    Name,
+    Symbol,
+    Granularity,
+    Send,
+    Mint,
+    Burn,
+}
+
+/// The type of a field in the payload of a contract event.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum ContractEventFieldType {
+    Bool,
+    U32,
+    U64,
+    U128,
+    /// 32 bytes account id.
+    AccountId,
+    /// Length prefixed bytes.
+    Bytes,
+}
+
+/// A decoded field value of a contract event.
+#[derive(PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum ContractEventFieldValue {
+    Bool(bool),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    AccountId([u8; 32]),
+    Bytes(Vec<u8>),
+}
+
+/// The layout of a contract event, the payload is the SCALE encoded fields in order.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ContractEventLayout {
+    pub name: XString,
+    pub fields: Vec<(XString, ContractEventFieldType)>,
+}
+
+impl ContractEventLayout {
+    /// Decode the payload into named fields, `None` if the payload doesn't match the layout.
+    pub fn decode(&self, payload: &[u8]) -> Option<Vec<(XString, ContractEventFieldValue)>> {
+        let input = &mut &payload[..];
+        let mut fields = Vec::with_capacity(self.fields.len());
+        for (name, type_) in self.fields.iter() {
+            let value = match type_ {
+                ContractEventFieldType::Bool => {
+                    ContractEventFieldValue::Bool(Decode::decode(input)?)
+                }
+                ContractEventFieldType::U32 => ContractEventFieldValue::U32(Decode::decode(input)?),
+                ContractEventFieldType::U64 => ContractEventFieldValue::U64(Decode::decode(input)?),
+                ContractEventFieldType::U128 => {
+                    ContractEventFieldValue::U128(Decode::decode(input)?)
+                }
+                ContractEventFieldType::AccountId => {
+                    ContractEventFieldValue::AccountId(Decode::decode(input)?)
+                }
+                ContractEventFieldType::Bytes => {
+                    ContractEventFieldValue::Bytes(Decode::decode(input)?)
+                }
+            };
+            fields.push((name.clone(), value));
+        }
+        // all the bytes of payload should be consumed
+        if !input.is_empty() {
+            return None;
+        }
+        Some(fields)
+    }
+}
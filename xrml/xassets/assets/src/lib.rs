@@ -27,7 +27,7 @@ use rstd::{
 };
 use substrate_primitives::crypto::UncheckedFrom;
 
-use support::traits::{Imbalance, SignedImbalance};
+use support::traits::{Imbalance, OnFreeBalanceZero, SignedImbalance};
 use support::{decl_event, decl_module, decl_storage, dispatch::Result, Parameter, StorageMap};
 use system::{ensure_signed, IsDeadAccount, OnNewAccount};
 
@@ -93,6 +93,8 @@ pub trait Trait: system::Trait {
         + MaybeSerializeDebug;
     /// Handler for when a new account is created.
     type OnNewAccount: OnNewAccount<Self::AccountId>;
+    /// Handler for when the PCX free balance of an account drops to zero.
+    type OnFreeBalanceZero: OnFreeBalanceZero<Self::AccountId>;
     /// Event
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
@@ -453,6 +455,14 @@ impl<T: Trait> Module<T> {
         Self::asset_type_balance(&(who.clone(), token.clone()), type_)
     }
 
+    /// The non-zero balances of the account other than `Free`, in the order of `AssetType`.
+    pub fn reserved_breakdown(who: &T::AccountId, token: &Token) -> Vec<(AssetType, T::Balance)> {
+        Self::asset_balance(&(who.clone(), token.clone()))
+            .into_iter()
+            .filter(|(type_, balance)| *type_ != AssetType::Free && !balance.is_zero())
+            .collect()
+    }
+
     pub fn free_balance_of(who: &T::AccountId, token: &Token) -> T::Balance {
         Self::asset_type_balance(&(who.clone(), token.clone()), AssetType::Free)
     }
@@ -509,6 +519,21 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Destroy the `GasPayment` balance of the account, i.e. the spent gas of the contracts paid
+    /// in a token other than PCX.
+    pub fn destroy_gas_payment(token: &Token, who: &T::AccountId, value: T::Balance) -> Result {
+        {
+            Self::should_not_chainx(token)?;
+            Self::is_valid_asset(token)?;
+
+            let type_ = AssetType::GasPayment;
+
+            debug!("[destroy_gas_payment]|destroy the spent gas paid in token");
+            let _imbalance = Self::inner_destroy(token, who, type_, value)?;
+        }
+        Ok(())
+    }
+
     fn new_account(who: &T::AccountId) {
         T::OnNewAccount::on_new_account(&who);
         // set empty balance for pcx
@@ -636,6 +661,7 @@ impl<T: Trait> Module<T> {
         };
 
         AssetTriggerEventAfter::<T>::on_destroy(token, who, value)?;
+        Self::try_on_free_balance_zero(token, who, type_, current, new);
         Ok(negative)
     }
 
@@ -700,6 +726,7 @@ impl<T: Trait> Module<T> {
         let to_imbalance = Self::make_type_balance_be(&to_key, to_type, new_to_balance);
 
         AssetTriggerEventAfter::<T>::on_move(token, from, from_type, to, to_type, value)?;
+        Self::try_on_free_balance_zero(token, from, from_type, from_balance, new_from_balance);
 
         Ok((from_imbalance, to_imbalance))
     }
@@ -729,9 +756,28 @@ impl<T: Trait> Module<T> {
             let _imbalance = Self::make_type_balance_be(&key, type_, val);
 
             AssetTriggerEventAfter::<T>::on_set_balance(token, who, type_, val)?;
+            Self::try_on_free_balance_zero(token, who, type_, old_val, val);
         }
         Ok(())
     }
+
+    /// Notify `OnFreeBalanceZero` after the balance is changed, if the PCX free balance of the
+    /// account drops to zero.
+    fn try_on_free_balance_zero(
+        token: &Token,
+        who: &T::AccountId,
+        type_: AssetType,
+        original: T::Balance,
+        current: T::Balance,
+    ) {
+        if type_ == AssetType::Free
+            && token.as_slice() == <Self as ChainT>::TOKEN
+            && !original.is_zero()
+            && current.is_zero()
+        {
+            T::OnFreeBalanceZero::on_free_balance_zero(who);
+        }
+    }
 }
 
 // wrapper for balances module
@@ -756,6 +802,15 @@ impl<T: Trait> Module<T> {
         Self::issue(&Self::TOKEN.to_vec(), who, value)
     }
 
+    /// Destroy the PCX `GasPayment` balance of the account, i.e. the spent gas of the contracts
+    /// which is not paid to the council.
+    pub fn pcx_destroy_gas_payment(who: &T::AccountId, value: T::Balance) -> Result {
+        debug!("[pcx_destroy_gas_payment]|destroy the spent gas paid in pcx");
+        let _imbalance =
+            Self::inner_destroy(&Self::TOKEN.to_vec(), who, AssetType::GasPayment, value)?;
+        Ok(())
+    }
+
     pub fn pcx_move_balance(
         from: &T::AccountId,
         from_type: AssetType,
@@ -785,6 +840,7 @@ impl<T: Trait> Module<T> {
     pub fn pcx_make_free_balance_be(who: &T::AccountId, value: T::Balance) -> SignedImbalanceT<T> {
         let key = (who.clone(), <Self as ChainT>::TOKEN.to_vec());
         Self::try_new_account(&key);
+        let original = Self::asset_type_balance(&key, AssetType::Free);
         let imbalance = Self::make_type_balance_be(&key, AssetType::Free, value);
         let b = match imbalance {
             SignedImbalance::Positive(ref p) => SignedBalance::Positive(p.peek()),
@@ -796,6 +852,13 @@ impl<T: Trait> Module<T> {
             AssetType::Free,
             b,
         ));
+        Self::try_on_free_balance_zero(
+            &<Self as ChainT>::TOKEN.to_vec(),
+            who,
+            AssetType::Free,
+            original,
+            value,
+        );
         imbalance
     }
 }
@@ -53,6 +53,7 @@ impl Trait for Test {
     /// Event
     type Balance = Balance;
     type OnNewAccount = Indices;
+    type OnFreeBalanceZero = ();
     type Event = ();
     type OnAssetChanged = ();
     type OnAssetRegisterOrRevoke = ();
@@ -847,3 +847,43 @@ fn test_move() {
         assert_eq!(XAssets::free_balance_of(&b, &token), 100);
     })
 }
+
+#[test]
+fn test_reserved_breakdown() {
+    with_externalities(&mut new_test_ext(), || {
+        let a: u64 = 1; // accountid
+        let token = XAssets::TOKEN.to_vec();
+        assert!(XAssets::reserved_breakdown(&a, &token).is_empty());
+
+        for (type_, value) in [
+            (AssetType::ReservedWithdrawal, 50),
+            (AssetType::ReservedStaking, 30),
+        ]
+        .iter()
+        {
+            XAssets::move_balance(&token, &a, AssetType::Free, &a, *type_, *value).unwrap();
+        }
+        assert_eq!(
+            XAssets::reserved_breakdown(&a, &token),
+            vec![
+                (AssetType::ReservedStaking, 30),
+                (AssetType::ReservedWithdrawal, 50)
+            ]
+        );
+
+        // the emptied bucket is not shown.
+        XAssets::move_balance(
+            &token,
+            &a,
+            AssetType::ReservedStaking,
+            &a,
+            AssetType::Free,
+            30,
+        )
+        .unwrap();
+        assert_eq!(
+            XAssets::reserved_breakdown(&a, &token),
+            vec![(AssetType::ReservedWithdrawal, 50)]
+        );
+    })
+}
@@ -156,6 +156,8 @@ define_enum!(
         ReservedDexSpot,
         ReservedDexFuture,
         ReservedCurrency,
+        // Reserved by the token contract instance, either xrc20 or xrc777, for the converted
+        // balance. The name is kept for the encoding and the rpc of the existing chain.
         ReservedXRC20,
         GasPayment,
     }
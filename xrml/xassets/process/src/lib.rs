@@ -13,16 +13,26 @@ use parity_codec::{Decode, Encode};
 use serde_derive::{Deserialize, Serialize};
 
 // Substrate
+use primitives::traits::Zero;
 use rstd::prelude::Vec;
-use support::{decl_module, decl_storage, dispatch::Result, StorageValue};
+use support::{decl_event, decl_module, decl_storage, dispatch::Result, StorageMap, StorageValue};
 use system::ensure_signed;
 
 // ChainX
 use xassets::{Chain, ChainT, Memo, Token};
 use xr_primitives::AddrStr;
+use xsupport::{debug, ensure_with_errorlog, warn};
 #[cfg(feature = "std")]
-use xsupport::token;
-use xsupport::{debug, ensure_with_errorlog};
+use xsupport::{token, try_hex_or_str};
+
+/// Max number of recent withdrawal addresses tracked for each account and token.
+pub const MAX_TRACKED_WITHDRAWAL_ADDRESSES: usize = 32;
+
+/// Default max memo size of the withdrawals on Bitcoin, which is the capacity of OP_RETURN.
+pub const DEFAULT_BITCOIN_MAX_MEMO_SIZE: u32 = 80;
+
+/// Max number of the allowed or denied withdrawal address prefixes of each token.
+pub const MAX_ADDRESS_PREFIXES: usize = 16;
 
 #[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
@@ -32,61 +42,227 @@ pub struct WithdrawalLimit<Balance> {
     pub fee: Balance,
 }
 
-pub trait Trait: xassets::Trait + xrecords::Trait + xbitcoin::Trait {}
+pub trait Trait: xassets::Trait + xrecords::Trait + xbitcoin::Trait {
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
 
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event<T>() = default;
+
         fn withdraw(origin, token: Token, value: T::Balance, addr: AddrStr, ext: Memo) -> Result {
             let who = ensure_signed(origin)?;
+            Self::apply_withdraw(who, token, value, addr, ext, None)
+        }
 
-            Self::can_withdraw(&token)?;
+        /// Withdraw with a reference id, which is kept with the application until it's finished.
+        fn withdraw_with_reference(origin, token: Token, value: T::Balance, addr: AddrStr, ext: Memo, reference: Vec<u8>) -> Result {
+            let who = ensure_signed(origin)?;
+            Self::apply_withdraw(who, token, value, addr, ext, Some(reference))
+        }
 
-            debug!("[withdraw]withdraw|who:{:?}|token:{:}|value:{:}", who, token!(token), value);
+        fn revoke_withdraw(origin, id: u64) -> Result {
+            let from = ensure_signed(origin)?;
+            xrecords::Module::<T>::withdrawal_revoke(&from, id)
+        }
 
-            let asset = xassets::Module::<T>::get_asset(&token)?;
-            if asset.chain() == Chain::ChainX {
-                return Err("Can't withdraw the asset on ChainX")
-            }
+        /// Replace the address and memo of a withdrawal which is not processing yet,
+        /// the locked balance stays locked.
+        fn amend_withdrawal(origin, id: u64, new_addr: AddrStr, new_memo: Memo) -> Result {
+            let who = ensure_signed(origin)?;
 
-            Self::verify_addr(&token, &addr, &ext)?;
+            let token = xrecords::Module::<T>::application_map(id)
+                .map(|node| node.data.token())
+                .ok_or("withdrawal application record not exist")?;
+            Self::can_withdraw(&token)?;
 
-            let limit = Self::withdrawal_limit(&token).ok_or("token should has withdrawal limit")?;
-            // withdrawal value should larger than minimal_withdrawal, allow equal
-            if value < limit.minimal_withdrawal {
-                return Err("withdrawal value should larger than requirement")
-            }
+            let asset = xassets::Module::<T>::get_asset(&token)?;
+            ensure_with_errorlog!(
+                new_memo.len() as u32 <= Self::memo_size_limit(asset.chain()),
+                "memo is too long for this chain",
+                "chain:{:?}|memo len:{:}", asset.chain(), new_memo.len()
+            );
 
-            xrecords::Module::<T>::withdrawal(&who, &token, value, addr, ext)?;
-            Ok(())
-        }
+            Self::verify_addr(&token, &new_addr, &new_memo)?;
 
-        fn revoke_withdraw(origin, id: u32) -> Result {
-            let from = ensure_signed(origin)?;
-            xrecords::Module::<T>::withdrawal_revoke(&from, id)
+            xrecords::Module::<T>::withdrawal_amend(&who, id, new_addr, new_memo)
         }
 
         pub fn modify_token_black_list(token :Token) {
             TokenBlackList::<T>::mutate(|v| {
                 if v.contains(&token) {
                     v.retain(|i| *i != token);
+                    TokenBlackListReason::<T>::remove(&token);
                 } else {
                     v.push(token);
                 }
             });
         }
+
+        /// Put the token into the black list with the reason, or update the reason if already in.
+        pub fn block_token_with_reason(token: Token, reason: Vec<u8>) {
+            TokenBlackList::<T>::mutate(|v| {
+                if !v.contains(&token) {
+                    v.push(token.clone());
+                }
+            });
+            TokenBlackListReason::<T>::insert(token, reason);
+        }
+
+        /// Set the max memo size of the withdrawals on the chain, `None` for the default.
+        pub fn set_max_memo_size(chain: Chain, size: Option<u32>) {
+            match size {
+                Some(size) => MaxMemoSize::<T>::insert(chain, size),
+                None => MaxMemoSize::<T>::remove(chain),
+            }
+        }
+
+        /// Set the withdrawal limit of the token which has no computed one, `None` to remove it.
+        pub fn set_withdrawal_limit(token: Token, limit: Option<WithdrawalLimit<T::Balance>>) -> Result {
+            match limit {
+                Some(limit) => {
+                    if Self::computed_withdrawal_limit(&token).is_some() {
+                        return Err("the withdrawal limit of this token is computed");
+                    }
+                    if limit.minimal_withdrawal <= limit.fee {
+                        return Err("minimal withdrawal should be larger than the fee");
+                    }
+                    <WithdrawalLimitOf<T>>::insert(token, limit);
+                }
+                None => <WithdrawalLimitOf<T>>::remove(token),
+            }
+            Ok(())
+        }
+
+        /// Set the allowed and denied prefixes of the withdrawal addresses of the token,
+        /// empty for no restriction.
+        pub fn set_address_prefixes(token: Token, allowed: Vec<AddrStr>, denied: Vec<AddrStr>) -> Result {
+            if allowed.len() > MAX_ADDRESS_PREFIXES || denied.len() > MAX_ADDRESS_PREFIXES {
+                return Err("too many address prefixes");
+            }
+            if allowed.iter().chain(denied.iter()).any(|prefix| prefix.is_empty()) {
+                return Err("address prefix should not be empty");
+            }
+            if allowed.is_empty() {
+                <AllowedAddressPrefixes<T>>::remove(&token);
+            } else {
+                <AllowedAddressPrefixes<T>>::insert(&token, allowed);
+            }
+            if denied.is_empty() {
+                <DeniedAddressPrefixes<T>>::remove(&token);
+            } else {
+                <DeniedAddressPrefixes<T>>::insert(&token, denied);
+            }
+            Ok(())
+        }
+
+        /// Set the threshold and block window of the withdrawal address reuse detection,
+        /// zero threshold means the detection is disabled.
+        pub fn set_withdrawal_address_reuse_config(threshold: u32, window: T::BlockNumber) {
+            WithdrawalAddressReuseThreshold::<T>::put(threshold);
+            <WithdrawalAddressReuseWindow<T>>::put(window);
+        }
     }
 }
 
+decl_event!(
+    pub enum Event<T> where
+        <T as system::Trait>::AccountId {
+        /// An account withdraws to the same address more times than the threshold within the window.
+        /// (who, token, addr, count)
+        WithdrawalAddressReused(AccountId, Token, AddrStr, u32),
+    }
+);
+
 // bugfix:
 // notice the old version is `Withdrawal`, it's a wrong naming.
 // we fix it to `XAssetsProcess`, and it would affect genesis init for `TokenBlackList`
 decl_storage! {
     trait Store for Module<T: Trait> as XAssetsProcess {
         TokenBlackList get(token_black_list) config(): Vec<Token>;
+        /// The max memo size of the withdrawals on each chain, see `memo_size_limit` for the default.
+        pub MaxMemoSize get(max_memo_size): map Chain => Option<u32>;
+        /// The reason why the token is in the black list.
+        pub TokenBlackListReason get(token_black_list_reason): map Token => Vec<u8>;
+
+        /// Reused times of a withdrawal address beyond which a reuse event is emitted, 0 for disabled.
+        pub WithdrawalAddressReuseThreshold get(withdrawal_address_reuse_threshold): u32;
+        /// Only the withdrawals within these recent blocks are counted.
+        pub WithdrawalAddressReuseWindow get(withdrawal_address_reuse_window): T::BlockNumber;
+        /// Recent withdrawal addresses and their application heights of each account and token.
+        pub RecentWithdrawalAddresses get(recent_withdrawal_addresses): map (T::AccountId, Token) => Vec<(AddrStr, T::BlockNumber)>;
+        /// The withdrawal limit of the token overriding the computed one.
+        pub WithdrawalLimitOf get(withdrawal_limit_of): map Token => Option<WithdrawalLimit<T::Balance>>;
+        /// The withdrawal address of the token must start with one of these prefixes if not empty.
+        pub AllowedAddressPrefixes get(allowed_address_prefixes): map Token => Vec<AddrStr>;
+        /// The withdrawal address of the token starting with any of these prefixes is rejected.
+        pub DeniedAddressPrefixes get(denied_address_prefixes): map Token => Vec<AddrStr>;
     }
 }
 
 impl<T: Trait> Module<T> {
+    fn apply_withdraw(
+        who: T::AccountId,
+        token: Token,
+        value: T::Balance,
+        addr: AddrStr,
+        ext: Memo,
+        reference: Option<Vec<u8>>,
+    ) -> Result {
+        Self::can_withdraw(&token)?;
+
+        debug!(
+            "[withdraw]withdraw|who:{:?}|token:{:}|value:{:}",
+            who,
+            token!(token),
+            value
+        );
+
+        let asset = xassets::Module::<T>::get_asset(&token)?;
+        if asset.chain() == Chain::ChainX {
+            return Err("Can't withdraw the asset on ChainX");
+        }
+
+        ensure_with_errorlog!(
+            ext.len() as u32 <= Self::memo_size_limit(asset.chain()),
+            "memo is too long for this chain",
+            "chain:{:?}|memo len:{:}",
+            asset.chain(),
+            ext.len()
+        );
+
+        Self::verify_addr(&token, &addr, &ext)?;
+
+        let limit = Self::withdrawal_limit(&token).ok_or("token should has withdrawal limit")?;
+        // withdrawal value should larger than minimal_withdrawal, allow equal
+        if value < limit.minimal_withdrawal {
+            return Err("withdrawal value should larger than requirement");
+        }
+
+        xrecords::Module::<T>::withdrawal_with_reference(
+            &who,
+            &token,
+            value,
+            addr.clone(),
+            ext,
+            reference,
+        )?;
+        Self::note_withdrawal_address(&who, &token, addr);
+        Ok(())
+    }
+
+    /// All the black-listed tokens with their reasons, empty reason if not recorded.
+    pub fn blacklist_with_reasons() -> Vec<(Token, Vec<u8>)> {
+        Self::token_black_list()
+            .into_iter()
+            .map(|token| {
+                let reason = Self::token_black_list_reason(&token);
+                (token, reason)
+            })
+            .collect()
+    }
+
     #[inline]
     fn can_withdraw(token: &Token) -> Result {
         ensure_with_errorlog!(
@@ -100,16 +276,103 @@ impl<T: Trait> Module<T> {
 
     fn verify_addr(token: &Token, addr: &[u8], _ext: &[u8]) -> Result {
         match token.as_slice() {
-            <xbitcoin::Module<T> as ChainT>::TOKEN => xbitcoin::Module::<T>::check_addr(&addr, b""),
-            _ => Err("not found match token Token addr checker"),
+            <xbitcoin::Module<T> as ChainT>::TOKEN => {
+                xbitcoin::Module::<T>::check_addr(&addr, b"")?
+            }
+            _ => return Err("not found match token Token addr checker"),
+        }
+        Self::check_addr_prefix(token, addr)
+    }
+
+    fn check_addr_prefix(token: &Token, addr: &[u8]) -> Result {
+        let denied = Self::denied_address_prefixes(token);
+        ensure_with_errorlog!(
+            !denied.iter().any(|prefix| addr.starts_with(prefix)),
+            "withdrawal address matches a denied prefix",
+            "token:{:}|addr:{:}",
+            token!(token),
+            try_hex_or_str(addr),
+        );
+
+        let allowed = Self::allowed_address_prefixes(token);
+        ensure_with_errorlog!(
+            allowed.is_empty() || allowed.iter().any(|prefix| addr.starts_with(prefix)),
+            "withdrawal address does not match any allowed prefix",
+            "token:{:}|addr:{:}",
+            token!(token),
+            try_hex_or_str(addr),
+        );
+        Ok(())
+    }
+
+    /// Record the withdrawal address and return how many times it has been used within the window.
+    fn note_withdrawal_address(who: &T::AccountId, token: &Token, addr: AddrStr) -> u32 {
+        let threshold = Self::withdrawal_address_reuse_threshold();
+        if threshold == 0 {
+            return 0;
+        }
+
+        let current = system::Module::<T>::block_number();
+        let window = Self::withdrawal_address_reuse_window();
+        let key = (who.clone(), token.clone());
+
+        let mut history = Self::recent_withdrawal_addresses(&key);
+        history.retain(|(_, height)| window.is_zero() || *height + window > current);
+        history.push((addr.clone(), current));
+        if history.len() > MAX_TRACKED_WITHDRAWAL_ADDRESSES {
+            let overflow = history.len() - MAX_TRACKED_WITHDRAWAL_ADDRESSES;
+            history.drain(..overflow);
         }
+
+        let count = history.iter().filter(|(a, _)| *a == addr).count() as u32;
+        <RecentWithdrawalAddresses<T>>::insert(&key, history);
+
+        if count > threshold {
+            warn!(
+                "[note_withdrawal_address]|withdrawal address is reused|who:{:?}|token:{:}|addr:{:?}|count:{:}",
+                who,
+                token!(token),
+                addr,
+                count
+            );
+            Self::deposit_event(RawEvent::WithdrawalAddressReused(
+                who.clone(),
+                token.clone(),
+                addr,
+                count,
+            ));
+        }
+        count
+    }
+
+    /// The max memo size of the withdrawals on the chain,
+    /// Bitcoin defaults to the OP_RETURN capacity and the others to the global memo length.
+    pub fn memo_size_limit(chain: Chain) -> u32 {
+        Self::max_memo_size(chain).unwrap_or_else(|| match chain {
+            Chain::Bitcoin => DEFAULT_BITCOIN_MAX_MEMO_SIZE,
+            _ => xassets::Module::<T>::memo_len(),
+        })
     }
 
     pub fn verify_address(token: Token, addr: AddrStr, ext: Memo) -> Result {
         Self::verify_addr(&token, &addr, &ext)
     }
 
+    /// Verify the addresses all at once, the results are in the order of entries.
+    pub fn verify_addresses(entries: Vec<(Token, AddrStr, Memo)>) -> Vec<Result> {
+        entries
+            .into_iter()
+            .map(|(token, addr, ext)| Self::verify_address(token, addr, ext))
+            .collect()
+    }
+
+    /// The limit computed for the token, otherwise the configured `WithdrawalLimitOf`.
     pub fn withdrawal_limit(token: &Token) -> Option<WithdrawalLimit<T::Balance>> {
+        Self::computed_withdrawal_limit(token).or_else(|| Self::withdrawal_limit_of(token))
+    }
+
+    /// The withdrawal limit derived from the withdrawal fee of the token's chain.
+    fn computed_withdrawal_limit(token: &Token) -> Option<WithdrawalLimit<T::Balance>> {
         match token.as_slice() {
             <xbitcoin::Module<T> as ChainT>::TOKEN => {
                 let fee = xbitcoin::Module::<T>::btc_withdrawal_fee().into();
@@ -122,4 +385,12 @@ impl<T: Trait> Module<T> {
             _ => None,
         }
     }
+
+    /// The withdrawal limits of all the registered tokens which have one.
+    pub fn all_withdrawal_limits() -> Vec<(Token, WithdrawalLimit<T::Balance>)> {
+        xassets::Module::<T>::assets()
+            .into_iter()
+            .filter_map(|token| Self::withdrawal_limit(&token).map(|limit| (token, limit)))
+            .collect()
+    }
 }
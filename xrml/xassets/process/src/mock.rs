@@ -11,7 +11,7 @@ use primitives::traits::{BlakeTwo256, IdentityLookup};
 use primitives::{BuildStorage, StorageOverlay};
 use runtime_io::with_externalities;
 use substrate_primitives::{Blake2Hasher, H256};
-use support::impl_outer_origin;
+use support::{impl_outer_event, impl_outer_origin};
 
 // ChainX
 use xassets::{Asset, Chain};
@@ -20,6 +20,17 @@ impl_outer_origin! {
     pub enum Origin for Test {}
 }
 
+mod xprocess {
+    // This hack is required for `impl_outer_event!`.
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        xprocess<T>,
+    }
+}
+
 #[derive(Clone, Eq, PartialEq)]
 pub struct Test;
 
@@ -33,13 +44,14 @@ impl system::Trait for Test {
     type AccountId = u64;
     type Lookup = IdentityLookup<u64>;
     type Header = Header;
-    type Event = ();
+    type Event = TestEvent;
     type Log = DigestItem;
 }
 
 impl xassets::Trait for Test {
     type Balance = u64;
     type OnNewAccount = ();
+    type OnFreeBalanceZero = ();
     type Event = ();
     type OnAssetChanged = ();
     type OnAssetRegisterOrRevoke = ();
@@ -211,7 +223,9 @@ impl xsession::Trait for Test {
     type Event = ();
 }
 
-impl Trait for Test {}
+impl Trait for Test {
+    type Event = TestEvent;
+}
 
 pub type XAssets = xassets::Module<Test>;
 pub type XRecords = xrecords::Module<Test>;
@@ -148,3 +148,386 @@ fn test_check_blacklist() {
         );
     });
 }
+
+#[test]
+fn test_withdrawal_address_reuse() {
+    with_externalities(&mut new_test_ext(), || {
+        let btc = b"BTC".to_vec();
+        let addr = b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b".to_vec();
+        assert_ok!(XAssets::issue(&btc, &1, 1000));
+
+        // disabled by default
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_ok!(XProcess::withdraw(
+            origin,
+            btc.clone(),
+            11,
+            addr.clone(),
+            b"".to_vec()
+        ));
+        assert!(XProcess::recent_withdrawal_addresses(&(1, btc.clone())).is_empty());
+
+        assert_ok!(XProcess::set_withdrawal_address_reuse_config(2, 10));
+        for _ in 0..2 {
+            let origin = system::RawOrigin::Signed(1).into();
+            assert_ok!(XProcess::withdraw(
+                origin,
+                btc.clone(),
+                11,
+                addr.clone(),
+                b"".to_vec()
+            ));
+        }
+        assert_eq!(
+            XProcess::recent_withdrawal_addresses(&(1, btc.clone())).len(),
+            2
+        );
+        let reused = |count: u32| {
+            TestEvent::xprocess(RawEvent::WithdrawalAddressReused(
+                1,
+                btc.clone(),
+                addr.clone(),
+                count,
+            ))
+        };
+        let reported = || {
+            system::Module::<Test>::events()
+                .into_iter()
+                .filter(|record| match record.event {
+                    TestEvent::xprocess(RawEvent::WithdrawalAddressReused(..)) => true,
+                    _ => false,
+                })
+                .map(|record| record.event)
+                .collect::<Vec<_>>()
+        };
+        // not reported while the reuse is within the threshold.
+        assert!(reported().is_empty());
+
+        // beyond the threshold, the reuse would be reported.
+        assert_eq!(XProcess::note_withdrawal_address(&1, &btc, addr.clone()), 3);
+        assert_eq!(reported(), vec![reused(3)]);
+        assert_eq!(
+            XProcess::note_withdrawal_address(&1, &btc, b"other".to_vec()),
+            1
+        );
+
+        // the records out of the window are not counted.
+        system::Module::<Test>::set_block_number(10);
+        assert_eq!(XProcess::note_withdrawal_address(&1, &btc, addr.clone()), 1);
+        assert_eq!(
+            XProcess::recent_withdrawal_addresses(&(1, btc.clone())).len(),
+            1
+        );
+
+        // the tracked history is bounded.
+        for _ in 0..(MAX_TRACKED_WITHDRAWAL_ADDRESSES * 2) {
+            XProcess::note_withdrawal_address(&1, &btc, addr.clone());
+        }
+        assert_eq!(
+            XProcess::recent_withdrawal_addresses(&(1, btc)).len(),
+            MAX_TRACKED_WITHDRAWAL_ADDRESSES
+        );
+    });
+}
+
+#[test]
+fn test_blacklist_with_reasons() {
+    with_externalities(&mut new_test_ext(), || {
+        // `SDOT` is in the black list from genesis without reason
+        assert_eq!(
+            XProcess::blacklist_with_reasons(),
+            vec![(b"SDOT".to_vec(), vec![])]
+        );
+
+        assert_ok!(XProcess::block_token_with_reason(
+            b"BTC".to_vec(),
+            b"bridge upgrade".to_vec()
+        ));
+        assert_ok!(XProcess::block_token_with_reason(
+            b"SDOT".to_vec(),
+            b"mapping paused".to_vec()
+        ));
+        assert_eq!(
+            XProcess::blacklist_with_reasons(),
+            vec![
+                (b"SDOT".to_vec(), b"mapping paused".to_vec()),
+                (b"BTC".to_vec(), b"bridge upgrade".to_vec()),
+            ]
+        );
+
+        // toggle out clears the reason
+        assert_ok!(XProcess::modify_token_black_list(b"BTC".to_vec()));
+        assert_eq!(XProcess::token_black_list_reason(b"BTC".to_vec()), vec![]);
+        assert_ok!(XProcess::modify_token_black_list(b"BTC".to_vec()));
+        assert_eq!(
+            XProcess::blacklist_with_reasons(),
+            vec![
+                (b"SDOT".to_vec(), b"mapping paused".to_vec()),
+                (b"BTC".to_vec(), vec![]),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_verify_addresses() {
+    with_externalities(&mut new_test_ext(), || {
+        let results = XProcess::verify_addresses(vec![
+            (
+                b"BTC".to_vec(),
+                b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b".to_vec(),
+                b"".to_vec(),
+            ),
+            (b"BTC".to_vec(), b"sdfds".to_vec(), b"".to_vec()),
+            (b"SDOT".to_vec(), b"xxx".to_vec(), b"".to_vec()),
+        ]);
+        assert_eq!(
+            results,
+            vec![
+                Ok(()),
+                Err("Verify btc addr err"),
+                Err("not found match token Token addr checker"),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_max_memo_size() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_eq!(
+            XProcess::memo_size_limit(Chain::Bitcoin),
+            DEFAULT_BITCOIN_MAX_MEMO_SIZE
+        );
+        assert_eq!(XProcess::memo_size_limit(Chain::Ethereum), 128);
+
+        assert_ok!(XAssets::issue(&b"BTC".to_vec(), &1, 1000));
+        assert_ok!(XProcess::set_max_memo_size(Chain::Bitcoin, Some(4)));
+        assert_ok!(XProcess::set_max_memo_size(Chain::Ethereum, Some(64)));
+        assert_eq!(XProcess::memo_size_limit(Chain::Ethereum), 64);
+
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_err!(
+            XProcess::withdraw(
+                origin,
+                b"BTC".to_vec(),
+                11,
+                b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b".to_vec(),
+                b"12345".to_vec()
+            ),
+            "memo is too long for this chain"
+        );
+
+        assert_ok!(XProcess::set_max_memo_size(Chain::Bitcoin, Some(10)));
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_ok!(XProcess::withdraw(
+            origin,
+            b"BTC".to_vec(),
+            11,
+            b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b".to_vec(),
+            b"12345".to_vec()
+        ));
+    });
+}
+
+#[test]
+fn test_amend_withdrawal() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_ok!(XAssets::issue(&b"BTC".to_vec(), &1, 1000));
+
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_ok!(XProcess::withdraw(
+            origin,
+            b"BTC".to_vec(),
+            100,
+            b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b".to_vec(),
+            b"".to_vec()
+        ));
+        let id = XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap()[0];
+
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_err!(
+            XProcess::amend_withdrawal(origin, id, b"sdfds".to_vec(), b"".to_vec()),
+            "Verify btc addr err"
+        );
+
+        let origin = system::RawOrigin::Signed(2).into();
+        assert_err!(
+            XProcess::amend_withdrawal(
+                origin,
+                id,
+                b"2N8tR484JD32i1DY2FnRPLwBVaNuXSfzoAv".to_vec(),
+                b"".to_vec()
+            ),
+            "the applicant is not this account"
+        );
+
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_ok!(XProcess::amend_withdrawal(
+            origin,
+            id,
+            b"2N8tR484JD32i1DY2FnRPLwBVaNuXSfzoAv".to_vec(),
+            b"memo".to_vec()
+        ));
+        let application = XRecords::application_map(id).unwrap().data;
+        assert_eq!(
+            application.addr,
+            b"2N8tR484JD32i1DY2FnRPLwBVaNuXSfzoAv".to_vec()
+        );
+        assert_eq!(application.ext, b"memo".to_vec());
+        // the balance stays locked
+        assert_eq!(XAssets::free_balance_of(&1, &b"BTC".to_vec()), 900);
+
+        assert_ok!(XRecords::withdrawal_processing(&[id]));
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_err!(
+            XProcess::amend_withdrawal(
+                origin,
+                id,
+                b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b".to_vec(),
+                b"".to_vec()
+            ),
+            "only allow `Applying` state for applicant amend"
+        );
+    });
+}
+
+#[test]
+fn test_withdraw_with_reference() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_ok!(XAssets::issue(&b"BTC".to_vec(), &1, 1000));
+
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_err!(
+            XProcess::withdraw_with_reference(
+                origin,
+                b"BTC".to_vec(),
+                100,
+                b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b".to_vec(),
+                b"".to_vec(),
+                vec![0; xrecords::MAX_REFERENCE_LEN + 1]
+            ),
+            "reference is too long"
+        );
+
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_ok!(XProcess::withdraw_with_reference(
+            origin,
+            b"BTC".to_vec(),
+            100,
+            b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b".to_vec(),
+            b"".to_vec(),
+            b"order-42".to_vec()
+        ));
+        let id = XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap()[0];
+        assert_eq!(
+            XRecords::application_reference(id),
+            Some(b"order-42".to_vec())
+        );
+        assert_eq!(XAssets::free_balance_of(&1, &b"BTC".to_vec()), 900);
+    });
+}
+
+#[test]
+fn test_all_withdrawal_limits() {
+    with_externalities(&mut new_test_ext(), || {
+        XBitCoin::set_btc_withdrawal_fee(100).unwrap();
+        let btc_limit = WithdrawalLimit {
+            minimal_withdrawal: 150,
+            fee: 100,
+        };
+        assert_eq!(
+            XProcess::all_withdrawal_limits(),
+            vec![(b"BTC".to_vec(), btc_limit.clone())]
+        );
+
+        let sdot_limit = WithdrawalLimit {
+            minimal_withdrawal: 10,
+            fee: 1,
+        };
+        XProcess::set_withdrawal_limit(b"SDOT".to_vec(), Some(sdot_limit.clone())).unwrap();
+        let limits = XProcess::all_withdrawal_limits();
+        assert_eq!(limits.len(), 2);
+        assert!(limits.contains(&(b"BTC".to_vec(), btc_limit)));
+        assert!(limits.contains(&(b"SDOT".to_vec(), sdot_limit)));
+
+        XProcess::set_withdrawal_limit(b"SDOT".to_vec(), None).unwrap();
+        assert_eq!(XProcess::all_withdrawal_limits().len(), 1);
+
+        // the minimal withdrawal must cover the fee
+        assert_err!(
+            XProcess::set_withdrawal_limit(
+                b"SDOT".to_vec(),
+                Some(WithdrawalLimit {
+                    minimal_withdrawal: 1,
+                    fee: 1,
+                })
+            ),
+            "minimal withdrawal should be larger than the fee"
+        );
+        // the computed limit of BTC can't be overridden
+        assert_err!(
+            XProcess::set_withdrawal_limit(
+                b"BTC".to_vec(),
+                Some(WithdrawalLimit {
+                    minimal_withdrawal: 10,
+                    fee: 1,
+                })
+            ),
+            "the withdrawal limit of this token is computed"
+        );
+        assert_eq!(
+            XProcess::withdrawal_limit(&b"BTC".to_vec()),
+            Some(WithdrawalLimit {
+                minimal_withdrawal: 150,
+                fee: 100,
+            })
+        );
+    });
+}
+
+#[test]
+fn test_address_prefixes() {
+    with_externalities(&mut new_test_ext(), || {
+        let btc = XBitCoin::TOKEN.to_vec();
+        // testnet p2pkh and p2sh addresses
+        let p2pkh = b"mjKE11gjVN4JaC9U8qL6ZB5vuEBgmwik7b";
+        let p2sh = b"2N8tR484JD32i1DY2FnRPLwBVaNuXSfzoAv";
+
+        assert_err!(
+            XProcess::set_address_prefixes(btc.clone(), vec![b"".to_vec()], vec![]),
+            "address prefix should not be empty"
+        );
+
+        assert_ok!(XProcess::set_address_prefixes(
+            btc.clone(),
+            vec![],
+            vec![b"mjKE".to_vec()]
+        ));
+        assert_eq!(
+            XProcess::verify_addr(&btc, p2pkh, b""),
+            Err("withdrawal address matches a denied prefix")
+        );
+        assert_eq!(XProcess::verify_addr(&btc, p2sh, b""), Ok(()));
+        // the format is still checked first.
+        assert_eq!(
+            XProcess::verify_addr(&btc, b"mjKEsdfds", b""),
+            Err("Verify btc addr err")
+        );
+
+        assert_ok!(XProcess::set_address_prefixes(
+            btc.clone(),
+            vec![b"2".to_vec()],
+            vec![]
+        ));
+        assert_eq!(XProcess::verify_addr(&btc, p2sh, b""), Ok(()));
+        assert_eq!(
+            XProcess::verify_addr(&btc, p2pkh, b""),
+            Err("withdrawal address does not match any allowed prefix")
+        );
+
+        // no restriction by default.
+        assert_ok!(XProcess::set_address_prefixes(btc.clone(), vec![], vec![]));
+        assert_eq!(XProcess::verify_addr(&btc, p2pkh, b""), Ok(()));
+    });
+}
@@ -9,8 +9,12 @@ mod mock;
 mod tests;
 pub mod types;
 
+use parity_codec::{Decode, Encode};
+
 // Substrate
 use rstd::prelude::*;
+use runtime_io::{blake2_256, twox_128};
+use support::storage::unhashed;
 use support::{decl_event, decl_module, decl_storage, dispatch::Result, StorageMap, StorageValue};
 
 // ChainX
@@ -23,10 +27,20 @@ use xsupport::{error, info, warn};
 #[cfg(feature = "std")]
 use xsupport::{token, u8array_to_addr, u8array_to_string};
 
+use self::types::LegacyApplicationNode;
 pub use self::types::{
     Application, ApplicationState, HeightOrTime, LinkedMultiKey, RecordInfo, TxState,
 };
 
+/// The max number of nodes `withdrawal_applications` walks through.
+pub const MAX_APPLICATION_ITERATIONS: u32 = 100_000;
+
+/// The max number of legacy applications translated in a block.
+pub const MAX_MIGRATION_NODES_PER_BLOCK: u32 = 1_000;
+
+/// The max length of the reference id supplied with a deposit or withdrawal.
+pub const MAX_REFERENCE_LEN: usize = 64;
+
 pub trait Trait: system::Trait + xassets::Trait + timestamp::Trait {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
@@ -35,6 +49,13 @@ pub trait Trait: system::Trait + xassets::Trait + timestamp::Trait {
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn deposit_event<T>() = default;
+
+        fn on_initialize() {
+            if !Self::serial_number_migrated() {
+                Self::migrate_serial_number(MAX_MIGRATION_NODES_PER_BLOCK);
+            }
+        }
+
         // only for root
         fn deposit_from_root(who: T::AccountId, token: Token, balance: T::Balance) -> Result {
             Self::deposit(&who, &token, balance)
@@ -44,7 +65,7 @@ decl_module! {
             Self::withdrawal(&who, &token, balance, Default::default(), Default::default())
         }
 
-        pub fn fix_withdrawal_state(withdrawal_id: u32, state: ApplicationState) -> Result {
+        pub fn fix_withdrawal_state(withdrawal_id: u64, state: ApplicationState) -> Result {
             match Self::withdrawal_finish_impl(withdrawal_id, state) {
                 Ok(_) => {
                     info!("[withdraw]|ID of withdrawal completion: {:}", withdrawal_id);
@@ -57,7 +78,7 @@ decl_module! {
             }
         }
 
-        pub fn fix_withdrawal_state_list(item: Vec<(u32, ApplicationState)>) -> Result {
+        pub fn fix_withdrawal_state_list(item: Vec<(u64, ApplicationState)>) -> Result {
             for (withdrawal_id, state) in item {
                 let _ = Self::fix_withdrawal_state(withdrawal_id, state);
             }
@@ -71,8 +92,14 @@ decl_event!(
         <T as system::Trait>::AccountId,
         <T as xassets::Trait>::Balance {
         Deposit(AccountId, Token, Balance),
-        WithdrawalApply(u32, AccountId, Chain, Token, Balance, Memo, AddrStr),
-        WithdrawalFinish(u32, ApplicationState),
+        WithdrawalApply(u64, AccountId, Chain, Token, Balance, Memo, AddrStr),
+        WithdrawalFinish(u64, ApplicationState),
+        /// The reference id supplied with a deposit, (who, token, balance, reference).
+        DepositReference(AccountId, Token, Balance, Vec<u8>),
+        /// The reference id supplied with a withdrawal application, (id, reference).
+        WithdrawalReference(u64, Vec<u8>),
+        /// The address and memo of a withdrawal application are amended, (id, addr, memo).
+        WithdrawalAmend(u64, AddrStr, Memo),
     }
 );
 
@@ -83,9 +110,16 @@ decl_storage! {
         /// linked node tail
         pub ApplicationMTail get(application_mtail): map Chain => Option<MultiNodeIndex<Chain, Application<T::AccountId, T::Balance, T::BlockNumber>>>;
         /// withdrawal applications collection, use serial number to mark them, and has prev and next to link them
-        pub ApplicationMap get(application_map): map u32 => Option<Node<Application<T::AccountId, T::Balance, T::BlockNumber>>>;
+        pub ApplicationMap get(application_map): map u64 => Option<Node<Application<T::AccountId, T::Balance, T::BlockNumber>>>;
         /// withdrawal application serial number
-        pub SerialNumber get(number): u32 = 0;
+        pub SerialNumber get(number): u64 = 0;
+        /// the reference id supplied with the withdrawal application, removed when the application is finished
+        pub ApplicationReference get(application_reference): map u64 => Option<Vec<u8>>;
+        /// whether the storage written with the legacy u32 serial number is translated
+        pub SerialNumberMigrated get(serial_number_migrated): bool;
+        /// the next legacy application to translate and the last translated one of the chain,
+        /// present while the migration of the chain is in progress
+        pub LegacyApplicationCursor get(legacy_application_cursor): map Chain => Option<(u32, Option<u64>)>;
     }
 }
 
@@ -109,12 +143,37 @@ impl<T: Trait> Module<T> {
 
         Ok(())
     }
+
+    fn check_reference(reference: &Option<Vec<u8>>) -> Result {
+        if let Some(reference) = reference {
+            if reference.len() > MAX_REFERENCE_LEN {
+                error!(
+                    "[check_reference]|reference is too long|len:{:}|max:{:}",
+                    reference.len(),
+                    MAX_REFERENCE_LEN
+                );
+                return Err("reference is too long");
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T: Trait> Module<T> {
     /// deposit, notice this func has include deposit_init and deposit_finish (not wait for block confirm process)
     pub fn deposit(who: &T::AccountId, token: &Token, balance: T::Balance) -> Result {
+        Self::deposit_with_reference(who, token, balance, None)
+    }
+
+    /// deposit with an optional reference id, which is echoed in the `DepositReference` event
+    pub fn deposit_with_reference(
+        who: &T::AccountId,
+        token: &Token,
+        balance: T::Balance,
+        reference: Option<Vec<u8>>,
+    ) -> Result {
         Self::before(who, token)?;
+        Self::check_reference(&reference)?;
 
         info!(
             "[deposit]|who:{:?}|token:{:}|balance:{:}",
@@ -125,6 +184,14 @@ impl<T: Trait> Module<T> {
 
         xassets::Module::<T>::issue(token, who, balance)?;
         Self::deposit_event(RawEvent::Deposit(who.clone(), token.clone(), balance));
+        if let Some(reference) = reference {
+            Self::deposit_event(RawEvent::DepositReference(
+                who.clone(),
+                token.clone(),
+                balance,
+                reference,
+            ));
+        }
         Ok(())
     }
 
@@ -135,10 +202,27 @@ impl<T: Trait> Module<T> {
         balance: T::Balance,
         addr: AddrStr,
         ext: Memo,
+    ) -> Result {
+        Self::withdrawal_with_reference(who, token, balance, addr, ext, None)
+    }
+
+    /// withdrawal with an optional reference id, which is kept with the application
+    /// until it's finished and echoed in the `WithdrawalReference` event
+    pub fn withdrawal_with_reference(
+        who: &T::AccountId,
+        token: &Token,
+        balance: T::Balance,
+        addr: AddrStr,
+        ext: Memo,
+        reference: Option<Vec<u8>>,
     ) -> Result {
         Self::withdraw_check_before(who, token, balance)?;
+        Self::check_reference(&reference)?;
 
         let asset = xassets::Module::<T>::get_asset(token)?;
+        if Self::legacy_applications_pending(asset.chain()) {
+            return Err("the withdrawal applications of this chain are still being migrated");
+        }
 
         let id = Self::number();
 
@@ -175,7 +259,7 @@ impl<T: Trait> Module<T> {
             }
         }
 
-        let newid = match id.checked_add(1_u32) {
+        let newid = match id.checked_add(1_u64) {
             Some(r) => r,
             None => 0,
         };
@@ -190,11 +274,15 @@ impl<T: Trait> Module<T> {
             appl.ext,
             appl.addr, // if btc, the addr is base58 addr
         ));
+        if let Some(reference) = reference {
+            ApplicationReference::<T>::insert(appl.id, reference.clone());
+            Self::deposit_event(RawEvent::WithdrawalReference(appl.id, reference));
+        }
         Ok(())
     }
 
     /// change Applying to Processing
-    pub fn withdrawal_processing(serial_number: &[u32]) -> Result {
+    pub fn withdrawal_processing(serial_number: &[u64]) -> Result {
         let mut v = Vec::new();
         for id in serial_number.iter() {
             if let Some(node) = Self::application_map(id) {
@@ -221,7 +309,7 @@ impl<T: Trait> Module<T> {
 
     /// withdrawal finish, let the locking token destroy
     /// Change Processing to final state
-    pub fn withdrawal_finish(serial_number: u32) -> Result {
+    pub fn withdrawal_finish(serial_number: u64) -> Result {
         if let Some(node) = Self::application_map(serial_number) {
             if node.data.state != ApplicationState::Processing {
                 error!("[withdrawal_finish]only allow `Processing` for this application|id:{:}|state:{:?}", serial_number, node.data.state);
@@ -231,7 +319,7 @@ impl<T: Trait> Module<T> {
         Self::withdrawal_finish_impl(serial_number, ApplicationState::NormalFinish)
     }
 
-    pub fn withdrawal_revoke(who: &T::AccountId, serial_number: u32) -> Result {
+    pub fn withdrawal_revoke(who: &T::AccountId, serial_number: u64) -> Result {
         if let Some(node) = Self::application_map(serial_number) {
             if node.data.applicant != *who {
                 error!(
@@ -249,8 +337,57 @@ impl<T: Trait> Module<T> {
         Self::withdrawal_finish_impl(serial_number, ApplicationState::NormalCancel)
     }
 
+    /// replace the address and memo of an `Applying` application in place, the locked balance is untouched
+    pub fn withdrawal_amend(
+        who: &T::AccountId,
+        serial_number: u64,
+        addr: AddrStr,
+        ext: Memo,
+    ) -> Result {
+        let mut node = if let Some(node) = Self::application_map(serial_number) {
+            node
+        } else {
+            error!(
+                "[withdrawal_amend]|withdrawal application record not exist|id:{:}",
+                serial_number
+            );
+            return Err("withdrawal application record not exist");
+        };
+
+        if node.data.applicant != *who {
+            error!(
+                "[withdrawal_amend]|the applicant is not this account|applicant:{:?}|who:{:?}",
+                node.data.applicant, who
+            );
+            return Err("the applicant is not this account");
+        }
+
+        if node.data.state != ApplicationState::Applying {
+            error!(
+                "[withdrawal_amend]|only allow `Applying` for this application|id:{:}|state:{:?}",
+                serial_number, node.data.state
+            );
+            return Err("only allow `Applying` state for applicant amend");
+        }
+
+        info!(
+            "[withdrawal_amend]|id:{:}|who:{:?}|addr:{:}|memo:{:}",
+            serial_number,
+            who,
+            u8array_to_addr(&addr),
+            u8array_to_string(&ext)
+        );
+
+        node.data.addr = addr.clone();
+        node.data.ext = ext.clone();
+        ApplicationMap::<T>::insert(serial_number, node);
+
+        Self::deposit_event(RawEvent::WithdrawalAmend(serial_number, addr, ext));
+        Ok(())
+    }
+
     /// revoke to applying
-    pub fn withdrawal_recover_by_trustee(serial_number: u32) -> Result {
+    pub fn withdrawal_recover_by_trustee(serial_number: u64) -> Result {
         if let Some(mut node) = Self::application_map(serial_number) {
             if node.data.state != ApplicationState::Processing {
                 error!("[withdrawal_recover_by_trustee]|only allow `Processing` for this application|id:{:}|state:{:?}", serial_number, node.data.state);
@@ -264,7 +401,7 @@ impl<T: Trait> Module<T> {
     }
 
     /// revoke to cancel
-    pub fn withdrawal_revoke_by_trustee(serial_number: u32) -> Result {
+    pub fn withdrawal_revoke_by_trustee(serial_number: u64) -> Result {
         if let Some(node) = Self::application_map(serial_number) {
             if node.data.state != ApplicationState::Processing {
                 error!("[withdrawal_revoke_by_trustee]|only allow `Processing` for this application|id:{:}|state:{:?}", serial_number, node.data.state);
@@ -276,7 +413,7 @@ impl<T: Trait> Module<T> {
 
     pub fn fix_withdrawal_state_by_trustees(
         chain: Chain,
-        withdrawal_id: u32,
+        withdrawal_id: u64,
         state: ApplicationState,
     ) -> Result {
         if let Some(node) = Self::application_map(withdrawal_id) {
@@ -304,7 +441,7 @@ impl<T: Trait> Module<T> {
         Self::fix_withdrawal_state(withdrawal_id, state)
     }
 
-    fn withdrawal_finish_impl(serial_number: u32, state: ApplicationState) -> Result {
+    fn withdrawal_finish_impl(serial_number: u64, state: ApplicationState) -> Result {
         let mut node = if let Some(node) = Self::application_map(serial_number) {
             node
         } else {
@@ -315,6 +452,7 @@ impl<T: Trait> Module<T> {
         let asset = xassets::Module::<T>::get_asset(&node.data.token())?;
 
         node.remove_option_with_key::<LinkedMultiKey<T>, Chain>(asset.chain())?;
+        ApplicationReference::<T>::remove(serial_number);
 
         let application = node.data;
         let who = application.applicant();
@@ -376,7 +514,7 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
-    pub fn withdrawal_application_numbers(chain: Chain, max_count: u32) -> Option<Vec<u32>> {
+    pub fn withdrawal_application_numbers(chain: Chain, max_count: u32) -> Option<Vec<u64>> {
         let mut vec = Vec::new();
         // begin from header
         if let Some(header) = Self::application_mheader(chain) {
@@ -396,23 +534,205 @@ impl<T: Trait> Module<T> {
         None
     }
 
+    /// The serial numbers of the withdrawal applications of the chain in `Processing` state.
+    pub fn processing_applications(chain: Chain) -> Vec<u64> {
+        Self::withdrawal_applications(chain)
+            .into_iter()
+            .filter(|app| app.state() == ApplicationState::Processing)
+            .map(|app| app.id())
+            .collect()
+    }
+
     pub fn withdrawal_applications(
         chain: Chain,
+    ) -> Vec<Application<T::AccountId, T::Balance, T::BlockNumber>> {
+        Self::withdrawal_applications_bounded(chain, MAX_APPLICATION_ITERATIONS)
+    }
+
+    /// Walk through the withdrawal applications of the chain, at most `max_iterations` nodes,
+    /// in case the linked list is corrupted, e.g. has a cycle.
+    pub fn withdrawal_applications_bounded(
+        chain: Chain,
+        max_iterations: u32,
     ) -> Vec<Application<T::AccountId, T::Balance, T::BlockNumber>> {
         let mut vec = Vec::new();
         // begin from header
         if let Some(header) = Self::application_mheader(chain) {
             let mut index = header.index();
-            while let Some(node) = Self::application_map(&index) {
+            for _ in 0..max_iterations {
+                let node = match Self::application_map(&index) {
+                    Some(node) => node,
+                    None => return vec,
+                };
                 let next = node.next();
                 vec.push(node.data);
-                if let Some(next) = next {
-                    index = next;
-                } else {
-                    break;
+                match next {
+                    Some(next) => index = next,
+                    None => return vec,
                 }
             }
+            error!(
+                "[withdrawal_applications]|not reach the tail after max iterations, the list may be corrupted|chain:{:?}|max_iterations:{:}",
+                chain, max_iterations
+            );
         }
         vec
     }
 }
+
+impl<T: Trait> Module<T> {
+    /// The hashed key of the entry of `map` under the legacy `u32` serial number.
+    fn legacy_map_key(map: &[u8], key: &impl Encode) -> [u8; 32] {
+        let mut k = map.to_vec();
+        key.encode_to(&mut k);
+        blake2_256(&k)
+    }
+
+    /// Whether the legacy applications of `chain` are not translated yet.
+    fn legacy_applications_pending(chain: Chain) -> bool {
+        !Self::serial_number_migrated() && LegacyApplicationCursor::<T>::exists(&chain)
+    }
+
+    /// Decode the raw value only if it's consumed exactly, so that a value written with the
+    /// widened `u64` index is not taken for the legacy one.
+    fn decode_exact<D: Decode>(raw: &[u8]) -> Option<D> {
+        let mut input = raw;
+        let value = D::decode(&mut input)?;
+        if input.is_empty() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Rewrite the legacy header and tail of the chain with the widened index in place, they
+    /// share the storage keys with `ApplicationMHeader` and `ApplicationMTail`. Return the
+    /// first legacy application to translate, `None` if the chain has no legacy list.
+    fn widen_legacy_header(chain: Chain) -> Option<u32> {
+        let header_key = Self::legacy_map_key(b"XAssetsRecords ApplicationMHeader", &chain);
+        let tail_key = Self::legacy_map_key(b"XAssetsRecords ApplicationMTail", &chain);
+        let (_, header) = unhashed::get_raw(&header_key)
+            .and_then(|raw| Self::decode_exact::<(Chain, u32)>(&raw))?;
+        let tail = unhashed::get_raw(&tail_key)
+            .and_then(|raw| Self::decode_exact::<(Chain, u32)>(&raw))
+            .map(|(_, tail)| tail)
+            .unwrap_or(header);
+
+        ApplicationMHeader::<T>::insert(chain, MultiNodeIndex::new(chain, u64::from(header)));
+        ApplicationMTail::<T>::insert(chain, MultiNodeIndex::new(chain, u64::from(tail)));
+        Some(header)
+    }
+
+    /// Cut the list of the chain after `last`, the last application translated, since the
+    /// following one can't be translated.
+    fn truncate_migrated_applications(chain: Chain, last: Option<u64>) {
+        match last.and_then(|last| Self::application_map(last).map(|node| (last, node))) {
+            Some((last, node)) => {
+                let node = Node::with_links(node.prev(), None, node.data);
+                ApplicationMap::<T>::insert(last, node);
+                ApplicationMTail::<T>::insert(chain, MultiNodeIndex::new(chain, last));
+            }
+            None => {
+                ApplicationMHeader::<T>::remove(chain);
+                ApplicationMTail::<T>::remove(chain);
+            }
+        }
+    }
+
+    /// Translate the storage written with the legacy `u32` serial number. The applications of
+    /// each chain keep their ids and links, which are widened to `u64`.
+    ///
+    /// The header and tail of a chain are widened in place first, then at most `max_nodes`
+    /// applications are translated in a call following `LegacyApplicationCursor`, and the
+    /// migration is only marked finished when no chain is in progress. The withdrawals of a
+    /// chain in progress are refused. A legacy application which can't be decoded is reported
+    /// and the list is cut before it, as the following ones can't be found without its link.
+    fn migrate_serial_number(max_nodes: u32) {
+        let key = twox_128(b"XAssetsRecords SerialNumber");
+        if let Some(raw) = unhashed::get_raw(&key) {
+            if let (4, Some(number)) = (raw.len(), u32::decode(&mut raw.as_slice())) {
+                SerialNumber::<T>::put(u64::from(number));
+            }
+        }
+
+        let mut budget = max_nodes;
+        let mut finished = true;
+        for chain in Chain::iterator() {
+            let (mut index, mut last) = match Self::legacy_application_cursor(chain) {
+                Some(cursor) => cursor,
+                None => match Self::widen_legacy_header(*chain) {
+                    Some(header) => (header, None),
+                    None => continue,
+                },
+            };
+
+            let mut chain_finished = false;
+            while budget > 0 {
+                let node_key = Self::legacy_map_key(b"XAssetsRecords ApplicationMap", &index);
+                let legacy = unhashed::get_raw(&node_key).and_then(|raw| {
+                    Self::decode_exact::<
+                        LegacyApplicationNode<T::AccountId, T::Balance, T::BlockNumber>,
+                    >(&raw)
+                });
+                let (prev, next, application) = match legacy {
+                    Some(legacy) => legacy,
+                    None => {
+                        error!(
+                            "[migrate_serial_number]|can't read the legacy application, the list is cut before it|chain:{:?}|index:{:}|last:{:?}",
+                            chain, index, last
+                        );
+                        Self::truncate_migrated_applications(*chain, last);
+                        chain_finished = true;
+                        break;
+                    }
+                };
+                unhashed::kill(&node_key);
+                budget -= 1;
+
+                let application: Application<T::AccountId, T::Balance, T::BlockNumber> =
+                    application.into();
+                let id = application.id();
+                let node = Node::with_links(prev.map(u64::from), next.map(u64::from), application);
+                ApplicationMap::<T>::insert(id, node);
+
+                let reference_key =
+                    Self::legacy_map_key(b"XAssetsRecords ApplicationReference", &index);
+                if let Some(raw) = unhashed::get_raw(&reference_key) {
+                    unhashed::kill(&reference_key);
+                    match Self::decode_exact::<Vec<u8>>(&raw) {
+                        Some(reference) => ApplicationReference::<T>::insert(id, reference),
+                        None => error!(
+                            "[migrate_serial_number]|can't read the legacy reference, skip it|chain:{:?}|index:{:}",
+                            chain, index
+                        ),
+                    }
+                }
+
+                last = Some(id);
+                match next {
+                    Some(next) => index = next,
+                    None => {
+                        chain_finished = true;
+                        break;
+                    }
+                }
+            }
+
+            if chain_finished {
+                LegacyApplicationCursor::<T>::remove(chain);
+                info!(
+                    "[migrate_serial_number]|migrate the applications|chain:{:?}|count:{:}",
+                    chain,
+                    Self::withdrawal_applications(*chain).len()
+                );
+            } else {
+                LegacyApplicationCursor::<T>::insert(chain, (index, last));
+                finished = false;
+            }
+        }
+
+        if finished {
+            SerialNumberMigrated::<T>::put(true);
+        }
+    }
+}
@@ -49,6 +49,7 @@ impl timestamp::Trait for Test {
 impl xassets::Trait for Test {
     type Balance = u64;
     type OnNewAccount = ();
+    type OnFreeBalanceZero = ();
     type Event = ();
     type OnAssetChanged = ();
     type OnAssetRegisterOrRevoke = ();
@@ -59,6 +60,7 @@ impl Trait for Test {
     type Event = ();
 }
 
+pub type System = system::Module<Test>;
 pub type XAssets = xassets::Module<Test>;
 pub type XRecords = Module<Test>;
 
@@ -132,3 +132,343 @@ fn test_withdrawal_first() {
         );
     })
 }
+
+#[test]
+fn test_withdrawal_applications_cyclic_list() {
+    with_externalities(&mut new_test_ext(), || {
+        let a: u64 = 1; // accountid
+        let btc_token = b"BTC".to_vec();
+        assert_ok!(XRecords::deposit(&a, &btc_token, 100));
+        for _ in 0..2 {
+            assert_ok!(XRecords::withdrawal(
+                &a,
+                &btc_token,
+                10,
+                b"addr".to_vec(),
+                b"ext".to_vec()
+            ));
+        }
+        assert_eq!(XRecords::withdrawal_applications(Chain::Bitcoin).len(), 2);
+        assert_eq!(
+            XRecords::withdrawal_applications_bounded(Chain::Bitcoin, 1).len(),
+            1
+        );
+
+        // corrupt the list, the second node points to itself.
+        let first = XRecords::application_mheader(Chain::Bitcoin)
+            .unwrap()
+            .index();
+        let node = XRecords::application_map(first).unwrap();
+        let second = node.next().unwrap();
+        ApplicationMap::<Test>::insert(second, node);
+
+        assert_eq!(
+            XRecords::withdrawal_applications_bounded(Chain::Bitcoin, 10).len(),
+            10
+        );
+    });
+}
+
+#[test]
+fn test_withdrawal_reference() {
+    with_externalities(&mut new_test_ext(), || {
+        let a: u64 = 1; // accountid
+        let btc_token = b"BTC".to_vec();
+
+        let events = System::events().len();
+        assert_ok!(XRecords::deposit(&a, &btc_token, 100));
+        let deposit_events = System::events().len() - events;
+
+        let events = System::events().len();
+        assert_ok!(XRecords::deposit_with_reference(
+            &a,
+            &btc_token,
+            100,
+            Some(b"deposit-1".to_vec())
+        ));
+        // echoed in an extra `DepositReference` event
+        assert_eq!(System::events().len() - events, deposit_events + 1);
+
+        assert_err!(
+            XRecords::withdrawal_with_reference(
+                &a,
+                &btc_token,
+                50,
+                b"addr".to_vec(),
+                b"ext".to_vec(),
+                Some(vec![0; MAX_REFERENCE_LEN + 1])
+            ),
+            "reference is too long"
+        );
+
+        let events = System::events().len();
+        assert_ok!(XRecords::withdrawal(
+            &a,
+            &btc_token,
+            50,
+            b"addr".to_vec(),
+            b"ext".to_vec()
+        ));
+        let withdrawal_events = System::events().len() - events;
+
+        let events = System::events().len();
+        assert_ok!(XRecords::withdrawal_with_reference(
+            &a,
+            &btc_token,
+            50,
+            b"addr".to_vec(),
+            b"ext".to_vec(),
+            Some(b"withdrawal-1".to_vec())
+        ));
+        // echoed in an extra `WithdrawalReference` event
+        assert_eq!(System::events().len() - events, withdrawal_events + 1);
+
+        let numbers = XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap();
+        assert_eq!(numbers.len(), 2);
+        assert_eq!(XRecords::application_reference(numbers[0]), None);
+        assert_eq!(
+            XRecords::application_reference(numbers[1]),
+            Some(b"withdrawal-1".to_vec())
+        );
+
+        assert_ok!(XRecords::withdrawal_processing(&numbers));
+        for i in numbers.iter() {
+            assert_ok!(XRecords::withdrawal_finish(*i));
+        }
+        assert_eq!(XRecords::application_reference(numbers[1]), None);
+    })
+}
+
+#[test]
+fn test_processing_applications() {
+    with_externalities(&mut new_test_ext(), || {
+        let a: u64 = 1; // accountid
+        let btc_token = b"BTC".to_vec();
+        assert_ok!(XRecords::deposit(&a, &btc_token, 100));
+        for _ in 0..3 {
+            assert_ok!(XRecords::withdrawal(
+                &a,
+                &btc_token,
+                10,
+                b"addr".to_vec(),
+                b"ext".to_vec()
+            ));
+        }
+        assert!(XRecords::processing_applications(Chain::Bitcoin).is_empty());
+
+        let numbers = XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap();
+        assert_eq!(numbers.len(), 3);
+        assert_ok!(XRecords::withdrawal_processing(&numbers[..2]));
+        assert_eq!(
+            XRecords::processing_applications(Chain::Bitcoin),
+            numbers[..2].to_vec()
+        );
+
+        // no longer processing after finished.
+        assert_ok!(XRecords::withdrawal_finish(numbers[0]));
+        assert_eq!(
+            XRecords::processing_applications(Chain::Bitcoin),
+            vec![numbers[1]]
+        );
+        assert!(XRecords::processing_applications(Chain::Ethereum).is_empty());
+    })
+}
+
+/// Write the applications of Bitcoin with the legacy u32 serial number, linked in the given
+/// order, the last one is referenced with `b"ref"`.
+fn put_legacy_applications(who: u64, ids: &[u32]) {
+    let key = |map: &[u8], key: u32| XRecords::legacy_map_key(map, &key);
+    for (i, id) in ids.iter().enumerate() {
+        let application = types::LegacyApplication {
+            id: *id,
+            state: ApplicationState::Applying,
+            applicant: who,
+            token: b"BTC".to_vec(),
+            balance: 10_u64,
+            addr: b"addr".to_vec(),
+            ext: b"ext".to_vec(),
+            height: 1_u64,
+        };
+        let prev = if i == 0 { None } else { Some(ids[i - 1]) };
+        let next = ids.get(i + 1).cloned();
+        unhashed::put(
+            &key(b"XAssetsRecords ApplicationMap", *id),
+            &(prev, next, application),
+        );
+    }
+    let last = *ids.last().unwrap();
+    unhashed::put(
+        &key(b"XAssetsRecords ApplicationReference", last),
+        &b"ref".to_vec(),
+    );
+    unhashed::put(
+        &XRecords::legacy_map_key(b"XAssetsRecords ApplicationMHeader", &Chain::Bitcoin),
+        &(Chain::Bitcoin, ids[0]),
+    );
+    unhashed::put(
+        &XRecords::legacy_map_key(b"XAssetsRecords ApplicationMTail", &Chain::Bitcoin),
+        &(Chain::Bitcoin, last),
+    );
+    unhashed::put(&twox_128(b"XAssetsRecords SerialNumber"), &u32::max_value());
+}
+
+fn withdraw_btc(who: u64) -> Result {
+    XRecords::withdrawal(
+        &who,
+        &b"BTC".to_vec(),
+        10,
+        b"addr".to_vec(),
+        b"ext".to_vec(),
+    )
+}
+
+#[test]
+fn test_serial_number_migration() {
+    with_externalities(&mut new_test_ext(), || {
+        let a: u64 = 1; // accountid
+        let btc_token = b"BTC".to_vec();
+        assert_ok!(XRecords::deposit(&a, &btc_token, 100));
+
+        let max = u32::max_value();
+        let ids = [max - 3, max - 2, max - 1];
+        put_legacy_applications(a, &ids);
+        let widened = ids.iter().map(|id| u64::from(*id)).collect::<Vec<_>>();
+
+        XRecords::migrate_serial_number(MAX_MIGRATION_NODES_PER_BLOCK);
+        assert!(XRecords::serial_number_migrated());
+        assert_eq!(XRecords::number(), u64::from(max));
+        for id in ids.iter() {
+            assert!(unhashed::get_raw(&XRecords::legacy_map_key(
+                b"XAssetsRecords ApplicationMap",
+                id
+            ))
+            .is_none());
+        }
+
+        // the typed list is readable and complete
+        assert_eq!(
+            XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap(),
+            widened
+        );
+        assert_eq!(
+            XRecords::application_mheader(Chain::Bitcoin)
+                .unwrap()
+                .index(),
+            widened[0]
+        );
+        assert_eq!(
+            XRecords::application_mtail(Chain::Bitcoin).unwrap().index(),
+            widened[2]
+        );
+        let middle = XRecords::application_map(widened[1]).unwrap();
+        assert_eq!(middle.prev(), Some(widened[0]));
+        assert_eq!(middle.next(), Some(widened[2]));
+        assert_eq!(XRecords::withdrawal_applications(Chain::Bitcoin).len(), 3);
+        assert_eq!(
+            XRecords::application_reference(widened[2]),
+            Some(b"ref".to_vec())
+        );
+
+        // the ids beyond u32::MAX are allocated after the migration
+        for _ in 0..2 {
+            assert_ok!(withdraw_btc(a));
+        }
+        let max = u64::from(max);
+        assert_eq!(
+            XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap(),
+            vec![widened[0], widened[1], widened[2], max, max + 1]
+        );
+        assert_eq!(XRecords::number(), max + 2);
+        assert_eq!(XAssets::free_balance_of(&a, &btc_token), 80);
+    })
+}
+
+#[test]
+fn test_serial_number_migration_paged() {
+    with_externalities(&mut new_test_ext(), || {
+        let a: u64 = 1; // accountid
+        let btc_token = b"BTC".to_vec();
+        assert_ok!(XRecords::deposit(&a, &btc_token, 100));
+
+        let max = u32::max_value();
+        let ids = [max - 3, max - 2, max - 1];
+        put_legacy_applications(a, &ids);
+        let widened = ids.iter().map(|id| u64::from(*id)).collect::<Vec<_>>();
+
+        // two applications are translated in a block, the withdrawals wait for the rest
+        XRecords::migrate_serial_number(2);
+        assert!(!XRecords::serial_number_migrated());
+        assert_eq!(
+            XRecords::legacy_application_cursor(Chain::Bitcoin),
+            Some((ids[2], Some(widened[1])))
+        );
+        assert_eq!(
+            XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap(),
+            widened[..2].to_vec()
+        );
+        assert_eq!(
+            XRecords::application_mtail(Chain::Bitcoin).unwrap().index(),
+            widened[2]
+        );
+        assert_err!(
+            withdraw_btc(a),
+            "the withdrawal applications of this chain are still being migrated"
+        );
+
+        XRecords::migrate_serial_number(2);
+        assert!(XRecords::serial_number_migrated());
+        assert_eq!(XRecords::legacy_application_cursor(Chain::Bitcoin), None);
+        assert_eq!(
+            XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap(),
+            widened
+        );
+        assert_eq!(XRecords::withdrawal_applications(Chain::Bitcoin).len(), 3);
+        assert_eq!(
+            XRecords::application_reference(widened[2]),
+            Some(b"ref".to_vec())
+        );
+
+        assert_ok!(withdraw_btc(a));
+        let max = u64::from(max);
+        assert_eq!(
+            XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap(),
+            vec![widened[0], widened[1], widened[2], max]
+        );
+    })
+}
+
+#[test]
+fn test_serial_number_migration_cuts_before_undecodable_application() {
+    with_externalities(&mut new_test_ext(), || {
+        let a: u64 = 1; // accountid
+        assert_ok!(XRecords::deposit(&a, &b"BTC".to_vec(), 100));
+
+        let max = u32::max_value();
+        let ids = [max - 3, max - 2, max - 1];
+        put_legacy_applications(a, &ids);
+        let bad_key = XRecords::legacy_map_key(b"XAssetsRecords ApplicationMap", &ids[2]);
+        unhashed::put_raw(&bad_key, &[1, 2, 3]);
+
+        // no panic, the list is cut before the undecodable one
+        XRecords::migrate_serial_number(MAX_MIGRATION_NODES_PER_BLOCK);
+        assert!(XRecords::serial_number_migrated());
+        assert_eq!(unhashed::get_raw(&bad_key), Some(vec![1, 2, 3]));
+        let (first, second) = (u64::from(ids[0]), u64::from(ids[1]));
+        assert_eq!(
+            XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap(),
+            vec![first, second]
+        );
+        assert_eq!(
+            XRecords::application_mtail(Chain::Bitcoin).unwrap().index(),
+            second
+        );
+        assert_eq!(XRecords::application_map(second).unwrap().next(), None);
+
+        // the new application is linked after the cut
+        assert_ok!(withdraw_btc(a));
+        assert_eq!(
+            XRecords::withdrawal_application_numbers(Chain::Bitcoin, 10).unwrap(),
+            vec![first, second, u64::from(max)]
+        );
+    })
+}
@@ -38,13 +38,15 @@ impl Default for ApplicationState {
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
 pub struct Application<AccountId, Balance, BlockNumber> {
-    pub id: u32,
+    pub id: u64,
     pub state: ApplicationState,
     pub applicant: AccountId,
     pub token: Token,
     pub balance: Balance,
     pub addr: AddrStr,
     pub ext: Memo,
+    /// The block number of the application, it's never stamped with the timestamp,
+    /// thus unaffected by a zero timestamp in the early blocks.
     pub height: BlockNumber,
 }
 
@@ -55,7 +57,7 @@ where
     BlockNumber: Codec + Clone,
 {
     pub fn new(
-        id: u32,
+        id: u64,
         applicant: AccountId,
         token: Token,
         balance: Balance,
@@ -74,7 +76,7 @@ where
             height,
         }
     }
-    pub fn id(&self) -> u32 {
+    pub fn id(&self) -> u64 {
         self.id
     }
     pub fn state(&self) -> ApplicationState {
@@ -101,12 +103,49 @@ where
 }
 
 impl<AccountId, Balance, BlockNumber> NodeT for Application<AccountId, Balance, BlockNumber> {
-    type Index = u32;
+    type Index = u64;
     fn index(&self) -> Self::Index {
         self.id
     }
 }
 
+/// application for withdrawal before the serial number is widened to u64, only for the migration
+#[derive(Encode, Decode)]
+pub struct LegacyApplication<AccountId, Balance, BlockNumber> {
+    pub id: u32,
+    pub state: ApplicationState,
+    pub applicant: AccountId,
+    pub token: Token,
+    pub balance: Balance,
+    pub addr: AddrStr,
+    pub ext: Memo,
+    pub height: BlockNumber,
+}
+
+impl<AccountId, Balance, BlockNumber> From<LegacyApplication<AccountId, Balance, BlockNumber>>
+    for Application<AccountId, Balance, BlockNumber>
+{
+    fn from(legacy: LegacyApplication<AccountId, Balance, BlockNumber>) -> Self {
+        Application {
+            id: u64::from(legacy.id),
+            state: legacy.state,
+            applicant: legacy.applicant,
+            token: legacy.token,
+            balance: legacy.balance,
+            addr: legacy.addr,
+            ext: legacy.ext,
+            height: legacy.height,
+        }
+    }
+}
+
+/// linked node of the legacy application, (prev, next, data)
+pub type LegacyApplicationNode<AccountId, Balance, BlockNumber> = (
+    Option<u32>,
+    Option<u32>,
+    LegacyApplication<AccountId, Balance, BlockNumber>,
+);
+
 pub struct LinkedMultiKey<T: Trait>(rstd::marker::PhantomData<T>);
 
 impl<T: Trait> LinkedNodeCollection for LinkedMultiKey<T> {
@@ -158,7 +197,7 @@ pub struct RecordInfo<AccountId, Balance, BlockNumber: Default, Timestamp> {
     /// tx height
     pub height_or_time: HeightOrTime<BlockNumber, Timestamp>,
     /// only for withdrawal, mark which id for application
-    pub withdrawal_id: u32, // only for withdrawal
+    pub withdrawal_id: u64, // only for withdrawal
     /// tx state
     pub state: TxState,
     /// application state
@@ -105,6 +105,9 @@ impl ProvideInherentData for InherentDataProvider {
 /// Something which can handle Aura consensus reports.
 pub trait HandleReport {
     fn handle_report(report: AuraReport);
+
+    /// The block of the given slot has been authored successfully.
+    fn handle_authored(_slot: usize) {}
 }
 
 impl HandleReport for () {
@@ -205,12 +208,15 @@ impl<T: Trait> Module<T> {
             last_slot < cur_slot,
             "Only one block may be authored per slot."
         );
+
+        let slot_to_usize = |slot: T::Moment| slot.saturated_into::<u64>() as usize;
+
+        H::handle_authored(slot_to_usize(cur_slot.clone()));
+
         if cur_slot == first_skipped {
             return;
         }
 
-        let slot_to_usize = |slot: T::Moment| slot.saturated_into::<u64>() as usize;
-
         let skipped_slots = cur_slot - last_slot - T::Moment::saturated_from(1);
 
         H::handle_report(AuraReport {
@@ -241,6 +247,14 @@ impl<T: xstaking::Trait + Trait> HandleReport for StakingSlasher<T> {
             xstaking::Module::<T>::on_offline_validator(&v.0);
         });
     }
+
+    fn handle_authored(slot: usize) {
+        let validators = xstaking::Module::<T>::validators();
+        if validators.is_empty() {
+            return;
+        }
+        xstaking::Module::<T>::on_validator_authored(&validators[slot % validators.len()].0);
+    }
 }
 
 impl<T: Trait> ProvideInherent for Module<T> {
@@ -39,7 +39,7 @@ impl<T: Trait> Module<T> {
             }
             Some(proposal) => {
                 // find proposal txhash
-                let confirmations = Module::<T>::confirmation_number();
+                let confirmations = Module::<T>::btc_network_settings().confirmation_number;
                 let mut current_hash = Self::best_index();
                 let mut tx_hash: Vec<u8> = Default::default();
                 let mut tx_confirmed = 1;
@@ -108,7 +108,7 @@ impl<T: Trait> Module<T> {
         let mut records = Vec::new();
 
         // find proposal txhash
-        let confirmations = Module::<T>::confirmation_number();
+        let confirmations = Module::<T>::btc_network_settings().confirmation_number;
         // not include confirmed block, when confirmations = 6, it's 0..5 => [0,1,2,3,4]
         // b(100)(confirmed) - b(101) - b(102) - b(103) - b(104) - b(105)(best)
         //                                                         current 0
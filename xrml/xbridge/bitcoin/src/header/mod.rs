@@ -69,7 +69,7 @@ pub fn check_prev_and_convert<T: Trait>(
     //           |--------- confirmations = 6 ------------|
     // b(prev) - b(confirm) - b - b - b - b - b(best_index)
     //      \    b_fork(ancient_fork)
-    let confirmations = Module::<T>::confirmation_number();
+    let confirmations = Module::<T>::btc_network_settings().confirmation_number;
     let this_height = prev_height + 1;
     if this_height <= best_height - (confirmations - 1) {
         error!("[check_prev_and_convert]|fatal error for bitcoin fork|best:{:?}|header:{:?}|confirmations:{:?}|height:{:} <= best_height - confirmations:{:}",
@@ -86,7 +86,7 @@ pub fn check_prev_and_convert<T: Trait>(
 
 pub fn remove_unused_headers<T: Trait>(header_info: &BlockHeaderInfo) {
     //delete old header info
-    let reserved = Module::<T>::reserved_block();
+    let reserved = Module::<T>::btc_network_settings().reserved_block;
     if header_info.height > reserved {
         let del = header_info.height - reserved;
         let v = Module::<T>::block_hash_for(&del);
@@ -115,7 +115,7 @@ pub fn remove_unused_headers<T: Trait>(header_info: &BlockHeaderInfo) {
 /// #issue 501 https://github.com/chainpool/ChainX/issues/501
 pub fn update_confirmed_header<T: Trait>(header_info: &BlockHeaderInfo) -> (H256, u32) {
     // update confirmd status
-    let confirmations = Module::<T>::confirmation_number();
+    let confirmations = Module::<T>::btc_network_settings().confirmation_number;
     let mut prev_hash = header_info.header.previous_header_hash;
     // start from prev, thus start from 1,when confirmations = 6, it's 1..5 => [1,2,3,4]
     // b(100)(confirmed) - b(101)(need_confirmed) - b(102) - b(103) - b(104) - b(105)(best) - b(106)(current)
@@ -184,7 +184,7 @@ fn handle_confirmed_block<T: Trait>(confirmed_header: &BlockHeaderInfo) {
 ///                           current 3
 ///       prev        current 4
 pub fn find_confirmed_block<T: Trait>(current: &H256) -> BlockHeaderInfo {
-    let confirmations = Module::<T>::confirmation_number();
+    let confirmations = Module::<T>::btc_network_settings().confirmation_number;
     let mut current_hash = *current;
     for _ in 0..(confirmations - 1) {
         if let Some(info) = Module::<T>::block_header_for(current_hash) {
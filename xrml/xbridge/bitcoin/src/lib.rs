@@ -17,6 +17,7 @@ use parity_codec::Decode;
 // Substrate
 use primitives::traits::MaybeDebug;
 use rstd::{prelude::*, result};
+use support::storage::unhashed;
 use support::{decl_event, decl_module, decl_storage, dispatch::Result, StorageMap, StorageValue};
 use system::ensure_signed;
 
@@ -31,7 +32,7 @@ use xr_primitives::{generic::b58, AddrStr};
 use xrecords::{ApplicationState, TxState};
 use xsupport::{debug, ensure_with_errorlog, error, info, warn};
 #[cfg(feature = "std")]
-use xsupport::{trustees, u8array_to_addr};
+use xsupport::{token, trustees, u8array_to_addr};
 
 // light-bitcoin
 use btc_chain::{BlockHeader, Transaction};
@@ -41,23 +42,27 @@ pub use btc_primitives::H264;
 use btc_ser::{deserialize, Reader};
 
 pub use self::traits::RelayTransaction;
-use self::tx::handler::remove_pending_deposit;
+use self::tx::handler::{credit_deposit, remove_pending_deposit};
 #[cfg(feature = "std")]
 use self::tx::utils::addr2vecu8;
 use self::tx::utils::{get_sig_num, get_trustee_address_pair, trustee_session};
 use self::tx::{
-    check_withdraw_tx, create_multi_address, detect_transaction_type, handle_tx,
-    insert_trustee_vote_state, parse_and_check_signed_tx, validate_transaction,
+    check_withdraw_tx, create_multi_address, detect_transaction_type,
+    handle_pending_large_deposits, handle_tx, insert_trustee_vote_state, parse_and_check_signed_tx,
+    validate_transaction,
 };
-use self::types::DepositCache;
 pub use self::types::{
-    BlockHeaderInfo, Params, RelayTx, TrusteeAddrInfo, TxInfo, TxType, VoteResult,
-    WithdrawalProposal,
+    BlockHeaderInfo, LargeDepositCache, NetworkSettings, Params, RelayTx, TrusteeAddrInfo, TxInfo,
+    TxType, VoteResult, WithdrawalProposal,
 };
+use self::types::{DepositCache, LegacyWithdrawalProposal};
 
 pub use self::lockup::types::LockupRelayTx;
 use self::lockup::Trait as LockupTrait;
 
+/// the max number of the large deposits waiting for more confirmations
+pub const MAX_PENDING_LARGE_DEPOSITS: usize = 256;
+
 pub trait Trait:
     system::Trait + timestamp::Trait + xsystem::Trait + xrecords::Trait + xbridge_common::Trait
 {
@@ -82,16 +87,26 @@ decl_event!(
         Deposit(AccountId, Chain, Token, Balance, Memo, AddrStr, Vec<u8>, TxState),
         /// who, Chain, Token, balance,  Chain Addr
         DepositPending(AccountId, Chain, Token, Balance, AddrStr),
+        /// who, Token, balance, accumulated balance, the deposit is accumulated rather than credited
+        /// until the accumulated balance reaches the minimum deposit
+        DepositBelowMinimum(AccountId, Token, Balance, Balance),
+        /// who, Token, balance, the accumulated deposits are credited by root
+        AccumulatedDepositReleased(AccountId, Token, Balance),
+        /// tx hash, balance, required confirmations, the large deposit waits for more confirmations
+        LargeDepositDeferred(H256, Balance, u32),
+        /// tx hash, the deferred large deposit fails to be handled and is dropped, root could handle
+        /// it again by `handle_transaction`
+        LargeDepositDropped(H256),
         /// who, withdrawal id, txid, TxState
-        Withdrawal(u32, Vec<u8>, TxState),
+        Withdrawal(u64, Vec<u8>, TxState),
         /// create withdraw tx, who proposal, withdrawal list id
-        CreateWithdrawalProposal(AccountId, Vec<u32>),
+        CreateWithdrawalProposal(AccountId, Vec<u64>),
         /// Sign withdraw tx
         SignWithdrawalProposal(AccountId, bool),
         /// WithdrawalFatalErr, tx hash, Proposal hash,
         WithdrawalFatalErr(Vec<u8>, Vec<u8>),
         /// reject_count, sum_count, withdrawal id list
-        DropWithdrawalProposal(u32, u32, Vec<u32>),
+        DropWithdrawalProposal(u32, u32, Vec<u64>),
     }
 );
 
@@ -116,6 +131,8 @@ decl_storage! {
 
         /// unclaim deposit info, addr => tx_hash, btc value, blockhash
         pub PendingDepositMap get(pending_deposit): map BitcoinAddress => Option<Vec<DepositCache>>;
+        /// large deposits which are confirmed but not credited until the higher tier is reached
+        pub PendingLargeDeposits get(pending_large_deposits): Vec<LargeDepositCache>;
         /// withdrawal tx outs for account, tx_hash => outs ( out index => withdrawal account )
         pub CurrentWithdrawalProposal get(withdrawal_proposal): Option<WithdrawalProposal<T::AccountId>>;
 
@@ -129,12 +146,28 @@ decl_storage! {
         pub ReservedBlock get(reserved_block) config(): u32;
         /// get ConfirmationNumber from genesis_config
         pub ConfirmationNumber get(confirmation_number) config(): u32;
+        /// header relay settings for each bitcoin-like network, the `BTC` network falls back
+        /// to `ConfirmationNumber` and `ReservedBlock` when not set
+        pub NetworkSettingsOf get(network_settings_of): map Token => Option<NetworkSettings>;
         /// get BtcWithdrawalFee from genesis_config
         pub BtcWithdrawalFee get(btc_withdrawal_fee) config(): u64;
         /// min deposit value limit, default is 10w sotashi(0.001 BTC)
         pub BtcMinDeposit get(btc_min_deposit): u64 = 100_000;
+        /// the deposit less than this amount would not be credited but accumulated
+        pub MinDepositAmount get(min_deposit_amount): map Token => T::Balance;
+        /// the deposits less than `MinDepositAmount` accumulated for each account, credited once the sum reaches it
+        pub AccumulatedDepositOf get(accumulated_deposit_of): map T::AccountId => u64;
+        /// (threshold, confirmation number), the deposit not less than the threshold would be
+        /// credited after the confirmation number instead of the `confirmation_number` of network
+        pub LargeDepositConfirmation get(large_deposit_confirmation): map Token => Option<(T::Balance, u32)>;
         /// max withdraw account count in bitcoin withdrawal transaction
         pub MaxWithdrawalCount get(max_withdrawal_count) config(): u32;
+        /// max number of txids could be attached to a block header
+        pub MaxTxidsPerHeader get(max_txids_per_header): u32 = 1024;
+        /// max number of competing headers could be stored at the same height
+        pub MaxHeadersPerHeight get(max_headers_per_height): u32 = 8;
+        /// whether the withdrawal proposal written with the legacy u32 withdrawal id is translated
+        pub WithdrawalProposalMigrated get(withdrawal_proposal_migrated): bool;
     }
     add_extra_genesis {
         config(genesis_hash): H256;
@@ -189,6 +222,12 @@ decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn deposit_event<T>() = default;
 
+        fn on_initialize() {
+            if !Self::withdrawal_proposal_migrated() {
+                Self::migrate_withdrawal_proposal();
+            }
+        }
+
         /// if use `BlockHeader` struct would export in metadata, cause complex in front-end
         pub fn push_header(origin, header: Vec<u8>) -> Result {
             let _from = ensure_signed(origin)?;
@@ -213,7 +252,7 @@ decl_module! {
             Ok(())
         }
 
-        pub fn create_withdraw_tx(origin, withdrawal_id_list: Vec<u32>, tx: Vec<u8>) -> Result {
+        pub fn create_withdraw_tx(origin, withdrawal_id_list: Vec<u64>, tx: Vec<u8>) -> Result {
             let from = ensure_signed(origin)?;
             // commiter must in trustee list
             Self::ensure_trustee(&from)?;
@@ -241,7 +280,7 @@ decl_module! {
             Ok(())
         }
 
-        pub fn fix_withdrawal_state_by_trustees(origin, withdrawal_id: u32, state: ApplicationState) -> Result {
+        pub fn fix_withdrawal_state_by_trustees(origin, withdrawal_id: u64, state: ApplicationState) -> Result {
             let from = ensure_signed(origin)?;
             T::TrusteeMultiSigProvider::check_multisig(&from)?;
             xrecords::Module::<T>::fix_withdrawal_state_by_trustees(Chain::Bitcoin, withdrawal_id, state)
@@ -274,6 +313,50 @@ decl_module! {
             BtcMinDeposit::<T>::put(value.into());
         }
 
+        pub fn set_large_deposit_confirmation(token: Token, setting: Option<(T::Balance, u32)>) -> Result {
+            match setting {
+                Some((threshold, confirmation_number)) => {
+                    if confirmation_number <= Self::network_settings(&token).confirmation_number {
+                        return Err("large deposit confirmation number should be greater than the confirmation number");
+                    }
+                    LargeDepositConfirmation::<T>::insert(token, (threshold, confirmation_number));
+                }
+                None => LargeDepositConfirmation::<T>::remove(token),
+            }
+            Ok(())
+        }
+
+        pub fn set_max_txids_per_header(value: u32) {
+            MaxTxidsPerHeader::<T>::put(value);
+        }
+
+        pub fn set_max_headers_per_height(value: u32) {
+            MaxHeadersPerHeight::<T>::put(value);
+        }
+
+        /// Set the minimum amount credited for a deposit of the token, the deposits less than it
+        /// are accumulated for the account until the sum reaches it.
+        pub fn set_min_deposit_amount(token: Token, value: T::Balance) {
+            MinDepositAmount::<T>::insert(token, value);
+        }
+
+        /// Credit the accumulated deposits of the account regardless of the minimum, e.g. which
+        /// would never reach it after the minimum is raised.
+        pub fn release_accumulated_deposit(who: T::AccountId) -> Result {
+            let accumulated = AccumulatedDepositOf::<T>::take(&who);
+            if accumulated == 0 {
+                return Err("no accumulated deposit for this account");
+            }
+            info!("[release_accumulated_deposit]|who:{:?}|balance:{:}", who, accumulated);
+            credit_deposit::<T>(&who, accumulated);
+            Self::deposit_event(RawEvent::AccumulatedDepositReleased(
+                who,
+                Self::TOKEN.to_vec(),
+                accumulated.into(),
+            ));
+            Ok(())
+        }
+
         pub fn set_btc_deposit_limit_by_trustees(origin, value: T::Balance) {
             let from = ensure_signed(origin)?;
             T::TrusteeMultiSigProvider::check_multisig(&from)?;
@@ -297,6 +380,13 @@ decl_module! {
             Self::remove_pending(addr, who)
         }
 
+        pub fn set_network_settings(token: Token, settings: NetworkSettings) -> Result {
+            Self::check_network_settings(&settings)?;
+            info!("[set_network_settings]|token:{:?}|settings:{:?}", token!(token), settings);
+            NetworkSettingsOf::<T>::insert(token, settings);
+            Ok(())
+        }
+
         /// Dangerous! Be careful to set BestIndex
         pub fn set_best_index(hash: H256) {
             warn!("[set_best_index]|Dangerous! set new best index|hash:{:?}", hash);
@@ -479,6 +569,81 @@ impl<T: Trait> Module<T> {
         BitcoinAddress::from_layout(&r)
     }
 
+    /// Header relay settings for the network of `token`, the global `ConfirmationNumber` and
+    /// `ReservedBlock` are used when the network is not configured.
+    pub fn network_settings(token: &Token) -> NetworkSettings {
+        Self::network_settings_of(token).unwrap_or_else(|| NetworkSettings {
+            confirmation_number: Self::confirmation_number(),
+            reserved_block: Self::reserved_block(),
+        })
+    }
+
+    /// Header relay settings for the bitcoin network which this module is relaying.
+    pub fn btc_network_settings() -> NetworkSettings {
+        Self::network_settings(&Self::TOKEN.to_vec())
+    }
+
+    /// Confirmations required before crediting a deposit of `balance`, the large deposit tier
+    /// applies when the balance reaches its threshold.
+    pub fn deposit_confirmation_number(token: &Token, balance: T::Balance) -> u32 {
+        let confirmation_number = Self::network_settings(token).confirmation_number;
+        match Self::large_deposit_confirmation(token) {
+            Some((threshold, large)) if balance >= threshold => large.max(confirmation_number),
+            _ => confirmation_number,
+        }
+    }
+
+    /// Confirmations of the header at `height` on the best chain, the best header counts one.
+    pub fn confirmations_at(height: u32) -> u32 {
+        Self::block_header_for(Self::best_index())
+            .map(|best| (best.height + 1).saturating_sub(height))
+            .unwrap_or_default()
+    }
+
+    /// The header of the best chain at the height, walking back from the best header.
+    pub fn best_chain_header_at(height: u32) -> Option<BlockHeaderInfo> {
+        let mut current = Self::block_header_for(Self::best_index())?;
+        while current.height > height {
+            current = Self::block_header_for(&current.header.previous_header_hash)?;
+        }
+        if current.height == height {
+            Some(current)
+        } else {
+            None
+        }
+    }
+
+    /// Height of the stored header relative to the bridge genesis, `None` if the header is
+    /// unknown or lower than the genesis.
+    pub fn height_above_genesis(hash: &H256) -> Option<u32> {
+        let header_info = Self::block_header_for(hash)?;
+        let (_, genesis_height) = Self::genesis_info();
+        header_info.height.checked_sub(genesis_height)
+    }
+
+    /// Translate the current withdrawal proposal written with the legacy `u32` withdrawal id.
+    fn migrate_withdrawal_proposal() {
+        let key = runtime_io::twox_128(b"XBridgeOfBTC CurrentWithdrawalProposal");
+        if let Some(legacy) = unhashed::get::<LegacyWithdrawalProposal<T::AccountId>>(&key) {
+            info!(
+                "[migrate_withdrawal_proposal]|migrate the withdrawal proposal|withdrawal idlist:{:?}",
+                legacy.withdrawal_id_list
+            );
+            CurrentWithdrawalProposal::<T>::put(WithdrawalProposal::from(legacy));
+        }
+        WithdrawalProposalMigrated::<T>::put(true);
+    }
+
+    fn check_network_settings(settings: &NetworkSettings) -> Result {
+        if settings.confirmation_number == 0 {
+            return Err("confirmation number should be greater than 0");
+        }
+        if settings.reserved_block <= settings.confirmation_number {
+            return Err("reserved block should be greater than confirmation number");
+        }
+        Ok(())
+    }
+
     fn ensure_trustee(who: &T::AccountId) -> Result {
         let trustee_session_info = trustee_session::<T>()?;
         if trustee_session_info.trustee_list.iter().any(|n| n == who) {
@@ -511,6 +676,11 @@ impl<T: Trait> Module<T> {
         // convert btc header to self header info
         let header_info: BlockHeaderInfo =
             header::check_prev_and_convert::<T>(header).map_err(|e| e.info())?;
+        // the siblings already stored are kept, reject the excess before the verification
+        Self::check_headers_per_height(
+            header_info.height,
+            &header_info.header.previous_header_hash,
+        )?;
         // check
         let c = header::HeaderVerifier::new::<T>(&header_info.header, header_info.height)
             .map_err(|e| e.info())?;
@@ -548,6 +718,8 @@ impl<T: Trait> Module<T> {
             );
             // change new best index
             BestIndex::<T>::put(hash);
+            // credit the large deposits which reach their tier with the new best header
+            handle_pending_large_deposits::<T>();
             (confirmed_hash, confirmed_height)
         } else {
             info!("[apply_push_header]|best index larger than this height|best height:{:}|this height{:}",
@@ -573,6 +745,39 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// The header extending the best header is always accepted, so that the best chain could
+    /// not be stalled by the siblings filling up the height.
+    fn check_headers_per_height(height: u32, prev_hash: &H256) -> Result {
+        if *prev_hash == Self::best_index() {
+            return Ok(());
+        }
+        let max = Self::max_headers_per_height();
+        ensure_with_errorlog!(
+            Self::block_hash_for(height).len() < max as usize,
+            "Too many headers at this height.",
+            "height:{:}|max:{:}|prev hash:{:}",
+            height,
+            max,
+            prev_hash,
+        );
+        Ok(())
+    }
+
+    fn append_txid(header_info: &mut BlockHeaderInfo, txid: H256) -> Result {
+        let max = Self::max_txids_per_header();
+        if header_info.txid_list.len() >= max as usize {
+            error!(
+                "[append_txid]|txid list of this header is full|block_hash:{:}|max:{:}|txid:{:}",
+                header_info.header.hash(),
+                max,
+                txid
+            );
+            return Err("txid list of this header is full");
+        }
+        header_info.txid_list.push(txid);
+        Ok(())
+    }
+
     fn apply_push_transaction<RT: RelayTransaction + MaybeDebug>(tx: RT) -> Result {
         let tx_hash = tx.tx_hash();
         let mut header_info = Module::<T>::block_header_for(tx.block_hash()).ok_or_else(|| {
@@ -590,7 +795,7 @@ impl<T: Trait> Module<T> {
         let confirmed = header_info.confirmed;
         // notice same tx may belong to different forked block, after check merkle proof, it's all valid
         if !header_info.txid_list.contains(&tx_hash) {
-            header_info.txid_list.push(tx_hash);
+            Self::append_txid(&mut header_info, tx_hash)?;
             // modify block info storage
             BlockHeaderFor::<T>::insert(tx.block_hash(), header_info);
         } else {
@@ -667,7 +872,7 @@ impl<T: Trait> Module<T> {
     fn apply_create_withdraw(
         who: T::AccountId,
         tx: Transaction,
-        withdrawal_id_list: Vec<u32>,
+        withdrawal_id_list: Vec<u64>,
     ) -> Result {
         let withdraw_amount = Self::max_withdrawal_count();
         if withdrawal_id_list.len() > withdraw_amount as usize {
@@ -1,5 +1,7 @@
 use super::*;
 
+use support::{assert_err, assert_ok};
+
 #[test]
 fn test() {
     with_externalities(&mut new_test_ext(), || {
@@ -64,6 +66,177 @@ fn test_init_mock_blocks() {
     );
 }
 
+#[test]
+fn test_network_settings_isolation() {
+    with_externalities(&mut new_test_ext(), || {
+        let btc = b"BTC".to_vec();
+        let bch = b"BCH".to_vec();
+        // not configured, fallback to the global settings
+        let default_settings = NetworkSettings {
+            confirmation_number: 3,
+            reserved_block: 2100,
+        };
+        assert_eq!(XBridgeOfBTC::network_settings(&btc), default_settings);
+        assert_eq!(XBridgeOfBTC::network_settings(&bch), default_settings);
+
+        let bch_settings = NetworkSettings {
+            confirmation_number: 6,
+            reserved_block: 1000,
+        };
+        assert_ok!(XBridgeOfBTC::set_network_settings(
+            bch.clone(),
+            bch_settings
+        ));
+        assert_eq!(XBridgeOfBTC::network_settings(&bch), bch_settings);
+        assert_eq!(XBridgeOfBTC::btc_network_settings(), default_settings);
+
+        let btc_settings = NetworkSettings {
+            confirmation_number: 1,
+            reserved_block: 2,
+        };
+        assert_ok!(XBridgeOfBTC::set_network_settings(
+            btc.clone(),
+            btc_settings
+        ));
+        assert_eq!(XBridgeOfBTC::btc_network_settings(), btc_settings);
+        assert_eq!(XBridgeOfBTC::network_settings(&bch), bch_settings);
+
+        // the btc header window follows its own `reserved_block`
+        let (genesis, height) = XBridgeOfBTC::genesis_info();
+        let genesis_info = XBridgeOfBTC::block_header_for(&genesis.hash()).unwrap();
+        let header_info = BlockHeaderInfo {
+            height: height + 2,
+            ..genesis_info
+        };
+        crate::header::remove_unused_headers::<Test>(&header_info);
+        assert!(XBridgeOfBTC::block_hash_for(height).is_empty());
+        assert!(XBridgeOfBTC::block_header_for(&genesis.hash()).is_none());
+
+        assert_err!(
+            XBridgeOfBTC::set_network_settings(
+                bch.clone(),
+                NetworkSettings {
+                    confirmation_number: 0,
+                    reserved_block: 1000,
+                }
+            ),
+            "confirmation number should be greater than 0"
+        );
+        assert_err!(
+            XBridgeOfBTC::set_network_settings(
+                bch,
+                NetworkSettings {
+                    confirmation_number: 6,
+                    reserved_block: 6,
+                }
+            ),
+            "reserved block should be greater than confirmation number"
+        );
+    })
+}
+
+#[test]
+fn test_height_above_genesis() {
+    with_externalities(&mut new_test_ext(), || {
+        let (genesis, height) = XBridgeOfBTC::genesis_info();
+        let genesis_info = XBridgeOfBTC::block_header_for(&genesis.hash()).unwrap();
+        assert_eq!(XBridgeOfBTC::height_above_genesis(&genesis.hash()), Some(0));
+
+        // build a header chain upon the genesis
+        let mut prev_hash = genesis.hash();
+        let mut hashes = vec![];
+        for i in 1..=3 {
+            let header = BlockHeader {
+                previous_header_hash: prev_hash,
+                nonce: i,
+                ..genesis_info.header.clone()
+            };
+            prev_hash = header.hash();
+            hashes.push(prev_hash);
+            BlockHeaderFor::<Test>::insert(
+                &prev_hash,
+                BlockHeaderInfo {
+                    header,
+                    height: height + i,
+                    confirmed: false,
+                    txid_list: vec![],
+                },
+            );
+        }
+        for (i, hash) in hashes.iter().enumerate() {
+            assert_eq!(XBridgeOfBTC::height_above_genesis(hash), Some(i as u32 + 1));
+        }
+
+        assert_eq!(XBridgeOfBTC::height_above_genesis(&H256::default()), None);
+    })
+}
+
+#[test]
+fn test_max_txids_per_header() {
+    with_externalities(&mut new_test_ext(), || {
+        let (genesis, _) = XBridgeOfBTC::genesis_info();
+        let mut header_info = XBridgeOfBTC::block_header_for(&genesis.hash()).unwrap();
+        assert_ok!(XBridgeOfBTC::set_max_txids_per_header(2));
+
+        assert_ok!(XBridgeOfBTC::append_txid(
+            &mut header_info,
+            H256::from_slice(&[1; 32])
+        ));
+        assert_ok!(XBridgeOfBTC::append_txid(
+            &mut header_info,
+            H256::from_slice(&[2; 32])
+        ));
+        assert_err!(
+            XBridgeOfBTC::append_txid(&mut header_info, H256::from_slice(&[3; 32])),
+            "txid list of this header is full"
+        );
+        assert_eq!(
+            header_info.txid_list,
+            vec![H256::from_slice(&[1; 32]), H256::from_slice(&[2; 32])]
+        );
+    })
+}
+
+#[test]
+fn test_max_headers_per_height() {
+    with_externalities(&mut new_test_ext(), || {
+        let (genesis, height) = XBridgeOfBTC::genesis_info();
+        assert_ok!(XBridgeOfBTC::set_max_headers_per_height(2));
+
+        // the siblings on a fork are capped.
+        let fork = H256::from_slice(&[9; 32]);
+        BlockHashFor::<Test>::insert(height + 1, vec![H256::from_slice(&[1; 32])]);
+        assert_ok!(XBridgeOfBTC::check_headers_per_height(height + 1, &fork));
+
+        BlockHashFor::<Test>::mutate(height + 1, |v| v.push(H256::from_slice(&[2; 32])));
+        assert_err!(
+            XBridgeOfBTC::check_headers_per_height(height + 1, &fork),
+            "Too many headers at this height."
+        );
+
+        // the header extending the best header is always accepted.
+        assert_eq!(XBridgeOfBTC::best_index(), genesis.hash());
+        assert_ok!(XBridgeOfBTC::check_headers_per_height(
+            height + 1,
+            &genesis.hash()
+        ));
+
+        // the excess sibling is rejected in the header submission path once it doesn't extend
+        // the best header.
+        BestIndex::<Test>::put(fork);
+        let header = BlockHeader {
+            previous_header_hash: genesis.hash(),
+            nonce: 1,
+            ..genesis
+        };
+        assert_err!(
+            XBridgeOfBTC::apply_push_header(header),
+            "Too many headers at this height."
+        );
+        assert_eq!(XBridgeOfBTC::block_hash_for(height + 1).len(), 2);
+    })
+}
+
 //#[test]
 //fn test_genesis() {
 //    with_externalities(&mut new_test_mock_ext(), || {
@@ -10,7 +10,7 @@ use primitives::traits::{BlakeTwo256, IdentityLookup};
 use primitives::BuildStorage;
 use substrate_primitives::ed25519::Public;
 use substrate_primitives::{Blake2Hasher, H256 as S_H256};
-use support::impl_outer_origin;
+use support::{impl_outer_event, impl_outer_origin};
 
 // light-bitcoin
 use btc_primitives::{h256_from_rev_str, Compact};
@@ -20,6 +20,17 @@ impl_outer_origin! {
     pub enum Origin for Test {}
 }
 
+mod xbitcoin {
+    // This hack is required for `impl_outer_event!`.
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        xbitcoin<T>,
+    }
+}
+
 type AccountId = Public;
 
 #[derive(Clone, Eq, PartialEq)]
@@ -35,7 +46,7 @@ impl system::Trait for Test {
     type AccountId = AccountId;
     type Lookup = IdentityLookup<AccountId>;
     type Header = Header;
-    type Event = ();
+    type Event = TestEvent;
     type Log = DigestItem;
 }
 
@@ -93,6 +104,7 @@ impl xrecords::Trait for Test {
 impl xassets::Trait for Test {
     type Balance = u64;
     type OnNewAccount = ();
+    type OnFreeBalanceZero = ();
     type OnAssetChanged = ();
     type OnAssetRegisterOrRevoke = ();
     type DetermineTokenJackpotAccountId = ();
@@ -118,7 +130,7 @@ impl Trait for Test {
     type TrusteeSessionProvider = DummyTrusteeSession;
     type TrusteeMultiSigProvider = DummyBitcoinTrusteeMultiSig;
     type CrossChainProvider = DummyCrossChain;
-    type Event = ();
+    type Event = TestEvent;
 }
 
 pub struct DummyTrusteeSession;
@@ -186,6 +198,7 @@ impl xbridge_common::traits::TrusteeMultiSig<AccountId> for DummyBitcoinTrusteeM
     }
 }
 
+pub type System = system::Module<Test>;
 pub type XAssets = xassets::Module<Test>;
 pub type XBridgeOfBTC = Module<Test>;
 pub type XBridgeOfBTCLockup = lockup::Module<Test>;
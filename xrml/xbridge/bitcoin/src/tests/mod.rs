@@ -15,6 +15,7 @@ use hex_literal::hex;
 
 use runtime_io::with_externalities;
 use substrate_primitives::crypto::UncheckedInto;
+use support::{assert_err, assert_ok};
 
 use btc_crypto::dhash160;
 use btc_primitives::h256_from_rev_str;
@@ -60,3 +61,244 @@ fn test_accountid() {
 //        //        handle_condidate::<Test>(tx).unwrap();
 //    })
 //}
+
+#[test]
+fn test_min_deposit_amount() {
+    with_externalities(&mut new_test_ext(), || {
+        let btc = xassets::Asset::new(
+            XBridgeOfBTC::TOKEN.to_vec(),
+            b"X-BTC".to_vec(),
+            xassets::Chain::Bitcoin,
+            8,
+            b"ChainX's Cross-chain Bitcoin".to_vec(),
+        )
+        .unwrap();
+        XAssets::bootstrap_register_asset(btc, true, true).unwrap();
+
+        let who: substrate_primitives::ed25519::Public =
+            substrate_primitives::H256::repeat_byte(0x1).unchecked_into();
+        let token = XBridgeOfBTC::TOKEN.to_vec();
+        XBridgeOfBTC::set_min_deposit_amount(token.clone(), 1000).unwrap();
+
+        // less than the minimum, accumulated rather than credited
+        assert!(!tx::handler::deposit_token::<Test>(&who, 600));
+        assert_eq!(XAssets::free_balance_of(&who, &token), 0);
+        assert_eq!(XBridgeOfBTC::accumulated_deposit_of(&who), 600);
+        assert!(!tx::handler::deposit_token::<Test>(&who, 300));
+        assert_eq!(XBridgeOfBTC::accumulated_deposit_of(&who), 900);
+
+        let events = System::events()
+            .into_iter()
+            .map(|r| r.event)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                TestEvent::xbitcoin(RawEvent::DepositBelowMinimum(
+                    who.clone(),
+                    token.clone(),
+                    600,
+                    600
+                )),
+                TestEvent::xbitcoin(RawEvent::DepositBelowMinimum(
+                    who.clone(),
+                    token.clone(),
+                    300,
+                    900
+                )),
+            ]
+        );
+
+        // the accumulated balance is credited along with the deposit reaching the minimum
+        assert!(tx::handler::deposit_token::<Test>(&who, 100));
+        assert_eq!(XAssets::free_balance_of(&who, &token), 1000);
+        assert_eq!(XBridgeOfBTC::accumulated_deposit_of(&who), 0);
+
+        // the accumulated deposits are released by root after the minimum is raised
+        XBridgeOfBTC::set_min_deposit_amount(token.clone(), 10_000).unwrap();
+        assert!(!tx::handler::deposit_token::<Test>(&who, 2000));
+        assert_eq!(XBridgeOfBTC::accumulated_deposit_of(&who), 2000);
+        assert_ok!(XBridgeOfBTC::release_accumulated_deposit(who.clone()));
+        assert_eq!(XAssets::free_balance_of(&who, &token), 3000);
+        assert_eq!(XBridgeOfBTC::accumulated_deposit_of(&who), 0);
+        assert_eq!(
+            System::events().last().map(|r| r.event.clone()),
+            Some(TestEvent::xbitcoin(RawEvent::AccumulatedDepositReleased(
+                who.clone(),
+                token.clone(),
+                2000
+            )))
+        );
+        assert_err!(
+            XBridgeOfBTC::release_accumulated_deposit(who.clone()),
+            "no accumulated deposit for this account"
+        );
+    })
+}
+
+#[test]
+fn test_large_deposit_confirmation() {
+    with_externalities(&mut new_test_ext(), || {
+        let btc = xassets::Asset::new(
+            XBridgeOfBTC::TOKEN.to_vec(),
+            b"X-BTC".to_vec(),
+            xassets::Chain::Bitcoin,
+            8,
+            b"ChainX's Cross-chain Bitcoin".to_vec(),
+        )
+        .unwrap();
+        XAssets::bootstrap_register_asset(btc, true, true).unwrap();
+
+        let who: substrate_primitives::ed25519::Public =
+            substrate_primitives::H256::repeat_byte(0x1).unchecked_into();
+        let token = XBridgeOfBTC::TOKEN.to_vec();
+        assert_eq!(
+            XBridgeOfBTC::set_large_deposit_confirmation(token.clone(), Some((100_000, 3))),
+            Err("large deposit confirmation number should be greater than the confirmation number")
+        );
+        XBridgeOfBTC::set_large_deposit_confirmation(token.clone(), Some((100_000, 6))).unwrap();
+        assert_eq!(XBridgeOfBTC::deposit_confirmation_number(&token, 99_999), 3);
+        assert_eq!(
+            XBridgeOfBTC::deposit_confirmation_number(&token, 100_000),
+            6
+        );
+
+        // two forks from the genesis, the deposits are relayed in the first block of the fork a
+        let (genesis, height) = XBridgeOfBTC::genesis_info();
+        let genesis_info = XBridgeOfBTC::block_header_for(&genesis.hash()).unwrap();
+        let (small, large) = (H256::from_slice(&[1; 32]), H256::from_slice(&[2; 32]));
+        let fork = |nonce: u32, txid_list: Vec<H256>| {
+            let mut prev = genesis_info.clone();
+            (1..=6)
+                .map(|i| {
+                    let info = BlockHeaderInfo {
+                        header: BlockHeader {
+                            previous_header_hash: prev.header.hash(),
+                            nonce: nonce + i,
+                            ..genesis_info.header.clone()
+                        },
+                        height: height + i,
+                        confirmed: false,
+                        txid_list: if i == 1 { txid_list.clone() } else { vec![] },
+                    };
+                    let hash = info.header.hash();
+                    BlockHeaderFor::<Test>::insert(&hash, info.clone());
+                    BlockHashFor::<Test>::mutate(info.height, |v| v.push(hash));
+                    prev = info;
+                    hash
+                })
+                .collect::<Vec<_>>()
+        };
+        let fork_a = fork(0, vec![small, large]);
+        let fork_b = fork(100, vec![]);
+        let set_best = |hash: H256| BestIndex::<Test>::put(hash);
+
+        // the best header is 2 blocks above the deposit, i.e. the base 3 confirmations
+        set_best(fork_a[2]);
+        assert_eq!(XBridgeOfBTC::confirmations_at(height + 1), 3);
+        assert_eq!(
+            XBridgeOfBTC::best_chain_header_at(height + 1).map(|info| info.header.hash()),
+            Some(fork_a[0])
+        );
+
+        // small deposit credits at the base confirmations
+        assert_eq!(
+            tx::handler::defer_large_deposit::<Test>(&small, height + 1, 99_999),
+            Ok(false)
+        );
+        assert!(tx::handler::deposit_token::<Test>(&who, 99_999));
+        assert_eq!(XAssets::free_balance_of(&who, &token), 99_999);
+
+        // large deposit is deferred at the base confirmations
+        assert_eq!(
+            tx::handler::defer_large_deposit::<Test>(&large, height + 1, 200_000),
+            Ok(true)
+        );
+        let cache = LargeDepositCache {
+            txid: large,
+            height: height + 1,
+            block_hash: fork_a[0],
+            balance: 200_000,
+        };
+        assert_eq!(XBridgeOfBTC::pending_large_deposits(), vec![cache.clone()]);
+        assert_eq!(XAssets::free_balance_of(&who, &token), 99_999);
+
+        // still waiting before the higher tier
+        set_best(fork_a[4]);
+        tx::handle_pending_large_deposits::<Test>();
+        assert_eq!(XBridgeOfBTC::pending_large_deposits(), vec![cache.clone()]);
+
+        // the block of the deposit is no longer in the best chain after the reorganization
+        set_best(fork_b[5]);
+        assert_eq!(XBridgeOfBTC::confirmations_at(height + 1), 6);
+        tx::handle_pending_large_deposits::<Test>();
+        assert!(XBridgeOfBTC::pending_large_deposits().is_empty());
+        assert_eq!(XAssets::free_balance_of(&who, &token), 99_999);
+
+        // the higher tier is reached, but the deposit fails to be handled and is dropped
+        set_best(fork_a[4]);
+        assert_eq!(
+            tx::handler::defer_large_deposit::<Test>(&large, height + 1, 200_000),
+            Ok(true)
+        );
+        set_best(fork_a[5]);
+        tx::handle_pending_large_deposits::<Test>();
+        assert!(XBridgeOfBTC::pending_large_deposits().is_empty());
+        assert_eq!(
+            System::events().last().map(|r| r.event.clone()),
+            Some(TestEvent::xbitcoin(RawEvent::LargeDepositDropped(large)))
+        );
+        assert_eq!(XAssets::free_balance_of(&who, &token), 99_999);
+
+        // credited once the higher tier is reached
+        assert_eq!(
+            tx::handler::defer_large_deposit::<Test>(&large, height + 1, 200_000),
+            Ok(false)
+        );
+        assert!(tx::handler::deposit_token::<Test>(&who, 200_000));
+        assert_eq!(XAssets::free_balance_of(&who, &token), 299_999);
+    })
+}
+
+#[test]
+fn test_pending_large_deposits_bounded() {
+    with_externalities(&mut new_test_ext(), || {
+        let token = XBridgeOfBTC::TOKEN.to_vec();
+        XBridgeOfBTC::set_large_deposit_confirmation(token.clone(), Some((100_000, 6))).unwrap();
+        let (_, height) = XBridgeOfBTC::genesis_info();
+        let txid = |i: u32| {
+            let mut bytes = [0u8; 32];
+            bytes[..4].copy_from_slice(&i.to_be_bytes());
+            H256::from_slice(&bytes)
+        };
+
+        for i in 0..MAX_PENDING_LARGE_DEPOSITS as u32 {
+            assert_eq!(
+                tx::handler::defer_large_deposit::<Test>(&txid(i), height, 200_000),
+                Ok(true)
+            );
+        }
+        // the deferred deposit is not pushed twice
+        assert_eq!(
+            tx::handler::defer_large_deposit::<Test>(&txid(0), height, 200_000),
+            Ok(true)
+        );
+        assert_eq!(
+            XBridgeOfBTC::pending_large_deposits().len(),
+            MAX_PENDING_LARGE_DEPOSITS
+        );
+
+        assert_eq!(
+            tx::handler::defer_large_deposit::<Test>(
+                &txid(MAX_PENDING_LARGE_DEPOSITS as u32),
+                height,
+                200_000
+            ),
+            Err("too many pending large deposits")
+        );
+        assert_eq!(
+            XBridgeOfBTC::pending_large_deposits().len(),
+            MAX_PENDING_LARGE_DEPOSITS
+        );
+    })
+}
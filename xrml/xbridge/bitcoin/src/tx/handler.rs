@@ -22,8 +22,11 @@ use btc_keys::Address;
 use btc_primitives::H256;
 use btc_script::Script;
 
-use crate::types::{DepositAccountInfo, DepositCache, TxInfo, TxType};
-use crate::{CurrentWithdrawalProposal, Module, PendingDepositMap, RawEvent, Trait, TxMarkFor2};
+use crate::types::{DepositAccountInfo, DepositCache, LargeDepositCache, TxInfo, TxType};
+use crate::{
+    AccumulatedDepositOf, CurrentWithdrawalProposal, Module, PendingDepositMap,
+    PendingLargeDeposits, RawEvent, Trait, TxMarkFor2, MAX_PENDING_LARGE_DEPOSITS,
+};
 
 use super::utils::{addr2vecu8, ensure_identical, get_hot_trustee_address, is_key, parse_opreturn};
 
@@ -62,6 +65,11 @@ impl TxHandler {
                 self.withdraw::<T>()?;
             }
             TxType::Deposit => {
+                let (_, deposit_balance, _) = parse_deposit_outputs::<T>(&self.tx_info.raw_tx)?;
+                if defer_large_deposit::<T>(&self.tx_hash, self.tx_info.height, deposit_balance)? {
+                    // do not mark this tx, it would be handled when reaching the higher tier
+                    return Ok(());
+                }
                 self.deposit::<T>()?;
             }
             TxType::Lock | TxType::Unlock => {
@@ -192,11 +200,17 @@ impl TxHandler {
         let deposit_account = match deposit_account_info {
             DepositAccountInfo::AccountId(accountid) => {
                 if deposit_balance > 0 {
-                    deposit_token::<T>(&accountid, deposit_balance);
-                    info!(
-                        "[deposit]|deposit success|who:{:?}|balance:{:}|tx_hash:{:}",
-                        accountid, deposit_balance, self.tx_hash
-                    );
+                    if deposit_token::<T>(&accountid, deposit_balance) {
+                        info!(
+                            "[deposit]|deposit success|who:{:?}|balance:{:}|tx_hash:{:}",
+                            accountid, deposit_balance, self.tx_hash
+                        );
+                    } else {
+                        info!(
+                            "[deposit]|deposit is accumulated|who:{:?}|balance:{:}|tx_hash:{:}",
+                            accountid, deposit_balance, self.tx_hash
+                        );
+                    }
                 } else {
                     info!(
                         "[deposit]|deposit balance is 0, may be a binding|who:{:?}",
@@ -295,7 +309,34 @@ fn update_binding<T: Trait>(who: &T::AccountId, channel_name: Option<Name>, inpu
     T::CrossChainProvider::update_binding(who, input_addr, channel_name)
 }
 
-pub fn deposit_token<T: Trait>(who: &T::AccountId, balance: u64) {
+/// return false if the balance is accumulated rather than credited, the deposits less than
+/// `MinDepositAmount` are accumulated and credited together once the sum reaches it
+pub fn deposit_token<T: Trait>(who: &T::AccountId, balance: u64) -> bool {
+    let token: xassets::Token = <Module<T> as xassets::ChainT>::TOKEN.to_vec();
+    let accumulated = Module::<T>::accumulated_deposit_of(who).saturating_add(balance);
+    let min_deposit = Module::<T>::min_deposit_amount(&token);
+    if T::Balance::from(accumulated) < min_deposit {
+        warn!(
+            "[deposit_token]|deposit balance is less than the minimum, accumulate it|who:{:?}|balance:{:}|accumulated:{:}|min:{:}",
+            who, balance, accumulated, min_deposit
+        );
+        AccumulatedDepositOf::<T>::insert(who, accumulated);
+        Module::<T>::deposit_event(RawEvent::DepositBelowMinimum(
+            who.clone(),
+            token,
+            balance.into(),
+            accumulated.into(),
+        ));
+        return false;
+    }
+
+    AccumulatedDepositOf::<T>::remove(who);
+    credit_deposit::<T>(who, accumulated);
+    true
+}
+
+/// credit the balance to the account without checking the minimum deposit
+pub fn credit_deposit<T: Trait>(who: &T::AccountId, balance: u64) {
     let token: xassets::Token = <Module<T> as xassets::ChainT>::TOKEN.to_vec();
     let _ = <xrecords::Module<T>>::deposit(&who, &token, balance.into()).map_err(|e| {
         error!(
@@ -306,6 +347,63 @@ pub fn deposit_token<T: Trait>(who: &T::AccountId, balance: u64) {
     });
 }
 
+/// return true if the deposit has not reached the confirmations of the large deposit tier,
+/// the deposit is kept in `PendingLargeDeposits` and would not be credited for now
+pub fn defer_large_deposit<T: Trait>(
+    txid: &H256,
+    height: u32,
+    balance: u64,
+) -> result::Result<bool, &'static str> {
+    let token: xassets::Token = <Module<T> as xassets::ChainT>::TOKEN.to_vec();
+    let required = Module::<T>::deposit_confirmation_number(&token, balance.into());
+    // the deposit under the threshold is credited once the block is confirmed
+    if required <= Module::<T>::network_settings(&token).confirmation_number {
+        return Ok(false);
+    }
+    let confirmations = Module::<T>::confirmations_at(height);
+    if confirmations >= required {
+        return Ok(false);
+    }
+
+    // the block at the height which the deposit is relayed in
+    let block_hash = Module::<T>::block_hash_for(height)
+        .into_iter()
+        .find(|hash| {
+            Module::<T>::block_header_for(hash)
+                .map(|info| info.txid_list.contains(txid))
+                .unwrap_or(false)
+        })
+        .unwrap_or_default();
+    let cache = LargeDepositCache {
+        txid: *txid,
+        height,
+        block_hash,
+        balance,
+    };
+    let mut list = Module::<T>::pending_large_deposits();
+    if !list.contains(&cache) {
+        if list.len() >= MAX_PENDING_LARGE_DEPOSITS {
+            error!(
+                "[defer_large_deposit]|too many pending large deposits|txhash:{:}|max:{:}",
+                txid, MAX_PENDING_LARGE_DEPOSITS
+            );
+            return Err("too many pending large deposits");
+        }
+        list.push(cache);
+        PendingLargeDeposits::<T>::put(list);
+    }
+    info!(
+        "[defer_large_deposit]|large deposit waits for more confirmations|txhash:{:}|balance:{:}|confirmations:{:}|required:{:}",
+        txid, balance, confirmations, required
+    );
+    Module::<T>::deposit_event(RawEvent::LargeDepositDeferred(
+        *txid,
+        balance.into(),
+        required,
+    ));
+    Ok(true)
+}
+
 fn insert_pending_deposit<T: Trait>(input_address: &Address, txid: &H256, balance: u64) {
     let cache = DepositCache {
         txid: *txid,
@@ -338,7 +436,9 @@ fn insert_pending_deposit<T: Trait>(input_address: &Address, txid: &H256, balanc
 pub fn remove_pending_deposit<T: Trait>(input_address: &Address, who: &T::AccountId) {
     if let Some(record) = Module::<T>::pending_deposit(input_address) {
         for r in record {
-            deposit_token::<T>(who, r.balance);
+            if !deposit_token::<T>(who, r.balance) {
+                continue;
+            }
             info!(
                 "[remove_pending_deposit]|use pending info to re-deposit|who:{:?}|balance:{:}",
                 who, r.balance
@@ -6,9 +6,10 @@ pub mod validator;
 
 // Substrate
 use rstd::{prelude::*, result};
-use support::{dispatch::Result, StorageMap};
+use support::{dispatch::Result, StorageMap, StorageValue};
 
 // ChainX
+use xassets::ChainT;
 use xsupport::{debug, error, warn};
 
 // light-bitcoin
@@ -20,7 +21,7 @@ use btc_script::{Builder, Opcode, Script};
 
 use crate::traits::RelayTransaction;
 use crate::types::{TrusteeAddrInfo, TxType};
-use crate::{InputAddrFor, Module, RawEvent, Trait, TxFor};
+use crate::{InputAddrFor, Module, PendingLargeDeposits, RawEvent, Trait, TxFor};
 
 use crate::lockup::detect_lockup_type;
 
@@ -173,6 +174,49 @@ pub fn handle_tx<T: Trait>(txid: &H256) -> Result {
     Ok(())
 }
 
+/// handle the deferred large deposits which reach the confirmations of their tier, the deposit
+/// is credited only if its block is still in the best chain, and is dropped if it fails to be handled
+pub fn handle_pending_large_deposits<T: Trait>() {
+    let token = <Module<T> as ChainT>::TOKEN.to_vec();
+    let pending = Module::<T>::pending_large_deposits();
+    let total = pending.len();
+    let mut waiting = Vec::with_capacity(total);
+    let mut ready = Vec::new();
+    for cache in pending {
+        if Module::<T>::confirmations_at(cache.height)
+            < Module::<T>::deposit_confirmation_number(&token, cache.balance.into())
+        {
+            waiting.push(cache);
+            continue;
+        }
+        let in_best_chain = Module::<T>::best_chain_header_at(cache.height)
+            .map(|info| info.header.hash() == cache.block_hash)
+            .unwrap_or(false);
+        if in_best_chain {
+            ready.push(cache);
+        } else {
+            warn!(
+                "[handle_pending_large_deposits]|the block of the deposit is not in the best chain, drop it|tx_hash:{:}|block_hash:{:}|height:{:}",
+                cache.txid, cache.block_hash, cache.height,
+            );
+        }
+    }
+    if waiting.len() == total {
+        return;
+    }
+
+    for cache in ready {
+        if let Err(_e) = handle_tx::<T>(&cache.txid) {
+            error!(
+                "[handle_pending_large_deposits]|Handle tx failed, the error info:{:}|tx_hash:{:}",
+                _e, cache.txid,
+            );
+            Module::<T>::deposit_event(RawEvent::LargeDepositDropped(cache.txid));
+        }
+    }
+    PendingLargeDeposits::<T>::put(waiting);
+}
+
 pub fn remove_unused_tx<T: Trait>(txid: &H256) {
     debug!("[remove_unused_tx]|remove old tx|tx_hash:{:}", txid);
     TxFor::<T>::remove(txid);
@@ -220,7 +264,7 @@ pub fn create_multi_address<T: Trait>(pubkeys: &[Public], sig_num: u32) -> Optio
 }
 
 /// Check that the cash withdrawal transaction is correct
-pub fn check_withdraw_tx<T: Trait>(tx: &Transaction, withdrawal_id_list: &[u32]) -> Result {
+pub fn check_withdraw_tx<T: Trait>(tx: &Transaction, withdrawal_id_list: &[u64]) -> Result {
     match Module::<T>::withdrawal_proposal() {
         Some(_) => Err("Unfinished withdrawal transaction"),
         None => {
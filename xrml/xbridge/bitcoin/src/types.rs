@@ -64,7 +64,7 @@ impl RelayTransaction for RelayTx {
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct WithdrawalProposal<AccountId> {
     pub sig_state: VoteResult,
-    pub withdrawal_id_list: Vec<u32>,
+    pub withdrawal_id_list: Vec<u64>,
     pub tx: Transaction,
     pub trustee_list: Vec<(AccountId, bool)>,
 }
@@ -72,7 +72,7 @@ pub struct WithdrawalProposal<AccountId> {
 impl<AccountId> WithdrawalProposal<AccountId> {
     pub fn new(
         sig_state: VoteResult,
-        withdrawal_id_list: Vec<u32>,
+        withdrawal_id_list: Vec<u64>,
         tx: Transaction,
         trustee_list: Vec<(AccountId, bool)>,
     ) -> Self {
@@ -85,6 +85,30 @@ impl<AccountId> WithdrawalProposal<AccountId> {
     }
 }
 
+/// withdrawal proposal before the withdrawal id is widened to u64, only for the migration
+#[derive(Encode, Decode)]
+pub struct LegacyWithdrawalProposal<AccountId> {
+    pub sig_state: VoteResult,
+    pub withdrawal_id_list: Vec<u32>,
+    pub tx: Transaction,
+    pub trustee_list: Vec<(AccountId, bool)>,
+}
+
+impl<AccountId> From<LegacyWithdrawalProposal<AccountId>> for WithdrawalProposal<AccountId> {
+    fn from(legacy: LegacyWithdrawalProposal<AccountId>) -> Self {
+        WithdrawalProposal::new(
+            legacy.sig_state,
+            legacy
+                .withdrawal_id_list
+                .into_iter()
+                .map(u64::from)
+                .collect(),
+            legacy.tx,
+            legacy.trustee_list,
+        )
+    }
+}
+
 #[derive(PartialEq, Clone, Copy, Eq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
 pub enum VoteResult {
@@ -125,6 +149,17 @@ pub struct DepositCache {
     pub balance: u64,
 }
 
+/// deposit which waits for the confirmations of the large deposit tier
+#[derive(PartialEq, Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct LargeDepositCache {
+    pub txid: H256,
+    pub height: u32,
+    /// the block the deposit is in, which must be in the best chain when it's credited
+    pub block_hash: H256,
+    pub balance: u64,
+}
+
 #[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
@@ -156,6 +191,17 @@ impl std::fmt::Debug for TrusteeAddrInfo {
     }
 }
 
+/// Header relay settings of a bitcoin-like network, keyed by the network token (e.g. `BTC`).
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct NetworkSettings {
+    /// confirmations for a header to become confirmed
+    pub confirmation_number: u32,
+    /// count of recent heights reserved in storage, older headers would be removed
+    pub reserved_block: u32,
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
@@ -17,8 +17,8 @@
 use support::{storage::unhashed, StorageMap};
 
 use super::{
-    CodeHash, Config, ContractAddressFor, ContractInfo, ContractInfoOf, Event, RawEvent, Trait,
-    TrieId,
+    CodeHash, Config, ContractAddressFor, ContractInfo, ContractInfoOf, Event, InstantiationNonce,
+    Module, RawEvent, Trait, TrieId,
 };
 use crate::account_db::{AccountDb, DirectAccountDb, OverlayAccountDb};
 use crate::gas::{approx_gas_for_balance, Gas, GasMeter, Token};
@@ -71,6 +71,15 @@ pub struct ExecError {
     pub buffer: Vec<u8>,
 }
 
+impl From<&'static str> for ExecError {
+    fn from(reason: &'static str) -> Self {
+        ExecError {
+            reason,
+            buffer: Vec::new(),
+        }
+    }
+}
+
 pub type ExecResult = Result<ExecReturnValue, ExecError>;
 
 /// Evaluate an expression of type Result<_, &'static str> and either resolve to the value if Ok or
@@ -281,6 +290,12 @@ pub enum DeferredAction<T: Trait> {
         /// The keys to delete upon restoration.
         delta: Vec<StorageKey>,
     },
+    SetOwner {
+        /// The account id of the instantiated contract.
+        contract: T::AccountId,
+        /// The account id which instantiated the contract, either an account or a contract.
+        owner: T::AccountId,
+    },
 }
 
 pub struct ExecutionContext<'a, T: Trait + 'a, V, L> {
@@ -295,6 +310,8 @@ pub struct ExecutionContext<'a, T: Trait + 'a, V, L> {
     pub loader: &'a L,
     pub timestamp: MomentOf<T>,
     pub block_number: T::BlockNumber,
+    /// Bytes written to storage so far in this top level call.
+    pub written_bytes: u32,
 }
 
 impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
@@ -320,6 +337,7 @@ where
             loader: &loader,
             timestamp: timestamp::Module::<T>::now(),
             block_number: <system::Module<T>>::block_number(),
+            written_bytes: 0,
         }
     }
 
@@ -340,6 +358,7 @@ where
             loader: self.loader,
             timestamp: self.timestamp.clone(),
             block_number: self.block_number.clone(),
+            written_bytes: self.written_bytes,
         }
     }
 
@@ -470,6 +489,19 @@ where
         gas_meter: &mut GasMeter<T>,
         code_hash: &CodeHash<T>,
         input_data: Vec<u8>,
+    ) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
+        self.instantiate_salted(endowment, gas_meter, code_hash, input_data, &[])
+    }
+
+    /// Same as `instantiate`, except that a non-empty `salt` is appended to the input data
+    /// when deriving the contract address.
+    pub fn instantiate_salted(
+        &mut self,
+        endowment: T::Balance,
+        gas_meter: &mut GasMeter<T>,
+        code_hash: &CodeHash<T>,
+        input_data: Vec<u8>,
+        salt: &[u8],
     ) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
         if self.depth == self.config.max_depth as usize {
             return Err(ExecError {
@@ -478,6 +510,9 @@ where
             });
         }
 
+        // Checked here so that the contracts can't instantiate the unapproved code either.
+        try_or_exec_error!(<Module<T>>::check_approved_code_hash(code_hash), input_data);
+
         if gas_meter
             .charge(self.config, ExecFeeToken::Instantiate)
             .is_out_of_gas()
@@ -489,8 +524,15 @@ where
         }
 
         let caller = self.self_account.clone();
-        let dest =
-            T::DetermineContractAddress::contract_address_for(code_hash, &input_data, &caller);
+        let dest = if salt.is_empty() {
+            T::DetermineContractAddress::contract_address_for(code_hash, &input_data, &caller)
+        } else {
+            let salted = [&input_data[..], salt].concat();
+            T::DetermineContractAddress::contract_address_for(code_hash, &salted, &caller)
+        };
+        // Bump the nonce of the caller once the address is derived, including the nested
+        // instantiations, so that the next instantiation gets a new address even if this fails.
+        <InstantiationNonce<T>>::mutate(&caller, |nonce| *nonce += 1);
 
         // TrieId has not been generated yet and storage is empty since contract is new.
         let dest_trie_id = None;
@@ -538,6 +580,11 @@ where
                 event: RawEvent::Instantiated(caller.clone(), dest.clone()),
                 topics: Vec::new(),
             });
+            // Record the owner, including the contracts instantiated by a contract.
+            nested.deferred.push(DeferredAction::SetOwner {
+                contract: dest.clone(),
+                owner: caller.clone(),
+            });
 
             Ok(output)
         })?;
@@ -594,15 +641,21 @@ where
     where
         F: FnOnce(&mut ExecutionContext<T, V, L>) -> ExecResult,
     {
-        let (output, change_set, deferred) = {
+        let (output, change_set, deferred, written_bytes) = {
             let mut nested = self.nested(dest, trie_id);
             let output = func(&mut nested)?;
-            (output, nested.overlay.into_change_set(), nested.deferred)
+            (
+                output,
+                nested.overlay.into_change_set(),
+                nested.deferred,
+                nested.written_bytes,
+            )
         };
 
         if output.is_success() {
             self.overlay.commit(change_set);
             self.deferred.extend(deferred);
+            self.written_bytes = written_bytes;
         }
 
         Ok(output)
@@ -766,6 +819,16 @@ where
             if self.max_value_size() < value.len() as u32 {
                 return Err("value size exceeds maximum");
             }
+
+            let written_bytes = self
+                .ctx
+                .written_bytes
+                .saturating_add(key.len() as u32)
+                .saturating_add(value.len() as u32);
+            if written_bytes > self.ctx.config.max_write_bytes_per_call {
+                return Err("storage write bytes exceed the limit of this call");
+            }
+            self.ctx.written_bytes = written_bytes;
         }
 
         self.ctx
@@ -1452,6 +1515,47 @@ mod tests {
         });
     }
 
+    #[test]
+    fn xrc20_max_depth() {
+        // This test verifies that calling as a xrc20 instance traps at a depth which is
+        // still allowed for the general calls.
+        let nesting = crate::DEFAULT_XRC20_MAX_DEPTH as usize + 1;
+        let level = RefCell::new(0usize);
+        let trapped = RefCell::new(false);
+
+        let vm = MockVm::new();
+        let mut loader = MockLoader::empty();
+        let recurse_ch = loader.insert(|ctx| {
+            let current = {
+                let mut level = level.borrow_mut();
+                *level += 1;
+                *level
+            };
+            if current < nesting && ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![]).is_err() {
+                *trapped.borrow_mut() = true;
+            }
+            exec_success()
+        });
+
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            for (cfg, expect_trapped) in vec![
+                (Config::preload_for_xrc20(), true),
+                (Config::preload(), false),
+            ] {
+                *level.borrow_mut() = 0;
+                *trapped.borrow_mut() = false;
+
+                let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+                ctx.overlay.set_balance(&BOB, 1);
+                ctx.overlay.instantiate_contract(&BOB, recurse_ch).unwrap();
+
+                let result = ctx.call(BOB, 0, &mut GasMeter::<Test>::with_limit(100000, 1), vec![]);
+                assert_matches!(result, Ok(_));
+                assert_eq!(*trapped.borrow(), expect_trapped);
+            }
+        });
+    }
+
     #[test]
     fn caller_returns_proper_values() {
         let origin = ALICE;
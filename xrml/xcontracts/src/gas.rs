@@ -14,14 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{GasSpent, Module, Trait};
+use crate::{AssetPowerFor, ContractGasAllowance, GasFeeTreasuryRatio, GasSpent, Module, Trait};
 use rstd::convert::TryFrom;
 use sr_primitives::traits::{
     CheckedMul, SaturatedConversion, SimpleArithmetic, UniqueSaturatedInto, Zero,
 };
-use support::StorageValue;
+use support::{StorageMap, StorageValue};
 
-use xsupport::debug;
+use xsupport::{debug, error};
 
 #[cfg(test)]
 use std::{any::Any, fmt::Debug};
@@ -204,35 +204,231 @@ pub fn buy_gas<T: Trait>(
 ) -> Result<GasMeter<T>, &'static str> {
     // Buy the specified amount of gas.
     let gas_price = <Module<T>>::gas_price();
-    let cost = if gas_price.is_zero() {
-        <T::Balance>::zero()
+    let cost = gas_cost::<T>(gas_price, gas_limit)?;
+
+    debug!(
+        "[buy_gas]|account:{:?}|gas_limit:{:?}|gas_price:{:?}|cost:{:}",
+        transactor, gas_limit, gas_price, cost
+    );
+
+    // temp store to `GasPayment` type
+    xassets::Module::<T>::pcx_move_balance(
+        transactor,
+        xassets::AssetType::Free,
+        transactor,
+        xassets::AssetType::GasPayment,
+        cost,
+    )
+    .map_err(|e| e.info())?;
+
+    Ok(GasMeter::with_limit(gas_limit, gas_price))
+}
+
+/// The cost of `gas_limit` in PCX at the given gas price.
+fn gas_cost<T: Trait>(gas_price: T::Balance, gas_limit: Gas) -> Result<T::Balance, &'static str> {
+    if gas_price.is_zero() {
+        Ok(<T::Balance>::zero())
     } else {
         <T::Balance as TryFrom<Gas>>::try_from(gas_limit)
             .ok()
             .and_then(|gas_limit| gas_price.checked_mul(&gas_limit))
-            .ok_or("overflow multiplying gas limit by price")?
+            .ok_or("overflow multiplying gas limit by price")
+    }
+}
+
+/// Buy the gas with an accepted token other than PCX.
+///
+/// The PCX cost is converted into `token` by its asset power, i.e., the PCX value of
+/// one whole `token`, the converted amount is reserved as the `GasPayment` of `token`.
+pub fn buy_gas_with_token<T: Trait>(
+    transactor: &T::AccountId,
+    token: &xassets::Token,
+    gas_limit: Gas,
+) -> Result<GasMeter<T>, &'static str> {
+    let gas_price = <Module<T>>::gas_price();
+    let cost = gas_cost::<T>(gas_price, gas_limit)?;
+
+    let power = T::AssetPower::asset_power(token)
+        .filter(|power| !power.is_zero())
+        .ok_or("this token has no asset power to pay the gas")?;
+    let precision = xassets::Module::<T>::get_asset(token)?.precision();
+
+    // Round up, the payment in token should never be less than the cost in PCX.
+    let power: u128 = power.saturated_into();
+    let token_cost =
+        (cost.saturated_into::<u128>() * 10_u128.pow(precision.into()) + power - 1) / power;
+    let token_cost: T::Balance = token_cost.saturated_into();
+
+    debug!(
+        "[buy_gas_with_token]|account:{:?}|token:{:?}|gas_limit:{:?}|gas_price:{:?}|cost:{:}|token_cost:{:}",
+        transactor, token, gas_limit, gas_price, cost, token_cost
+    );
+
+    xassets::Module::<T>::move_balance(
+        token,
+        transactor,
+        xassets::AssetType::Free,
+        transactor,
+        xassets::AssetType::GasPayment,
+        token_cost,
+    )
+    .map_err(|e| e.info())?;
+
+    Ok(GasMeter::with_limit(gas_limit, gas_price))
+}
+
+/// Refund the unused gas bought by `buy_gas_with_token`.
+///
+/// The refund is in proportion to the gas left, the rest is destroyed.
+pub fn refund_unused_gas_with_token<T: Trait>(
+    transactor: &T::AccountId,
+    token: &xassets::Token,
+    gas_meter: GasMeter<T>,
+) {
+    let gas_payment =
+        xassets::Module::<T>::asset_balance_of(transactor, token, xassets::AssetType::GasPayment);
+
+    let gas_spent = gas_meter.spent();
+    let gas_left = gas_meter.gas_left();
+
+    GasSpent::<T>::mutate(|block_gas_spent| *block_gas_spent += gas_spent);
+
+    let refund: T::Balance = if gas_meter.limit == 0 {
+        Zero::zero()
+    } else {
+        (gas_payment.saturated_into::<u128>() * u128::from(gas_left) / u128::from(gas_meter.limit))
+            .saturated_into()
     };
+    let pay_to_other = gas_payment - refund;
 
     debug!(
-        "[buy_gas]|account:{:?}|gas_limit:{:?}|gas_price:{:?}|cost:{:}",
-        transactor, gas_limit, gas_price, cost
+        "[refund_unused_gas_with_token]|account:{:?}|token:{:?}|gas_payment:{:?}|refund:{:?}|real cost:{:?}|gas_spent:{:}",
+        transactor, token, gas_payment, refund, pay_to_other, gas_spent
     );
 
-    // temp store to `GasPayment` type
-    xassets::Module::<T>::pcx_move_balance(
+    let _ = xassets::Module::<T>::move_balance(
+        token,
+        transactor,
+        xassets::AssetType::GasPayment,
         transactor,
         xassets::AssetType::Free,
+        refund,
+    );
+
+    if let Err(e) = xassets::Module::<T>::destroy_gas_payment(token, transactor, pay_to_other) {
+        error!(
+            "[refund_unused_gas_with_token]|fail to destroy the spent gas|account:{:?}|token:{:?}|spent:{:?}|err:{:}",
+            transactor, token, pay_to_other, e
+        );
+    }
+}
+
+/// Pay the spent gas fee in the PCX `GasPayment` of the transactor, the `GasFeeTreasuryRatio`
+/// of it goes to the council and the rest is destroyed.
+fn pay_spent_gas<T: Trait>(transactor: &T::AccountId, fee: T::Balance) {
+    let ratio = u128::from(GasFeeTreasuryRatio::<T>::get());
+    let for_council: T::Balance =
+        (fee.saturated_into::<u128>() * ratio / 1_000_000).saturated_into();
+    let to_burn = fee - for_council;
+
+    debug!(
+        "[pay_spent_gas]|account:{:?}|fee:{:?}|for council:{:?}|to burn:{:?}",
+        transactor, fee, for_council, to_burn
+    );
+
+    let _ = xassets::Module::<T>::pcx_move_balance(
         transactor,
         xassets::AssetType::GasPayment,
+        &xaccounts::Module::<T>::council_account(),
+        xassets::AssetType::Free,
+        for_council,
+    );
+
+    if to_burn.is_zero() {
+        return;
+    }
+    if let Err(e) = xassets::Module::<T>::pcx_destroy_gas_payment(transactor, to_burn) {
+        error!(
+            "[pay_spent_gas]|fail to destroy the spent gas|account:{:?}|to burn:{:?}|err:{:}",
+            transactor, to_burn, e
+        );
+    }
+}
+
+/// Whether the allowance pre-funded to the contract covers the cost of `gas_limit`, otherwise
+/// the gas should be bought from the free balance as usual.
+pub fn allowance_covers<T: Trait>(contract: &T::AccountId, gas_limit: Gas) -> bool {
+    let allowance = <Module<T>>::contract_gas_allowance(contract);
+    !allowance.is_zero()
+        && gas_cost::<T>(<Module<T>>::gas_price(), gas_limit)
+            .map(|cost| cost <= allowance)
+            .unwrap_or(false)
+}
+
+/// Buy the gas from the allowance pre-funded to the contract by `fund_contract_gas`.
+pub fn buy_gas_from_allowance<T: Trait>(
+    contract: &T::AccountId,
+    gas_limit: Gas,
+) -> Result<GasMeter<T>, &'static str> {
+    let gas_price = <Module<T>>::gas_price();
+    let cost = gas_cost::<T>(gas_price, gas_limit)?;
+
+    let allowance = <Module<T>>::contract_gas_allowance(contract);
+    if allowance < cost {
+        return Err("not enough gas allowance for this contract");
+    }
+
+    debug!(
+        "[buy_gas_from_allowance]|contract:{:?}|gas_limit:{:?}|gas_price:{:?}|cost:{:}|allowance:{:}",
+        contract, gas_limit, gas_price, cost, allowance
+    );
+
+    xassets::Module::<T>::pcx_move_balance(
+        contract,
+        xassets::AssetType::ReservedCurrency,
+        contract,
+        xassets::AssetType::GasPayment,
         cost,
     )
     .map_err(|e| e.info())?;
+    ContractGasAllowance::<T>::insert(contract, allowance - cost);
 
     Ok(GasMeter::with_limit(gas_limit, gas_price))
 }
 
 /// Refund the unused gas.
 pub fn refund_unused_gas<T: Trait>(transactor: &T::AccountId, gas_meter: GasMeter<T>) {
+    refund_unused_gas_to(transactor, transactor, gas_meter, xassets::AssetType::Free);
+}
+
+/// Refund the unused gas bought by the transactor to the free balance of the beneficiary.
+pub fn refund_unused_gas_to_beneficiary<T: Trait>(
+    transactor: &T::AccountId,
+    beneficiary: &T::AccountId,
+    gas_meter: GasMeter<T>,
+) {
+    refund_unused_gas_to(transactor, beneficiary, gas_meter, xassets::AssetType::Free);
+}
+
+/// Refund the unused gas back to the allowance of the contract.
+pub fn refund_unused_gas_to_allowance<T: Trait>(contract: &T::AccountId, gas_meter: GasMeter<T>) {
+    let refund = refund_unused_gas_to(
+        contract,
+        contract,
+        gas_meter,
+        xassets::AssetType::ReservedCurrency,
+    );
+    ContractGasAllowance::<T>::mutate(contract, |allowance| *allowance += refund);
+}
+
+/// Refund the unused gas bought by the transactor to `refund_type` of `refund_to`,
+/// returns the refunded balance.
+fn refund_unused_gas_to<T: Trait>(
+    transactor: &T::AccountId,
+    refund_to: &T::AccountId,
+    gas_meter: GasMeter<T>,
+    refund_type: xassets::AssetType,
+) -> T::Balance {
     let gas_payment =
         xassets::Module::<T>::pcx_type_balance(transactor, xassets::AssetType::GasPayment);
 
@@ -258,22 +454,19 @@ pub fn refund_unused_gas<T: Trait>(transactor: &T::AccountId, gas_meter: GasMete
         transactor, gas_payment, remainder, pay_to_other, gas_spent
     );
 
-    let _ = xassets::Module::<T>::pcx_move_balance(
+    let refunded = xassets::Module::<T>::pcx_move_balance(
         transactor,
         xassets::AssetType::GasPayment,
-        transactor,
-        xassets::AssetType::Free,
+        refund_to,
+        refund_type,
         remainder,
-    );
+    )
+    .map(|_| remainder)
+    .unwrap_or_else(|_| Zero::zero());
 
-    // pay gas to council
-    let _ = xassets::Module::<T>::pcx_move_balance(
-        transactor,
-        xassets::AssetType::GasPayment,
-        &xaccounts::Module::<T>::council_account(),
-        xassets::AssetType::Free,
-        pay_to_other,
-    );
+    pay_spent_gas::<T>(transactor, pay_to_other);
+
+    refunded
 }
 
 /// A little handy utility for converting a value in balance units into approximate value in gas units
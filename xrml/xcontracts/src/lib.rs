@@ -120,7 +120,9 @@ use rstd::{collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 use runtime_io::blake2_256;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sr_primitives::traits::{Hash, MaybeSerializeDebug, Member, StaticLookup, Zero};
+use sr_primitives::traits::{
+    Hash, MaybeSerializeDebug, Member, One, SaturatedConversion, StaticLookup, Zero,
+};
 use support::dispatch::{Dispatchable, Result};
 use support::{
     decl_event, decl_module, decl_storage, parameter_types, storage::child, Parameter, StorageMap,
@@ -133,8 +135,9 @@ use support::{
 use system::{ensure_root, ensure_signed, RawOrigin};
 
 use xassets::{AssetType, Token};
-pub use xr_primitives::XRC20Selector; // re-export
-use xsupport::{debug, ensure_with_errorlog, error, info};
+use xr_primitives::{ContractEventFieldValue, ContractEventLayout, XString};
+pub use xr_primitives::{XRC20Selector, XRC777Selector}; // re-export
+use xsupport::{debug, ensure_with_errorlog, error, info, warn};
 #[cfg(feature = "std")]
 use xsupport::{token, try_hex_or_str};
 
@@ -142,6 +145,21 @@ pub type CodeHash<T> = <T as system::Trait>::Hash;
 pub type TrieId = Vec<u8>;
 pub type Selector = [u8; 4];
 
+/// A reasonable default value for `XRC20MaxDepth`.
+pub const DEFAULT_XRC20_MAX_DEPTH: u32 = 8;
+
+/// The maximum number of storage keys could be queried by `get_storage_multi` at once.
+pub const MAX_STORAGE_KEYS_PER_QUERY: usize = 64;
+
+/// Max number of holders in a `query_xrc20_balances` query.
+pub const MAX_XRC20_BALANCE_HOLDERS: usize = 100;
+
+/// A reasonable default value for `GasPriceHistoryWindow`, about one day.
+pub const DEFAULT_GAS_PRICE_HISTORY_WINDOW: u64 = 43_200;
+
+/// The dynamic gas price moves 1/GAS_PRICE_DECAY_DIVISOR of its distance to the baseline per block.
+pub const GAS_PRICE_DECAY_DIVISOR: u32 = 10;
+
 /// A function that generates an `AccountId` for a contract upon instantiation.
 pub trait ContractAddressFor<CodeHash, AccountId> {
     fn contract_address_for(code_hash: &CodeHash, data: &[u8], origin: &AccountId) -> AccountId;
@@ -152,6 +170,41 @@ pub trait ComputeDispatchFee<Call, Balance> {
     fn compute_dispatch_fee(call: &Call) -> Option<Balance>;
 }
 
+/// A function that returns the PCX value of one whole token, e.g. the cross mining asset power.
+pub trait AssetPowerFor<Balance> {
+    fn asset_power(token: &Token) -> Option<Balance>;
+}
+
+impl<Balance> AssetPowerFor<Balance> for () {
+    fn asset_power(_token: &Token) -> Option<Balance> {
+        None
+    }
+}
+
+/// The kind of the token contract instance a token is bound to.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum TokenContractKind {
+    XRC20,
+    XRC777,
+}
+
+/// Where the unused gas of a sponsored call is refunded to.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum GasRefundPolicy {
+    /// Refund to the sponsor paying the gas.
+    Payer,
+    /// Refund to the beneficiary the call is made as.
+    Beneficiary,
+}
+
+impl Default for GasRefundPolicy {
+    fn default() -> Self {
+        GasRefundPolicy::Payer
+    }
+}
+
 /// Information for managing an acocunt and its sub trie abstraction.
 /// This is the required info to cache for an account
 #[derive(Encode, Decode)]
@@ -239,6 +292,30 @@ pub struct RawAliveContractInfo<CodeHash, Balance, BlockNumber> {
     pub last_write: Option<BlockNumber>,
 }
 
+pub type ContractSnapshotOf<T> = ContractSnapshot<
+    CodeHash<T>,
+    <T as xassets::Trait>::Balance,
+    <T as system::Trait>::BlockNumber,
+>;
+
+/// A portable snapshot of an alive contract, which could be imported by `import_contract`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct ContractSnapshot<CodeHash, Balance, BlockNumber> {
+    /// The code associated with the contract.
+    pub code_hash: CodeHash,
+    /// The storage key/values of the contract.
+    pub storage: Vec<(exec::StorageKey, Vec<u8>)>,
+    /// The root of the child trie, verifying the storage is complete on import.
+    pub storage_root: Vec<u8>,
+    /// Pay rent at most up to this value.
+    pub rent_allowance: Balance,
+    /// The size of stored value in octet.
+    pub storage_size: u32,
+    /// Last block rent has been payed.
+    pub deduct_block: BlockNumber,
+}
+
 pub type TombstoneContractInfo<T> =
     RawTombstoneContractInfo<<T as system::Trait>::Hash, <T as system::Trait>::Hashing>;
 
@@ -335,10 +412,16 @@ parameter_types! {
     pub const DefaultRentDepositOffset: u32 = 1000;
     /// A reasonable default value for [`Trait::MaxDepth`].
     pub const DefaultMaxDepth: u32 = 32;
+    /// A reasonable default value for [`Trait::MaxRestoreDelta`].
+    pub const DefaultMaxRestoreDelta: u32 = 128;
     /// A reasonable default value for [`Trait::MaxValueSize`].
     pub const DefaultMaxValueSize: u32 = 16_384;
+    /// A reasonable default value for [`Trait::MaxWriteBytesPerCall`].
+    pub const DefaultMaxWriteBytesPerCall: u32 = 1_048_576;
     /// A reasonable default value for [`Trait::BlockGasLimit`].
     pub const DefaultBlockGasLimit: u32 = 10_000_000;
+    /// A reasonable default value for [`Trait::MaxGasPerExtrinsic`].
+    pub const DefaultMaxGasPerExtrinsic: u32 = 5_000_000;
 }
 
 pub trait Trait:
@@ -373,6 +456,10 @@ pub trait Trait:
     /// for current_block - delay
     type SignedClaimHandicap: Get<Self::BlockNumber>;
 
+    /// Reward that is received by the party whose touch has led
+    /// to removal of a contract, paid out of the council which collects the rent.
+    type SurchargeReward: Get<Self::Balance>;
+
     /// The minimum amount required to generate a tombstone.
     type TombstoneDeposit: Get<Self::Balance>;
 
@@ -398,8 +485,20 @@ pub trait Trait:
     /// The maximum size of a storage value in bytes.
     type MaxValueSize: Get<u32>;
 
+    /// The maximum number of bytes (keys and values) a top level call could write to storage.
+    type MaxWriteBytesPerCall: Get<u32>;
+
+    /// The maximum number of storage keys in the `delta` of a restoration.
+    type MaxRestoreDelta: Get<u32>;
+
     /// The maximum amount of gas that could be expended per block.
     type BlockGasLimit: Get<Gas>;
+
+    /// The maximum amount of gas that a single extrinsic could be retried with.
+    type MaxGasPerExtrinsic: Get<Gas>;
+
+    /// A function type to value the accepted token in PCX when paying the gas in it.
+    type AssetPower: AssetPowerFor<<Self as xassets::Trait>::Balance>;
 }
 
 /// Simple contract address determiner.
@@ -408,6 +507,9 @@ pub trait Trait:
 /// and the account id that requested the account creation.
 ///
 /// Formula: `blake2_256(blake2_256(code) + blake2_256(data) + origin)`
+///
+/// Once the origin has instantiated some contracts, its `InstantiationNonce` is appended as well,
+/// so that the repeated deployments from the same origin, code and data won't collide.
 pub struct SimpleAddressDeterminer<T: Trait>(PhantomData<T>);
 impl<T: Trait> ContractAddressFor<CodeHash<T>, T::AccountId> for SimpleAddressDeterminer<T>
 where
@@ -418,15 +520,34 @@ where
         data: &[u8],
         origin: &T::AccountId,
     ) -> T::AccountId {
-        let data_hash = T::Hashing::hash(data);
-
-        let mut buf = Vec::new();
-        buf.extend_from_slice(code_hash.as_ref());
-        buf.extend_from_slice(data_hash.as_ref());
-        buf.extend_from_slice(origin.as_ref());
+        let nonce = <Module<T>>::instantiation_nonce(origin);
+        UncheckedFrom::unchecked_from(contract_address_hash::<T>(
+            code_hash,
+            data,
+            origin.as_ref(),
+            nonce,
+        ))
+    }
+}
 
-        UncheckedFrom::unchecked_from(T::Hashing::hash(&buf[..]))
+/// The hash used by `SimpleAddressDeterminer`, the zero nonce is omitted to keep the former addresses.
+pub fn contract_address_hash<T: Trait>(
+    code_hash: &CodeHash<T>,
+    data: &[u8],
+    origin: &[u8],
+    nonce: u64,
+) -> T::Hash {
+    let data_hash = T::Hashing::hash(data);
+
+    let mut buf = Vec::new();
+    buf.extend_from_slice(code_hash.as_ref());
+    buf.extend_from_slice(data_hash.as_ref());
+    buf.extend_from_slice(origin);
+    if nonce > 0 {
+        buf.extend_from_slice(&nonce.encode());
     }
+
+    T::Hashing::hash(&buf[..])
 }
 
 decl_module! {
@@ -436,12 +557,14 @@ decl_module! {
 
         /// Updates the schedule for metering contracts.
         ///
-        /// The schedule must have a greater version than the stored schedule.
+        /// The schedule must have a greater version than the stored schedule,
+        /// and must not lower the critical limits below the floors.
         pub fn update_schedule(origin, schedule: Schedule) -> Result {
             ensure_root(origin)?;
             if <Module<T>>::current_schedule().version >= schedule.version {
                 return Err("new schedule must have a greater version than current");
             }
+            Self::schedule_limit_floors().check(&schedule)?;
 
             Self::deposit_event(RawEvent::ScheduleUpdated(schedule.version));
             CurrentSchedule::<T>::put(schedule);
@@ -457,30 +580,24 @@ decl_module! {
             code: Vec<u8>
         ) -> Result {
             let origin = ensure_signed(origin)?;
+            Self::check_put_code_permission(&origin)?;
 
-            let (network, _) = xsystem::Module::<T>::network_props();
-            match network {
-                xsystem::NetworkType::Mainnet => {
-                    let council = xaccounts::Module::<T>::council_account();
-                    ensure_with_errorlog!(
-                        origin == council,
-                        "[put_code]|in mainnet, only council account could do `put_code`.",
-                        "[put_code]|in mainnet, only council account could do `put_code`|current:{:?}|council:{:?}",
-                        origin, council
-                    );
-                    info!("[put_code]|mainnet put_code, from account:{:?}", origin);
-                },
-                xsystem::NetworkType::Testnet => {
-                    // do nothing
-                },
-            }
+            // reject a too small gas_limit before buying gas and instrumenting the code
+            let schedule = <Module<T>>::current_schedule();
+            let gas_floor = wasm::save_code_gas_floor::<T>(code.len(), &schedule);
+            ensure_with_errorlog!(
+                gas_limit >= gas_floor,
+                "[put_code]|gas_limit is too low to store the code",
+                "[put_code]|gas_limit:{:}|gas_floor:{:}|code_len:{:}",
+                gas_limit, gas_floor, code.len()
+            );
 
             let mut gas_meter = gas::buy_gas::<T>(&origin, gas_limit)?;
 
-            let schedule = <Module<T>>::current_schedule();
             let result = wasm::save_code::<T>(code, &mut gas_meter, &schedule);
             if let Ok(code_hash) = result {
                 info!("[put_code]|set new code|code_hash:{:?}", code_hash);
+                Self::note_put_code(&origin);
                 Self::deposit_event(RawEvent::CodeStored(code_hash));
             }
 
@@ -489,6 +606,72 @@ decl_module! {
             result.map(|_| ())
         }
 
+        /// Stores the given binary Wasm code and instantiates a contract with it in one transaction,
+        /// sharing the same gas meter. The code already on chain is reused rather than stored again,
+        /// and only the code stored by this call is removed if the instantiation fails.
+        ///
+        /// A non-empty `salt` is mixed into the derivation of the contract address.
+        pub fn put_code_and_instantiate(
+            origin,
+            #[compact] endowment: T::Balance,
+            #[compact] gas_limit: Gas,
+            code: Vec<u8>,
+            data: Vec<u8>,
+            salt: Vec<u8>
+        ) -> Result {
+            let origin = ensure_signed(origin)?;
+            Self::check_put_code_permission(&origin)?;
+
+            let schedule = <Module<T>>::current_schedule();
+            let gas_floor = wasm::save_code_gas_floor::<T>(code.len(), &schedule);
+            ensure_with_errorlog!(
+                gas_limit >= gas_floor,
+                "[put_code_and_instantiate]|gas_limit is too low to store the code",
+                "[put_code_and_instantiate]|gas_limit:{:}|gas_floor:{:}|code_len:{:}",
+                gas_limit, gas_floor, code.len()
+            );
+
+            let code_hash = T::Hashing::hash(&code);
+            let code_existed = <PristineCode<T>>::exists(&code_hash);
+            let deployer = origin.clone();
+            let result = Self::execute_wasm(origin, None, gas_limit, |ctx, gas_meter| {
+                if !code_existed {
+                    try_or_exec_error!(wasm::save_code::<T>(code, gas_meter, &schedule), data);
+                }
+                ctx.instantiate_salted(endowment, gas_meter, &code_hash, data, &salt)
+                    .map(|(_, output)| output)
+            })
+            .and_then(|output| {
+                if output.is_success() {
+                    Ok(())
+                } else {
+                    Err(ExecError{
+                        reason: "fail to create contract, maybe instantiate data decode error",
+                        buffer: Vec::new(),
+                    })
+                }
+            })
+            .map_err(|e| e.reason);
+
+            if let Err(e) = result {
+                // revert the code stored by this call along with the failed instantiation.
+                if !code_existed {
+                    <CodeStorage<T>>::remove(&code_hash);
+                    <PristineCode<T>>::remove(&code_hash);
+                }
+                return Err(e);
+            }
+
+            if code_existed {
+                info!("[put_code_and_instantiate]|reuse the stored code and create contract|code_hash:{:?}", code_hash);
+            } else {
+                info!("[put_code_and_instantiate]|set new code and create contract|code_hash:{:?}", code_hash);
+                Self::note_put_code(&deployer);
+                Self::deposit_event(RawEvent::CodeStored(code_hash));
+            }
+            Ok(())
+        }
+
         /// Makes a call to an account, optionally transferring some balance.
         ///
         /// * If the account is a smart-contract account, the associated code will be
@@ -505,6 +688,7 @@ decl_module! {
         ) -> Result {
             let origin = ensure_signed(origin)?;
             let dest = T::Lookup::lookup(dest)?;
+            Self::check_call_limit(&dest)?;
             debug!("[call]|call contract|from:{:?}|dest:{:?}|value:{:?}|data:{:}", origin, dest, value, try_hex_or_str(&data));
 
             Self::bare_call(origin, dest.clone(), value, gas_limit, data)
@@ -522,6 +706,198 @@ decl_module! {
                 .map_err(|e| e.reason)
         }
 
+        /// Makes a call to a contract as `beneficiary`, while the gas is paid by the caller.
+        ///
+        /// The `beneficiary` must have authorized the caller as a sponsor of the calls to `dest`
+        /// by `set_sponsor`, and the `gas_limit` must be within the authorized gas. No value could
+        /// be transferred from the `beneficiary` in a sponsored call.
+        pub fn call_sponsored(
+            origin,
+            dest: <T::Lookup as StaticLookup>::Source,
+            #[compact] value: T::Balance,
+            #[compact] gas_limit: Gas,
+            data: Vec<u8>,
+            beneficiary: T::AccountId
+        ) -> Result {
+            let sponsor = ensure_signed(origin)?;
+            let dest = T::Lookup::lookup(dest)?;
+            if !value.is_zero() {
+                return Err("value is not allowed in a sponsored call");
+            }
+            let max_gas = match Self::sponsor_allowance(&(beneficiary.clone(), sponsor.clone(), dest.clone())) {
+                Some(max_gas) => max_gas,
+                None => {
+                    error!("[call_sponsored]|beneficiary has not authorized this sponsor|sponsor:{:?}|beneficiary:{:?}|dest:{:?}", sponsor, beneficiary, dest);
+                    return Err("beneficiary has not authorized this sponsor");
+                }
+            };
+            ensure_with_errorlog!(
+                gas_limit <= max_gas,
+                "gas limit exceeds the gas authorized by the beneficiary",
+                "sponsor:{:?}|beneficiary:{:?}|gas_limit:{:}|max_gas:{:}", sponsor, beneficiary, gas_limit, max_gas
+            );
+            if <ContractInfoOf<T>>::get(&dest).is_none() {
+                return Err("unable to call dest contract as it does not exist");
+            }
+            Self::check_call_limit(&dest)?;
+            debug!("[call_sponsored]|call contract|sponsor:{:?}|from:{:?}|dest:{:?}|value:{:?}|data:{:}", sponsor, beneficiary, dest, value, try_hex_or_str(&data));
+
+            let refund_to = match Self::gas_refund_policy_of(&sponsor) {
+                GasRefundPolicy::Payer => None,
+                GasRefundPolicy::Beneficiary => Some(beneficiary.clone()),
+            };
+            let output = Self::execute_wasm_with_config(
+                beneficiary,
+                Some(sponsor),
+                None,
+                refund_to,
+                Some(dest.clone()),
+                gas_limit,
+                Config::preload(),
+                |ctx, gas_meter| ctx.call(dest.clone(), value, gas_meter, data),
+            ).map_err(|e| e.reason)?;
+            if !output.is_success() {
+                return Err("fail to call the contract, please check input_data and contract");
+            }
+            debug!("[call_sponsored]|call contract success|result:{:}|contract addr:{:?}", try_hex_or_str(&output.data), dest);
+            Ok(())
+        }
+
+        /// Makes a call to an account like `call`, but pays the gas in an accepted `gas_token`.
+        ///
+        /// The gas cost is converted into `gas_token` by its asset power, and the spent part
+        /// is destroyed.
+        pub fn call_with_token(
+            origin,
+            dest: <T::Lookup as StaticLookup>::Source,
+            #[compact] value: T::Balance,
+            #[compact] gas_limit: Gas,
+            data: Vec<u8>,
+            gas_token: Token
+        ) -> Result {
+            let origin = ensure_signed(origin)?;
+            let dest = T::Lookup::lookup(dest)?;
+            ensure_with_errorlog!(
+                Self::gas_payment_tokens().contains(&gas_token),
+                "this token is not accepted for paying the gas",
+                "token:{:}", token!(gas_token)
+            );
+            if <ContractInfoOf<T>>::get(&dest).is_none() {
+                return Err("unable to call dest contract as it does not exist");
+            }
+            Self::check_call_limit(&dest)?;
+            debug!("[call_with_token]|call contract|from:{:?}|dest:{:?}|value:{:?}|gas_token:{:}|data:{:}", origin, dest, value, token!(gas_token), try_hex_or_str(&data));
+
+            let output = Self::execute_wasm_with_config(
+                origin,
+                None,
+                Some(gas_token),
+                None,
+                Some(dest.clone()),
+                gas_limit,
+                Config::preload(),
+                |ctx, gas_meter| ctx.call(dest.clone(), value, gas_meter, data),
+            ).map_err(|e| e.reason)?;
+            if !output.is_success() {
+                return Err("fail to call the contract, please check input_data and contract");
+            }
+            debug!("[call_with_token]|call contract success|result:{:}|contract addr:{:?}", try_hex_or_str(&output.data), dest);
+            Ok(())
+        }
+
+        /// Limit the calls to the contract per block, `None` for no limit, only for the contract owner.
+        pub fn set_call_limit(origin, contract: T::AccountId, limit: Option<u32>) -> Result {
+            let who = ensure_signed(origin)?;
+            ensure_with_errorlog!(
+                Self::contract_owner_of(&contract).as_ref() == Some(&who),
+                "only the owner of the contract could set the call limit",
+                "who:{:?}|contract:{:?}", who, contract
+            );
+            match limit {
+                Some(limit) => <CallLimitPerBlock<T>>::insert(&contract, limit),
+                None => <CallLimitPerBlock<T>>::remove(&contract),
+            }
+            Ok(())
+        }
+
+        /// Reserve PCX as the gas allowance of `contract`.
+        ///
+        /// The calls made by the contract itself, e.g. the dispatched runtime calls,
+        /// pay the gas from the allowance while it covers the gas, otherwise from the free
+        /// balance. The allowance could be withdrawn by the owner by `withdraw_contract_gas`,
+        /// and is released to the owner when the contract is evicted, or reaped once its PCX
+        /// free balance drops to zero.
+        pub fn fund_contract_gas(origin, contract: T::AccountId, #[compact] amount: T::Balance) -> Result {
+            let funder = ensure_signed(origin)?;
+            if <ContractInfoOf<T>>::get(&contract).and_then(|c| c.get_alive()).is_none() {
+                return Err("unable to fund the gas for a non-alive contract");
+            }
+            xassets::Module::<T>::pcx_move_balance(
+                &funder,
+                AssetType::Free,
+                &contract,
+                AssetType::ReservedCurrency,
+                amount,
+            )
+            .map_err(|e| e.info())?;
+            <ContractGasAllowance<T>>::mutate(&contract, |allowance| *allowance += amount);
+            debug!("[fund_contract_gas]|funder:{:?}|contract:{:?}|amount:{:}", funder, contract, amount);
+            Self::deposit_event(RawEvent::ContractGasFunded(funder, contract, amount));
+            Ok(())
+        }
+
+        /// Withdraw the gas allowance of `contract` to the free balance of the caller,
+        /// only for the contract owner.
+        pub fn withdraw_contract_gas(origin, contract: T::AccountId, #[compact] amount: T::Balance) -> Result {
+            let who = ensure_signed(origin)?;
+            ensure_with_errorlog!(
+                Self::contract_owner_of(&contract).as_ref() == Some(&who),
+                "only the owner of the contract could withdraw the gas allowance",
+                "who:{:?}|contract:{:?}", who, contract
+            );
+            let allowance = Self::contract_gas_allowance(&contract);
+            ensure_with_errorlog!(
+                amount <= allowance,
+                "not enough gas allowance for this contract",
+                "contract:{:?}|amount:{:}|allowance:{:}", contract, amount, allowance
+            );
+            xassets::Module::<T>::pcx_move_balance(
+                &contract,
+                AssetType::ReservedCurrency,
+                &who,
+                AssetType::Free,
+                amount,
+            )
+            .map_err(|e| e.info())?;
+            Self::set_gas_allowance(&contract, allowance - amount);
+            Self::deposit_event(RawEvent::ContractGasReleased(contract, who, amount));
+            Ok(())
+        }
+
+        /// Authorize `sponsor` to pay the gas for the calls to the `dest` contract made as the
+        /// caller, each with a gas limit no more than `max_gas`, or revoke it with `None`.
+        pub fn set_sponsor(origin, sponsor: T::AccountId, dest: T::AccountId, max_gas: Option<Gas>) -> Result {
+            let beneficiary = ensure_signed(origin)?;
+            let key = (beneficiary.clone(), sponsor.clone(), dest.clone());
+            match max_gas {
+                Some(max_gas) => SponsorAllowance::<T>::insert(key, max_gas),
+                None => SponsorAllowance::<T>::remove(key),
+            }
+            Self::deposit_event(RawEvent::SponsorSet(beneficiary, sponsor, dest, max_gas));
+            Ok(())
+        }
+
+        /// Set where the unused gas of the calls sponsored by the caller is refunded to.
+        pub fn set_gas_refund_policy(origin, policy: GasRefundPolicy) -> Result {
+            let sponsor = ensure_signed(origin)?;
+            if policy == GasRefundPolicy::default() {
+                GasRefundPolicyOf::<T>::remove(&sponsor);
+            } else {
+                GasRefundPolicyOf::<T>::insert(&sponsor, policy);
+            }
+            Ok(())
+        }
+
         /// Instantiates a new contract from the `codehash` generated by `put_code`, optionally transferring some balance.
         ///
         /// Instantiation is executed as follows:
@@ -540,19 +916,10 @@ decl_module! {
             data: Vec<u8>
         ) -> Result {
             let origin = ensure_signed(origin)?;
+            Self::check_approved_code_hash(&code_hash)?;
             info!("[instantiate]|create new contract|from:{:?}|endowment:{:}|code_hash:{:?}|data:{:}", origin, endowment, code_hash, try_hex_or_str(&data));
-            Self::execute_wasm(origin, None, gas_limit, |ctx, gas_meter| {
-                ctx.instantiate(endowment, gas_meter, &code_hash, data)
-                    .map(|(_address, output)| {
-                        if output.is_success() {
-                            info!("[instantiate]|succeed to create contract:{:?}", _address);
-                        } else {
-                            info!("[instantiate]|fail to create contract:{:?}|status:{:}|data:{:?}", _address, output.status, try_hex_or_str(&output.data));
-                        }
-                        output
-                    })
-            })
-            .and_then(|output| {
+            Self::bare_instantiate(origin, endowment, gas_limit, code_hash, data)
+            .and_then(|(_, output)| {
                 if output.is_success() {
                     Ok(()) // just drop output
                 } else {
@@ -570,9 +937,9 @@ decl_module! {
         ///
         /// If contract is not evicted as a result of this call, no actions are taken and
         /// the sender is not eligible for the reward.
-        fn claim_surcharge(origin, _dest: T::AccountId, aux_sender: Option<T::AccountId>) {
+        fn claim_surcharge(origin, dest: T::AccountId, aux_sender: Option<T::AccountId>) {
             let origin = origin.into();
-            let (signed, _rewarded) = match (origin, aux_sender) {
+            let (signed, rewarded) = match (origin, aux_sender) {
                 (Ok(system::RawOrigin::Signed(account)), None) => {
                     (true, account)
                 },
@@ -588,49 +955,172 @@ decl_module! {
             // Add some advantage for block producers (who send unsigned extrinsics) by
             // adding a handicap: for signed extrinsics we use a slightly older block number
             // for the eviction check. This can be viewed as if we pushed regular users back in past.
-            let _handicap = if signed {
+            let handicap = if signed {
                 T::SignedClaimHandicap::get()
             } else {
                 Zero::zero()
             };
 
             // If poking the contract has lead to eviction of the contract, give out the rewards.
-            // if rent::try_evict::<T>(&dest, handicap) == rent::RentOutcome::Evicted {
-            //     T::Currency::deposit_into_existing(&rewarded, T::SurchargeReward::get())?;
-            // }
+            if rent::try_evict::<T>(&dest, handicap) == rent::RentOutcome::Evicted {
+                Self::release_gas_allowance(&dest);
+                rent::pay_surcharge_reward::<T>(&rewarded, T::SurchargeReward::get());
+                let tombstone = <ContractInfoOf<T>>::exists(&dest);
+                Self::deposit_event(RawEvent::Evicted(dest, tombstone));
+            }
         }
 
         /// Set gas price by root
-        pub fn set_gas_price(#[compact] price: T::Balance) {
+        pub fn set_gas_price(#[compact] price: T::Balance) -> Result {
+            let (floor, ceiling) = Self::gas_price_bounds();
+            ensure_with_errorlog!(
+                price >= floor && price <= ceiling,
+                "gas price is out of the bounds",
+                "price:{:}|floor:{:}|ceiling:{:}", price, floor, ceiling
+            );
             info!("[set_gas_price]|set new gas price:{:}", price);
+            Self::note_gas_price_change(Self::gas_price());
             GasPrice::<T>::mutate(|p| *p = price);
+            Ok(())
+        }
+
+        /// Set the floor and ceiling of the gas price accepted by `set_gas_price`.
+        pub fn set_gas_price_bounds(#[compact] floor: T::Balance, #[compact] ceiling: T::Balance) -> Result {
+            if floor.is_zero() {
+                return Err("gas price floor should be greater than zero");
+            }
+            if floor > ceiling {
+                return Err("gas price floor should not be greater than the ceiling");
+            }
+            GasPriceBounds::<T>::put((floor, ceiling));
+            Ok(())
+        }
+
+        /// Set the ratio in parts per million of the surcharge reward paid to the claimer of an
+        /// eviction, the rest is kept by the council.
+        pub fn set_surcharge_reward_ratio(ratio: u32) -> Result {
+            if ratio > 1_000_000 {
+                return Err("surcharge reward ratio should not be greater than 1_000_000");
+            }
+            SurchargeRewardRatio::<T>::put(ratio);
+            Ok(())
         }
 
-        /// Enable of Off println for contract. Just for debug.
-        pub fn set_println(state: bool) {
+        /// Set how many recent blocks the gas price history covers.
+        pub fn set_gas_price_history_window(window: T::BlockNumber) {
+            GasPriceHistoryWindow::<T>::put(window);
+        }
+
+        /// Set the ratio in parts per million of the spent gas fee paid to the council,
+        /// the rest is destroyed.
+        pub fn set_gas_fee_treasury_ratio(ratio: u32) -> Result {
+            if ratio > 1_000_000 {
+                return Err("gas fee treasury ratio should not be greater than 1_000_000");
+            }
+            GasFeeTreasuryRatio::<T>::put(ratio);
+            Ok(())
+        }
+
+        /// Turn on/off the dynamic gas price, which decays toward `baseline` when blocks are under-full.
+        pub fn set_dynamic_gas_price(enabled: bool, #[compact] baseline: T::Balance) {
+            info!("[set_dynamic_gas_price]|enabled:{:}|baseline:{:}", enabled, baseline);
+            DynamicGasPrice::<T>::put(enabled);
+            BaselineGasPrice::<T>::put(baseline);
+        }
+
+        /// Set the max nesting depth when calling the xrc20 instance.
+        pub fn set_xrc20_max_depth(depth: u32) -> Result {
+            if depth == 0 {
+                return Err("xrc20 max depth should be greater than 0");
+            }
+            XRC20MaxDepth::<T>::put(depth);
+            Ok(())
+        }
+
+        /// Approve or disapprove a code hash to be instantiated in mainnet.
+        pub fn set_approved_code_hash(code_hash: CodeHash<T>, approved: bool) {
+            info!("[set_approved_code_hash]|code_hash:{:?}|approved:{:}", code_hash, approved);
+            ApprovedCodeHashes::<T>::mutate(|hashes| {
+                if approved {
+                    if !hashes.contains(&code_hash) {
+                        hashes.push(code_hash);
+                    }
+                } else {
+                    hashes.retain(|h| *h != code_hash);
+                }
+            });
+        }
+
+        /// Accept or reject a token other than PCX for paying the gas.
+        pub fn set_gas_payment_token(token: Token, accepted: bool) -> Result {
+            if accepted {
+                xassets::Module::<T>::should_not_chainx(&token)?;
+                xassets::Module::<T>::get_asset(&token)?;
+                GasPaymentTokens::<T>::mutate(|tokens| {
+                    if !tokens.contains(&token) {
+                        tokens.push(token);
+                    }
+                });
+            } else {
+                GasPaymentTokens::<T>::mutate(|tokens| tokens.retain(|t| *t != token));
+            }
+            Ok(())
+        }
+
+        /// Enable of Off println for contract. Just for debug, so it could not be enabled in mainnet.
+        pub fn set_println(state: bool) -> Result {
+            let (network, _) = xsystem::Module::<T>::network_props();
+            ensure_with_errorlog!(
+                !state || network != xsystem::NetworkType::Mainnet,
+                "[set_println]|in mainnet, println could not be enabled.",
+                "[set_println]|network:{:?}",
+                network
+            );
             CurrentSchedule::<T>::mutate(|s| {
                 s.enable_println = state;
             });
+            Ok(())
         }
 
         // xrc20 and runtime assets
-        /// Convert asset balance to xrc20 token. This function would call xrc20 `issue` interface.
-        /// The gas cast would deduct the caller.
+        /// Convert asset balance to the token of the bound contract. This function would call the
+        /// xrc20 `issue` or the xrc777 `mint` interface according to the binding of the token.
+        /// The gas cast would deduct the caller. If the `issue` runs out of gas, it would be retried
+        /// once with a doubled gas limit (no more than `MaxGasPerExtrinsic`), and the caller pays
+        /// for the gas of both attempts.
         pub fn convert_to_xrc20(origin, token: Token, #[compact] value: T::Balance, #[compact] gas_limit: Gas) -> Result {
             let origin = ensure_signed(origin)?;
-            Self::issue_to_xrc20(token, origin, value, gas_limit)
+            match Self::issue_to_xrc20(token.clone(), origin.clone(), value, gas_limit) {
+                Err(ref e) if is_out_of_gas(e.reason) => {
+                    let retry_gas_limit = gas_limit.saturating_mul(2).min(T::MaxGasPerExtrinsic::get());
+                    if retry_gas_limit <= gas_limit {
+                        return Err(e.reason);
+                    }
+                    warn!(
+                        "[convert_to_xrc20]|issue ran out of gas, retry with a higher gas limit|token:{:}|who:{:?}|gas_limit:{:}|retry_gas_limit:{:}",
+                        token!(token), origin, gas_limit, retry_gas_limit
+                    );
+                    Self::issue_to_xrc20(token, origin, value, retry_gas_limit)
+                }
+                result => result,
+            }
+            .map_err(|e| e.reason)
         }
 
         /// Convert xrc20 token to asset balance. This function could not be called from an extrinsic,
         /// just could be called inside the xrc20, XRC777 and etc contract instance.
         pub fn convert_to_asset(origin, to: T::AccountId, #[compact] value: T::Balance) -> Result {
             let origin = ensure_signed(origin)?;
-            // check token xrc20 is exist
-            Self::refund_to_asset(origin, to, value)
+            // check token xrc20 is exist and the origin is the xrc20 contract itself
+            Self::ensure_registered_xrc20(&origin)?;
+            Self::refund_to_asset(origin, to, value).map_err(|e| e.reason)
         }
 
         /// Set the xrc20 addr and selectors for a token name.
         pub fn set_token_xrc20(token: Token, xrc20_addr: T::AccountId, selectors: BTreeMap<XRC20Selector, Selector>) {
+            if XRC777InfoOfToken::<T>::exists(&token) {
+                return Err("token is already bound to an xrc777 instance");
+            }
             XRC20InfoOfToken::<T>::insert(token.clone(), (xrc20_addr.clone(), selectors));
             TokenOfAddr::<T>::insert(xrc20_addr, token);
         }
@@ -651,6 +1141,143 @@ decl_module! {
             }
         }
 
+        /// Set the xrc777 addr and selectors for a token name, the token could not be bound to
+        /// an xrc20 instance at the same time.
+        pub fn set_token_xrc777(token: Token, xrc777_addr: T::AccountId, selectors: BTreeMap<XRC777Selector, Selector>) {
+            if XRC20InfoOfToken::<T>::exists(&token) {
+                return Err("token is already bound to an xrc20 instance");
+            }
+            XRC777InfoOfToken::<T>::insert(token.clone(), (xrc777_addr.clone(), selectors));
+            TokenOfAddr::<T>::insert(xrc777_addr, token);
+        }
+
+        /// Remove xrc777 relationship for a token name.
+        pub fn remove_token_xrc777(token: Token) {
+            if let Some(info) = XRC777InfoOfToken::<T>::take(&token) {
+                let _ = TokenOfAddr::<T>::take(info.0);
+            }
+        }
+
+        /// Set the max times of `put_code` per account within the block window in testnet,
+        /// zero count for no limit.
+        pub fn set_put_code_rate_limit(max_count: u32, window: T::BlockNumber) {
+            <PutCodeRateLimit<T>>::put((max_count, window));
+        }
+
+        /// Set the floors of the critical limits which a new schedule can't go below.
+        pub fn set_schedule_limit_floors(floors: ScheduleLimitFloors) {
+            ScheduleFloors::<T>::put(floors);
+        }
+
+        /// Register the layout of the contract event with the selector, `None` to remove it,
+        /// only for root or the contract owner.
+        pub fn set_contract_event_layout(origin, address: T::AccountId, selector: Selector, layout: Option<ContractEventLayout>) -> Result {
+            match origin.into() {
+                Ok(RawOrigin::Root) => (),
+                Ok(RawOrigin::Signed(who)) => {
+                    ensure_with_errorlog!(
+                        Self::contract_owner_of(&address).as_ref() == Some(&who),
+                        "only the owner of the contract could set the event layout",
+                        "who:{:?}|contract:{:?}", who, address
+                    );
+                }
+                _ => return Err("bad origin: expected to be root or signed origin"),
+            }
+            match layout {
+                Some(layout) => ContractEventLayouts::<T>::insert(&(address, selector), layout),
+                None => ContractEventLayouts::<T>::remove(&(address, selector)),
+            }
+            Ok(())
+        }
+
+        /// Set the owner of the contract, e.g. backfill the owners of the contracts instantiated
+        /// before the owners are recorded, `None` to remove it.
+        pub fn set_contract_owner(contract: T::AccountId, owner: Option<T::AccountId>) -> Result {
+            if !<ContractInfoOf<T>>::exists(&contract) {
+                return Err("the contract does not exist");
+            }
+            match owner {
+                Some(owner) => <ContractOwnerOf<T>>::insert(&contract, owner),
+                None => <ContractOwnerOf<T>>::remove(&contract),
+            }
+            Ok(())
+        }
+
+        /// Migrate the xrc20 binding of a token to a new contract, the `ReservedXRC20` balance
+        /// of the old contract is moved to the new one before repointing.
+        pub fn migrate_token_xrc20(token: Token, new_addr: T::AccountId, selectors: BTreeMap<XRC20Selector, Selector>) -> Result {
+            let (old_addr, _) = Self::xrc20_of_token(&token).ok_or("no xrc20 binding for this token")?;
+            ensure_with_errorlog!(
+                old_addr != new_addr,
+                "new xrc20 address should not be the current one",
+                "token:{:}|xrc20:{:?}", token!(token), old_addr
+            );
+            ensure_with_errorlog!(
+                Self::token_of_addr(&new_addr).is_none(),
+                "new xrc20 address is already bound to a token",
+                "token:{:}|new xrc20:{:?}", token!(token), new_addr
+            );
+            match ContractInfoOf::<T>::get(&new_addr) {
+                Some(ContractInfo::Alive(_)) => {}
+                _ => return Err("new xrc20 address is not an alive contract"),
+            }
+
+            let reserved = xassets::Module::<T>::asset_balance_of(&old_addr, &token, AssetType::ReservedXRC20);
+            let _ = xassets::Module::<T>::move_balance(
+                &token,
+                &old_addr,
+                AssetType::ReservedXRC20,
+                &new_addr,
+                AssetType::ReservedXRC20,
+                reserved,
+            )
+            .map_err(|e| e.info())?;
+            ensure_with_errorlog!(
+                xassets::Module::<T>::asset_balance_of(&old_addr, &token, AssetType::ReservedXRC20).is_zero(),
+                "reserved balance of the old xrc20 is not fully migrated",
+                "token:{:}|old xrc20:{:?}|new xrc20:{:?}", token!(token), old_addr, new_addr
+            );
+
+            TokenOfAddr::<T>::remove(&old_addr);
+            XRC20InfoOfToken::<T>::insert(token.clone(), (new_addr.clone(), selectors));
+            TokenOfAddr::<T>::insert(new_addr.clone(), token.clone());
+
+            info!(
+                "[migrate_token_xrc20]|token:{:}|old xrc20:{:?}|new xrc20:{:?}|reserved:{:}",
+                token!(token), old_addr, new_addr, reserved
+            );
+            Self::deposit_event(RawEvent::Xrc20Migrated(token, old_addr, new_addr, reserved));
+            Ok(())
+        }
+
+        /// Move the storage of an alive contract to a freshly generated trie id, the old trie is
+        /// killed afterwards. The child trie can't be enumerated, the keys could be paged in
+        /// several calls of at most `MaxRestoreDelta` keys, the trie is switched once all the
+        /// storage of the contract is copied, which is verified by the storage root. The copy is
+        /// metered against `gas_limit` by the data read and write costs of the current schedule.
+        pub fn migrate_contract_trie(contract: T::AccountId, keys: Vec<exec::StorageKey>, #[compact] gas_limit: Gas) -> Result {
+            Self::migrate_trie_of(contract, keys, gas_limit)
+        }
+
+        /// Import the snapshot exported by `export_contract` as a new contract at `address`,
+        /// the code of the snapshot should have been stored. The storage could be paged in
+        /// several snapshots of at most `MaxRestoreDelta` keys, the contract is created once
+        /// all the storage of the snapshot is imported.
+        pub fn import_contract(address: T::AccountId, snapshot: ContractSnapshotOf<T>) -> Result {
+            Self::import_contract_impl(address, snapshot)
+        }
+
+        /// Drop the unfinished trie migration or import of the address, e.g. the contract has
+        /// been written in between and the pages don't add up to its storage any more.
+        pub fn cancel_pending_contract_trie(address: T::AccountId) {
+            if let Some((trie_id, _)) = <PendingTrieMigration<T>>::take(&address) {
+                child::kill_storage(&trie_id);
+            }
+            if let Some((trie_id, _)) = <PendingContractImport<T>>::take(&address) {
+                child::kill_storage(&trie_id);
+            }
+        }
+
         /// Force issue xrc20 token.
         pub fn force_issue_xrc20(token: Token, issues: Vec<(T::AccountId, T::Balance)>, gas_limit: Gas) -> Result {
             for (origin, value)  in issues {
@@ -663,18 +1290,56 @@ decl_module! {
             Ok(())
         }
 
+        fn on_initialize() {
+            if Self::dynamic_gas_price() {
+                Self::decay_gas_price();
+            }
+        }
+
         fn on_finalize() {
-            GasSpent::<T>::kill();
+            LastGasSpent::<T>::put(GasSpent::<T>::take());
+            for contract in CalledContracts::<T>::take() {
+                <CallCountOf<T>>::remove(&contract);
+            }
         }
     }
 }
 
+/// Whether the execution error is caused by running out of gas, which may succeed with a higher
+/// gas limit.
+fn is_out_of_gas(reason: &str) -> bool {
+    [
+        "not enough gas to pay base call fee",
+        "not enough gas to pay transfer fee",
+        "ran out of gas during contract execution",
+    ]
+    .contains(&reason)
+}
+
+/// The detail of a failed asset movement in the xrc20 conversions, which is encoded into the
+/// buffer of `ExecError`.
+#[derive(Encode, Decode, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct AssetMoveError<AccountId, Balance> {
+    pub err: xassets::AssetErr,
+    pub token: Token,
+    pub from: AccountId,
+    pub from_type: AssetType,
+    /// The balance of `from_type` for `from` when failed.
+    pub available: Balance,
+    pub to: AccountId,
+    pub to_type: AssetType,
+    pub value: Balance,
+}
+
 /// The possible errors that can happen querying the storage of a contract.
 pub enum GetStorageError {
     /// The given address doesn't point on a contract.
     ContractDoesntExist,
     /// The specified contract is a tombstone and thus cannot have any storage.
     IsTombstone,
+    /// More than `MAX_STORAGE_KEYS_PER_QUERY` keys are queried at once.
+    TooManyKeys,
 }
 
 /// Public APIs provided by the contracts module.
@@ -690,15 +1355,140 @@ impl<T: Trait> Module<T> {
         gas_limit: Gas,
         input_data: Vec<u8>,
     ) -> ExecResult {
-        if <ContractInfoOf<T>>::get(&dest).is_none() {
-            return Err(ExecError {
-                reason: "unable to call dest contract as it does not exist",
-                buffer: input_data,
-            });
+        match <ContractInfoOf<T>>::get(&dest) {
+            None => {
+                return Err(ExecError {
+                    reason: "unable to call dest contract as it does not exist",
+                    buffer: input_data,
+                });
+            }
+            Some(ContractInfo::Tombstone(_)) => {
+                warn!(
+                    "[bare_call]|unable to call a tombstone contract|origin:{:?}|dest:{:?}",
+                    origin, dest
+                );
+                Self::deposit_event(RawEvent::CallToTombstone(origin, dest));
+                return Err(ExecError {
+                    reason: "contract is a tombstone",
+                    buffer: input_data,
+                });
+            }
+            Some(ContractInfo::Alive(_)) => {}
         }
-        Self::execute_wasm(origin, None, gas_limit, |ctx, gas_meter| {
-            ctx.call(dest, value, gas_meter, input_data)
-        })
+        Self::execute_wasm_with_config(
+            origin,
+            None,
+            None,
+            None,
+            Some(dest.clone()),
+            gas_limit,
+            Config::preload(),
+            |ctx, gas_meter| ctx.call(dest, value, gas_meter, input_data),
+        )
+    }
+
+    /// Instantiate a new contract from the given code hash.
+    ///
+    /// This function is similar to `Self::instantiate`, but returns the address of the new
+    /// contract along with the output of the constructor, and better suitable for instantiating
+    /// directly from Rust.
+    pub fn bare_instantiate(
+        origin: T::AccountId,
+        endowment: T::Balance,
+        gas_limit: Gas,
+        code_hash: CodeHash<T>,
+        data: Vec<u8>,
+    ) -> rstd::result::Result<(T::AccountId, ExecReturnValue), ExecError> {
+        let mut instantiated = None;
+        let output = Self::execute_wasm(origin.clone(), None, gas_limit, |ctx, gas_meter| {
+            ctx.instantiate(endowment, gas_meter, &code_hash, data)
+                .map(|(address, output)| {
+                    instantiated = Some(address);
+                    output
+                })
+        })?;
+        let address = instantiated.ok_or_else(|| ExecError {
+            reason: "fail to create contract, the address is unknown",
+            buffer: Vec::new(),
+        })?;
+
+        if output.is_success() {
+            info!(
+                "[bare_instantiate]|succeed to create contract:{:?}|from:{:?}",
+                address, origin
+            );
+        } else {
+            info!(
+                "[bare_instantiate]|fail to create contract:{:?}|from:{:?}|status:{:}|data:{:?}",
+                address,
+                origin,
+                output.status,
+                try_hex_or_str(&output.data)
+            );
+        }
+        Ok((address, output))
+    }
+
+    /// Dry run a call to the contract without paying the gas or committing any change, return
+    /// the result along with the gas consumed, e.g. for estimating the gas limit of a call.
+    /// notice this function just allow to be called in runtime api, not allow in an extrinsic
+    pub fn dry_run_call(
+        origin: T::AccountId,
+        dest: T::AccountId,
+        value: T::Balance,
+        gas_limit: Gas,
+        input_data: Vec<u8>,
+    ) -> (ExecResult, Gas) {
+        let is_alive = <ContractInfoOf<T>>::get(&dest)
+            .and_then(|info| info.get_alive())
+            .is_some();
+        if !is_alive {
+            let err = ExecError {
+                reason: "unable to call dest contract as it is not alive",
+                buffer: input_data,
+            };
+            return (Err(err), 0);
+        }
+
+        let cfg = Config::preload();
+        let vm = WasmVm::new(&cfg.schedule);
+        let loader = WasmLoader::new(&cfg.schedule);
+        let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader);
+        let mut gas_meter = GasMeter::<T>::with_limit(gas_limit, Self::gas_price());
+
+        // neither the overlay nor the deferred actions of the context are applied.
+        let result = ctx.call(dest, value, &mut gas_meter, input_data);
+        (result, gas_limit.saturating_sub(gas_meter.gas_left()))
+    }
+
+    /// Decode the payload of a `ContractExecution` event by the registered layout, the first 4 bytes
+    /// of the payload are the selector of the event, returns the event name and the named fields.
+    pub fn decode_contract_event(
+        address: T::AccountId,
+        data: Vec<u8>,
+    ) -> Option<(XString, Vec<(XString, ContractEventFieldValue)>)> {
+        if data.len() < 4 {
+            return None;
+        }
+        let mut selector: Selector = Default::default();
+        selector.copy_from_slice(&data[..4]);
+        let layout = Self::contract_event_layout(&(address, selector))?;
+        let fields = layout.decode(&data[4..])?;
+        Some((layout.name, fields))
+    }
+
+    /// List the tombstone contracts which could be restored, paginated by `offset` and `limit`.
+    pub fn tombstone_contracts(offset: u32, limit: u32) -> Vec<T::AccountId> {
+        Self::tombstone_index()
+            .into_iter()
+            .filter(|who| {
+                <ContractInfoOf<T>>::get(who)
+                    .map(|info| info.as_tombstone().is_some())
+                    .unwrap_or(false)
+            })
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect()
     }
 
     /// Query storage of a specified contract under a specified key.
@@ -720,6 +1510,113 @@ impl<T: Trait> Module<T> {
         Ok(maybe_value)
     }
 
+    /// Query storage of a specified contract under multiple keys, the results are in the order of keys.
+    ///
+    /// At most `MAX_STORAGE_KEYS_PER_QUERY` keys could be queried.
+    pub fn get_storage_multi(
+        address: T::AccountId,
+        keys: Vec<[u8; 32]>,
+    ) -> rstd::result::Result<Vec<Option<Vec<u8>>>, GetStorageError> {
+        if keys.len() > MAX_STORAGE_KEYS_PER_QUERY {
+            error!(
+                "[get_storage_multi]|too many keys|len:{:}|max:{:}",
+                keys.len(),
+                MAX_STORAGE_KEYS_PER_QUERY
+            );
+            return Err(GetStorageError::TooManyKeys);
+        }
+        let contract_info = <ContractInfoOf<T>>::get(&address)
+            .ok_or(GetStorageError::ContractDoesntExist)?
+            .get_alive()
+            .ok_or(GetStorageError::IsTombstone)?;
+
+        let values = keys
+            .iter()
+            .map(|key| {
+                AccountDb::<T>::get_storage(
+                    &DirectAccountDb,
+                    &address,
+                    Some(&contract_info.trie_id),
+                    key,
+                )
+            })
+            .collect();
+        Ok(values)
+    }
+
+    /// Estimate the gas `put_code` would consume for the code of the given length,
+    /// which is all charged for storing the code under the current schedule.
+    pub fn estimate_put_code_gas(code_len: u32) -> Gas {
+        wasm::save_code_gas_floor::<T>(code_len as usize, &Self::current_schedule())
+    }
+
+    /// The minimum gas limit with which `put_code` could store the given code under the
+    /// current schedule. The code is instrumented here just like `put_code` does, so the
+    /// code that could never be stored is rejected with the same error.
+    pub fn min_put_code_gas(code: &[u8]) -> rstd::result::Result<Gas, &'static str> {
+        wasm::save_code_gas::<T>(code, &Self::current_schedule())
+    }
+
+    /// Query `(rent_allowance, deduct_block, storage_size)` of an alive contract,
+    /// `None` for a tombstone or missing contract.
+    pub fn rent_status(address: T::AccountId) -> Option<(T::Balance, T::BlockNumber, u32)> {
+        <ContractInfoOf<T>>::get(&address)
+            .and_then(|info| info.get_alive())
+            .map(|info| (info.rent_allowance, info.deduct_block, info.storage_size))
+    }
+
+    /// Dry run the instantiation without paying the gas or committing any change, return the
+    /// address of the would-be contract, or the error if the constructor fails, e.g. due to the
+    /// undecodable instantiate data.
+    /// notice this function just allow to be called in runtime api, not allow in an extrinsic
+    pub fn validate_instantiate(
+        origin: T::AccountId,
+        code_hash: CodeHash<T>,
+        endowment: T::Balance,
+        gas_limit: Gas,
+        data: Vec<u8>,
+    ) -> rstd::result::Result<T::AccountId, ExecError> {
+        let cfg = Config::preload();
+        let vm = WasmVm::new(&cfg.schedule);
+        let loader = WasmLoader::new(&cfg.schedule);
+        let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader);
+        let mut gas_meter = GasMeter::<T>::with_limit(gas_limit, Self::gas_price());
+
+        // the overlay of the context is dropped without being committed.
+        let (address, output) = ctx.instantiate(endowment, &mut gas_meter, &code_hash, data)?;
+        if output.is_success() {
+            Ok(address)
+        } else {
+            Err(ExecError {
+                reason: "fail to create contract, maybe instantiate data decode error",
+                buffer: output.data,
+            })
+        }
+    }
+
+    /// Export a snapshot of an alive contract, `None` for a tombstone or missing contract.
+    /// The child trie can't be enumerated, only the values under `keys` are exported. The
+    /// storage could be exported in pages of at most `MaxRestoreDelta` keys, which are imported
+    /// one by one, the import is done once the pages cover all the storage of the contract.
+    pub fn export_contract(
+        address: T::AccountId,
+        keys: Vec<exec::StorageKey>,
+    ) -> Option<ContractSnapshotOf<T>> {
+        let info = <ContractInfoOf<T>>::get(&address).and_then(|c| c.get_alive())?;
+        let storage = keys
+            .into_iter()
+            .filter_map(|key| child::get_raw(&info.trie_id, &blake2_256(&key)).map(|v| (key, v)))
+            .collect();
+        Some(ContractSnapshot {
+            code_hash: info.code_hash,
+            storage,
+            storage_root: runtime_io::child_storage_root(&info.trie_id),
+            rent_allowance: info.rent_allowance,
+            storage_size: info.storage_size,
+            deduct_block: info.deduct_block,
+        })
+    }
+
     /// Query a call to a specified xrc20 token.
     /// notice this function just allow to be called in runtime api, not allow in an extrinsic
     pub fn call_xrc20(
@@ -742,43 +1639,107 @@ impl<T: Trait> Module<T> {
         Self::call_for_xrc20(token, pay_gas, gas_limit, selector, data)
     }
 
+    /// The selectors registered for the xrc20 of the token, `None` if the token is not bound.
+    pub fn xrc20_selectors(token: &Token) -> Option<BTreeMap<XRC20Selector, Selector>> {
+        Self::xrc20_of_token(token).map(|(_, selectors)| selectors)
+    }
+
+    /// Query the xrc20 balances of the holders, each query is paid by `pay_gas` with `gas_limit`.
+    /// Return an error if there are more than `MAX_XRC20_BALANCE_HOLDERS` holders.
+    /// notice this function just allow to be called in runtime api, not allow in an extrinsic
+    pub fn query_xrc20_balances(
+        token: Token,
+        pay_gas: T::AccountId,
+        gas_limit: Gas,
+        holders: Vec<T::AccountId>,
+    ) -> rstd::result::Result<Vec<rstd::result::Result<T::Balance, ExecError>>, &'static str> {
+        if holders.len() > MAX_XRC20_BALANCE_HOLDERS {
+            error!(
+                "[query_xrc20_balances]|too many holders|len:{:}|max:{:}",
+                holders.len(),
+                MAX_XRC20_BALANCE_HOLDERS
+            );
+            return Err("too many holders in a query");
+        }
+
+        let balances = holders
+            .iter()
+            .map(|holder| {
+                let output = Self::call_for_xrc20(
+                    token.clone(),
+                    pay_gas.clone(),
+                    gas_limit,
+                    XRC20Selector::BalanceOf,
+                    holder.encode(),
+                )?;
+                if !output.is_success() {
+                    return Err(ExecError {
+                        reason: "fail to call the contract, please check params and xrc20",
+                        buffer: Vec::new(),
+                    });
+                }
+                Decode::decode(&mut output.data.as_slice()).ok_or_else(|| ExecError {
+                    reason: "fail decode wasm result to balance",
+                    buffer: Vec::new(),
+                })
+            })
+            .collect();
+        Ok(balances)
+    }
+
     fn issue_to_xrc20(
         token: Token,
         origin: T::AccountId,
         value: T::Balance,
         gas_limit: Gas,
-    ) -> Result {
+    ) -> rstd::result::Result<(), ExecError> {
+        let (kind, contract_addr) = Self::token_contract_of(&token).ok_or_else(|| {
+            error!(
+                "no token contract instance for this token|token:{:}",
+                token!(token)
+            );
+            "no token contract instance for this token"
+        })?;
         // check
-        ensure_with_errorlog!(
-            xassets::Module::<T>::free_balance_of(&origin, &token) >= value,
-            "not enough balance for this token to convert to xrc20 token",
-            "token:{:}|who:{:?}|value:{:}",
-            token!(token),
-            origin,
-            value
-        );
+        if xassets::Module::<T>::free_balance_of(&origin, &token) < value {
+            return Err(Self::asset_move_error(
+                xassets::AssetErr::NotEnough,
+                &token,
+                (&origin, AssetType::Free),
+                (&contract_addr, AssetType::ReservedXRC20),
+                value,
+            ));
+        }
 
         let params = (origin.clone(), value).encode();
 
-        // call xrc20 contract to issue xrc20 token
-        let exec_value = Self::call_for_xrc20(
-            token.clone(),
-            origin.clone(),
-            gas_limit,
-            XRC20Selector::Issue,
-            params,
-        )
+        // call xrc20 contract to issue xrc20 token, or xrc777 contract to mint xrc777 token
+        let exec_value = match kind {
+            TokenContractKind::XRC20 => Self::call_for_xrc20(
+                token.clone(),
+                origin.clone(),
+                gas_limit,
+                XRC20Selector::Issue,
+                params,
+            ),
+            TokenContractKind::XRC777 => Self::call_for_xrc777(
+                token.clone(),
+                origin.clone(),
+                gas_limit,
+                XRC777Selector::Mint,
+                params,
+            ),
+        }
         .and_then(|output| {
             if output.is_success() {
                 Ok(output)
             } else {
                 Err(ExecError {
-                    reason: "fail to call the contract, please check params and xrc20",
+                    reason: "fail to call the contract, please check params and the token contract",
                     buffer: Vec::new(),
                 })
             }
-        })
-        .map_err(|e| e.reason)?;
+        })?;
 
         // notice when standard xrc20 return chech, this decode method should also change
         let result: bool = Decode::decode(&mut exec_value.data.as_slice()).ok_or_else(|| {
@@ -786,26 +1747,33 @@ impl<T: Trait> Module<T> {
                 "[issue_to_xrc20]|fail to decode wasm result|data:{:}",
                 try_hex_or_str(&exec_value.data)
             );
+            match kind {
+                TokenContractKind::XRC20 => Self::deposit_event(RawEvent::Xrc20ResultDecodeFailed(
+                    token.clone(),
+                    XRC20Selector::Issue,
+                    exec_value.data.clone(),
+                )),
+                TokenContractKind::XRC777 => {
+                    Self::deposit_event(RawEvent::Xrc777ResultDecodeFailed(
+                        token.clone(),
+                        XRC777Selector::Mint,
+                        exec_value.data.clone(),
+                    ))
+                }
+            }
             "fail decode wasm result to bool"
         })?;
         if !result {
-            return Err("fail to issue token in xrc20 contract");
+            return Err("fail to issue token in the token contract".into());
         }
 
-        let xrc20_addr = Self::xrc20_of_token(&token)
-            .expect("xrc20 info must be existed at here")
-            .0;
-        // success, transfer to the xrc20 contract
-        let _ = xassets::Module::<T>::move_balance(
+        // success, transfer to the token contract
+        Self::move_convertible_balance(
             &token,
-            &origin,
-            AssetType::Free,
-            &xrc20_addr,
-            AssetType::ReservedXRC20,
+            (&origin, AssetType::Free),
+            (&contract_addr, AssetType::ReservedXRC20),
             value,
         )
-        .map_err(|e| e.info())?;
-        Ok(())
     }
 
     fn call_for_xrc20(
@@ -835,76 +1803,469 @@ impl<T: Trait> Module<T> {
             }
         })?;
 
+        debug!("[call_for_xrc20]|call xrc20 instance|token:{:}|xrc20:{:?}|pay gas:{:?}|selector:{:?}|data:{:}",
+            token!(token), xrc20_addr, pay_gas, enum_selector, try_hex_or_str(&input_data));
+
+        Self::call_token_contract(xrc20_addr, pay_gas, gas_limit, selector, input_data)
+    }
+
+    fn call_for_xrc777(
+        token: Token,
+        pay_gas: T::AccountId,
+        gas_limit: Gas,
+        enum_selector: XRC777Selector,
+        input_data: Vec<u8>,
+    ) -> ExecResult {
+        let (xrc777_addr, selectors) = Self::xrc777_of_token(&token).ok_or_else(|| {
+            error!("no xrc777 instance for this token|token:{:}", token!(token));
+            ExecError {
+                reason: "no xrc777 instance for this token",
+                buffer: Vec::new(),
+            }
+        })?;
+        let selector = selectors.get(&enum_selector).ok_or_else(|| {
+            error!(
+                "no selector in xrc777 info for this token|token:{:}|selector:{:?}",
+                token!(token),
+                enum_selector
+            );
+            ExecError {
+                reason: "no selector in xrc777 info for this token",
+                buffer: Vec::new(),
+            }
+        })?;
+
+        debug!("[call_for_xrc777]|call xrc777 instance|token:{:}|xrc777:{:?}|pay gas:{:?}|selector:{:?}|data:{:}",
+            token!(token), xrc777_addr, pay_gas, enum_selector, try_hex_or_str(&input_data));
+
+        Self::call_token_contract(xrc777_addr, pay_gas, gas_limit, selector, input_data)
+    }
+
+    /// Call the token contract instance with the selector prepended to the input data.
+    fn call_token_contract(
+        contract_addr: T::AccountId,
+        pay_gas: T::AccountId,
+        gas_limit: Gas,
+        selector: &Selector,
+        input_data: Vec<u8>,
+    ) -> ExecResult {
         let mut data = selector.to_vec(); // provide selector
         data.extend_from_slice(input_data.as_slice());
 
-        debug!("[call_for_xrc20]|call xrc20 instance|token:{:}|xrc20:{:?}|pay gas:{:?}|selector:{:?}|data:{:}",
-            token!(token), xrc20_addr, pay_gas, enum_selector, try_hex_or_str(&data));
-
-        Self::execute_wasm(
-            xrc20_addr.clone(),
+        // the token contract instance could not nest as deep as the general calls.
+        // NOTE: `convert_to_asset` from the instance is a deferred dispatch, it's executed after
+        // the instance execution finished, so it never nests in this call.
+        Self::execute_wasm_with_config(
+            contract_addr.clone(),
             Some(pay_gas),
+            None,
+            None,
+            Some(contract_addr.clone()),
             gas_limit,
-            |ctx, gas_meter| ctx.call(xrc20_addr.clone(), Zero::zero(), gas_meter, data),
+            Config::preload_for_xrc20(),
+            |ctx, gas_meter| ctx.call(contract_addr.clone(), Zero::zero(), gas_meter, data),
         )
     }
 
-    fn refund_to_asset(contract_addr: T::AccountId, to: T::AccountId, value: T::Balance) -> Result {
+    /// The kind and the address of the contract instance the token is bound to.
+    pub fn token_contract_of(token: &Token) -> Option<(TokenContractKind, T::AccountId)> {
+        if let Some((xrc20_addr, _)) = Self::xrc20_of_token(token) {
+            return Some((TokenContractKind::XRC20, xrc20_addr));
+        }
+        Self::xrc777_of_token(token)
+            .map(|(xrc777_addr, _)| (TokenContractKind::XRC777, xrc777_addr))
+    }
+
+    /// Ensure the account is the alive xrc20 or xrc777 contract registered for its token.
+    fn ensure_registered_xrc20(addr: &T::AccountId) -> Result {
+        let token = Self::token_of_addr(addr).ok_or("no token for this contract address")?;
+        let registered = Self::token_contract_of(&token).map(|(_, contract)| contract);
+        let is_contract = <ContractInfoOf<T>>::get(addr)
+            .and_then(|info| info.get_alive())
+            .is_some();
+        ensure_with_errorlog!(
+            registered.as_ref() == Some(addr) && is_contract,
+            "only the registered token contract could convert to asset",
+            "token:{:}|addr:{:?}|registered:{:?}|is_contract:{:}",
+            token!(token),
+            addr,
+            registered,
+            is_contract
+        );
+        Ok(())
+    }
+
+    fn refund_to_asset(
+        contract_addr: T::AccountId,
+        to: T::AccountId,
+        value: T::Balance,
+    ) -> rstd::result::Result<(), ExecError> {
         let token: Token = Self::token_of_addr(&contract_addr).ok_or_else(|| {
             error!(
-                "no token for this xrc20 address|xrc20 addr:{:?}",
+                "no token for this contract address|contract addr:{:?}",
                 contract_addr
             );
-            "no token for this xrc20 address"
+            "no token for this contract address"
         })?;
-        let current_reserved = xassets::Module::<T>::asset_balance_of(
-            &contract_addr,
-            &token,
-            AssetType::ReservedXRC20,
-        );
-        ensure_with_errorlog!(
-            current_reserved >= value,
-            "not enough balance for this xrc20 instance to refund asset",
-            "token:{:}|xrc20:{:?}|value:{:}|current:{:}",
-            token!(token),
-            contract_addr,
-            value,
-            current_reserved
-        );
 
-        // success, refund asset to this account
-        let _ = xassets::Module::<T>::move_balance(
+        // refund asset to this account
+        Self::move_convertible_balance(
             &token,
-            &contract_addr,
-            AssetType::ReservedXRC20,
-            &to,
-            AssetType::Free,
+            (&contract_addr, AssetType::ReservedXRC20),
+            (&to, AssetType::Free),
             value,
         )
-        .map_err(|e| e.info())?;
+    }
+
+    /// Move the asset balance between `Free` and `ReservedXRC20` in the conversions, the balance
+    /// is expected to be conserved by the movement, otherwise it's logged as an error.
+    fn move_convertible_balance(
+        token: &Token,
+        from: (&T::AccountId, AssetType),
+        to: (&T::AccountId, AssetType),
+        value: T::Balance,
+    ) -> rstd::result::Result<(), ExecError> {
+        let convertible_total = Self::xrc20_convertible_total(token);
+        Self::move_asset_balance(token, from, to, value)?;
+        if !Self::xrc20_balance_conserved(token, convertible_total) {
+            error!(
+                "[move_convertible_balance]|xrc20 balance is not conserved|token:{:}|from:{:?}|from_type:{:?}|to:{:?}|to_type:{:?}|value:{:}|expected total:{:}|total:{:}",
+                token!(token),
+                from.0,
+                from.1,
+                to.0,
+                to.1,
+                value,
+                convertible_total,
+                Self::xrc20_convertible_total(token)
+            );
+        }
         Ok(())
     }
+
+    /// Move the asset balance, the detail of the failure is encoded into the buffer of `ExecError`.
+    fn move_asset_balance(
+        token: &Token,
+        from: (&T::AccountId, AssetType),
+        to: (&T::AccountId, AssetType),
+        value: T::Balance,
+    ) -> rstd::result::Result<(), ExecError> {
+        xassets::Module::<T>::move_balance(token, from.0, from.1, to.0, to.1, value)
+            .map(|_| ())
+            .map_err(|err| Self::asset_move_error(err, token, from, to, value))
+    }
+
+    fn asset_move_error(
+        err: xassets::AssetErr,
+        token: &Token,
+        from: (&T::AccountId, AssetType),
+        to: (&T::AccountId, AssetType),
+        value: T::Balance,
+    ) -> ExecError {
+        let detail = AssetMoveError {
+            err,
+            token: token.clone(),
+            from: from.0.clone(),
+            from_type: from.1,
+            available: xassets::Module::<T>::asset_balance_of(from.0, token, from.1),
+            to: to.0.clone(),
+            to_type: to.1,
+            value,
+        };
+        error!(
+            "[asset_move_error]|{:}|token:{:}|from:{:?}|from_type:{:?}|available:{:}|to:{:?}|to_type:{:?}|value:{:}",
+            err.info(),
+            token!(token),
+            detail.from,
+            detail.from_type,
+            detail.available,
+            detail.to,
+            detail.to_type,
+            value
+        );
+        ExecError {
+            reason: err.info(),
+            buffer: detail.encode(),
+        }
+    }
 }
 
 impl<T: Trait> Module<T> {
+    /// The `ReservedXRC20` balance held by the xrc20 or xrc777 instance of the token, which is
+    /// the reserved bucket shared by both kinds of the token contract.
+    pub fn total_xrc20_reserved(token: &Token) -> T::Balance {
+        match Self::token_contract_of(token) {
+            Some((_, xrc20_addr)) => {
+                xassets::Module::<T>::asset_balance_of(&xrc20_addr, token, AssetType::ReservedXRC20)
+            }
+            None => Zero::zero(),
+        }
+    }
+
+    /// The total `Free` and `ReservedXRC20` balance of the token, the conversions between
+    /// the asset and its xrc20 only move the balance between these two types.
+    pub fn xrc20_convertible_total(token: &Token) -> T::Balance {
+        let totals = xassets::Module::<T>::total_asset_balance(token);
+        [AssetType::Free, AssetType::ReservedXRC20]
+            .iter()
+            .filter_map(|type_| totals.get(type_))
+            .fold(Zero::zero(), |acc, x| acc + *x)
+    }
+
+    /// Whether `xrc20_convertible_total` of the token still equals `expected_total`, and all the
+    /// `ReservedXRC20` balance of the token is held by its xrc20 instance.
+    pub fn xrc20_balance_conserved(token: &Token, expected_total: T::Balance) -> bool {
+        let reserved = xassets::Module::<T>::total_asset_balance(token)
+            .get(&AssetType::ReservedXRC20)
+            .cloned()
+            .unwrap_or_else(Zero::zero);
+        Self::xrc20_convertible_total(token) == expected_total
+            && reserved == Self::total_xrc20_reserved(token)
+    }
+
+    /// The `ReservedXRC20` balance of all the registered tokens bound to an xrc20 or xrc777 instance.
+    pub fn all_xrc20_reserved() -> BTreeMap<Token, T::Balance> {
+        xassets::Module::<T>::assets()
+            .into_iter()
+            .filter(|token| Self::token_contract_of(token).is_some())
+            .map(|token| {
+                let reserved = Self::total_xrc20_reserved(&token);
+                (token, reserved)
+            })
+            .collect()
+    }
+
+    /// In mainnet, only the council account could put code.
+    fn check_put_code_permission(origin: &T::AccountId) -> Result {
+        let (network, _) = xsystem::Module::<T>::network_props();
+        match network {
+            xsystem::NetworkType::Mainnet => {
+                let council = xaccounts::Module::<T>::council_account();
+                ensure_with_errorlog!(
+                    *origin == council,
+                    "[put_code]|in mainnet, only council account could do `put_code`.",
+                    "[put_code]|in mainnet, only council account could do `put_code`|current:{:?}|council:{:?}",
+                    origin, council
+                );
+                info!("[put_code]|mainnet put_code, from account:{:?}", origin);
+            }
+            xsystem::NetworkType::Testnet => Self::check_put_code_rate(origin)?,
+        }
+        Ok(())
+    }
+
+    /// In testnet, an account could only `put_code` limited times within a block window.
+    fn check_put_code_rate(origin: &T::AccountId) -> Result {
+        let (max_count, window) = Self::put_code_rate_limit();
+        if max_count == 0 || window.is_zero() {
+            return Ok(());
+        }
+        let start = Self::put_code_window_start(window);
+        let count = if Self::put_code_window().0 == start {
+            Self::put_code_count_of(origin)
+        } else {
+            0
+        };
+        ensure_with_errorlog!(
+            count < max_count,
+            "[put_code]|too many put_code from this account in the current window.",
+            "[put_code]|too many put_code from this account in the current window|who:{:?}|count:{:}|window start:{:?}",
+            origin, count, start
+        );
+        Ok(())
+    }
+
+    /// Count the successful `put_code` in testnet, the counts of the former window are removed
+    /// once a new window begins.
+    fn note_put_code(origin: &T::AccountId) {
+        let (network, _) = xsystem::Module::<T>::network_props();
+        let (max_count, window) = Self::put_code_rate_limit();
+        if network != xsystem::NetworkType::Testnet || max_count == 0 || window.is_zero() {
+            return;
+        }
+        let start = Self::put_code_window_start(window);
+        let (last_start, mut accounts) = Self::put_code_window();
+        if last_start != start {
+            for who in accounts.drain(..) {
+                <PutCodeCountOf<T>>::remove(&who);
+            }
+        }
+        let count = Self::put_code_count_of(origin);
+        if count == 0 {
+            accounts.push(origin.clone());
+        }
+        <PutCodeCountOf<T>>::insert(origin, count + 1);
+        <PutCodeWindow<T>>::put((start, accounts));
+    }
+
+    /// The windows are aligned to the multiples of `window` blocks.
+    fn put_code_window_start(window: T::BlockNumber) -> T::BlockNumber {
+        let current = <system::Module<T>>::block_number();
+        current - current % window
+    }
+
+    /// Count the call to the contract in the current block, rejected if the call limit is reached.
+    fn check_call_limit(dest: &T::AccountId) -> Result {
+        let limit = match Self::call_limit_per_block(dest) {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        let count = Self::call_count_of(dest);
+        ensure_with_errorlog!(
+            count < limit,
+            "too many calls to this contract in the current block",
+            "contract:{:?}|limit:{:}",
+            dest,
+            limit
+        );
+        if count == 0 {
+            <CalledContracts<T>>::mutate(|contracts| contracts.push(dest.clone()));
+        }
+        <CallCountOf<T>>::insert(dest, count + 1);
+        Ok(())
+    }
+
+    /// In mainnet, only the approved code hashes could be instantiated if any is approved.
+    pub(crate) fn check_approved_code_hash(code_hash: &CodeHash<T>) -> Result {
+        let (network, _) = xsystem::Module::<T>::network_props();
+        if network == xsystem::NetworkType::Mainnet {
+            let approved = Self::approved_code_hashes();
+            ensure_with_errorlog!(
+                approved.is_empty() || approved.contains(code_hash),
+                "[instantiate]|in mainnet, only the approved code hash could be instantiated.",
+                "[instantiate]|code_hash:{:?}",
+                code_hash
+            );
+        }
+        Ok(())
+    }
+
+    /// Move the gas price a fraction toward the baseline if the last block was under-full.
+    fn decay_gas_price() {
+        if Self::last_gas_spent() >= T::BlockGasLimit::get() / 2 {
+            return;
+        }
+        let baseline = Self::baseline_gas_price();
+        let price = Self::gas_price();
+        if price <= baseline {
+            return;
+        }
+        let step = rstd::cmp::max(
+            (price - baseline) / GAS_PRICE_DECAY_DIVISOR.into(),
+            One::one(),
+        );
+        debug!(
+            "[decay_gas_price]|price:{:}|baseline:{:}|step:{:}",
+            price, baseline, step
+        );
+        Self::note_gas_price_change(price);
+        GasPrice::<T>::put(price - step);
+    }
+
+    /// Record the price before the change as the one of the prior block,
+    /// and drop the records out of the window.
+    fn note_gas_price_change(old: T::Balance) {
+        let current = <system::Module<T>>::block_number();
+        let prior = if current.is_zero() {
+            current
+        } else {
+            current - One::one()
+        };
+        let window = Self::gas_price_history_window();
+        let mut head = Self::gas_price_history_head();
+        let tail = Self::gas_price_history_tail();
+
+        // only the price before the first change in this block is the one of the prior block.
+        let last = if tail > head {
+            Self::gas_price_history(tail - 1).map(|(block, _)| block)
+        } else {
+            None
+        };
+        if last != Some(prior) {
+            GasPriceHistory::<T>::insert(tail, (prior, old));
+            GasPriceHistoryTail::<T>::put(tail + 1);
+        }
+
+        // the records are in ascending order of the block, drop them from the head.
+        while let Some((block, _)) = Self::gas_price_history(head) {
+            if block + window >= current {
+                break;
+            }
+            GasPriceHistory::<T>::remove(head);
+            head += 1;
+        }
+        GasPriceHistoryHead::<T>::put(head);
+    }
+
+    /// The gas price at the given block, `None` if the block is out of the history window.
+    pub fn gas_price_at(block: T::BlockNumber) -> Option<T::Balance> {
+        let current = <system::Module<T>>::block_number();
+        if block > current || block + Self::gas_price_history_window() < current {
+            return None;
+        }
+        let price = (Self::gas_price_history_head()..Self::gas_price_history_tail())
+            .filter_map(Self::gas_price_history)
+            .find(|(until, _)| *until >= block)
+            .map(|(_, price)| price)
+            .unwrap_or_else(Self::gas_price);
+        Some(price)
+    }
+
     fn execute_wasm(
         origin: T::AccountId,
         buy_gas_account: Option<T::AccountId>,
         gas_limit: Gas,
         func: impl FnOnce(&mut ExecutionContext<T, WasmVm, WasmLoader>, &mut GasMeter<T>) -> ExecResult,
+    ) -> ExecResult {
+        Self::execute_wasm_with_config(
+            origin,
+            buy_gas_account,
+            None,
+            None,
+            None,
+            gas_limit,
+            Config::preload(),
+            func,
+        )
+    }
+
+    /// Execute the wasm with the gas bought by `buy_gas_account` in PCX,
+    /// or in `gas_token` if given.
+    ///
+    /// The unused gas bought in PCX is refunded to `refund_to` if given,
+    /// and the consumed gas is accumulated to `callee` if given.
+    fn execute_wasm_with_config(
+        origin: T::AccountId,
+        buy_gas_account: Option<T::AccountId>,
+        gas_token: Option<Token>,
+        refund_to: Option<T::AccountId>,
+        callee: Option<T::AccountId>,
+        gas_limit: Gas,
+        cfg: Config<T>,
+        func: impl FnOnce(&mut ExecutionContext<T, WasmVm, WasmLoader>, &mut GasMeter<T>) -> ExecResult,
     ) -> ExecResult {
         // Pay for the gas upfront.
         //
         // NOTE: it is very important to avoid any state changes before
         // paying for the gas.
         let pay_gas = buy_gas_account.unwrap_or(origin.clone());
+        // a pre-funded contract pays the gas from its allowance, and from the free balance
+        // once the allowance runs out.
+        let from_allowance = gas_token.is_none() && gas::allowance_covers::<T>(&pay_gas, gas_limit);
         let mut gas_meter = try_or_exec_error!(
-            gas::buy_gas::<T>(&pay_gas, gas_limit),
+            match gas_token {
+                Some(ref token) => gas::buy_gas_with_token::<T>(&pay_gas, token, gas_limit),
+                None if from_allowance => gas::buy_gas_from_allowance::<T>(&pay_gas, gas_limit),
+                None => gas::buy_gas::<T>(&pay_gas, gas_limit),
+            },
             // We don't have a spare buffer here in the first place, so create a new empty one.
             Vec::new()
         );
+        Self::deposit_event(RawEvent::GasPriceApplied(
+            pay_gas.clone(),
+            gas_meter.gas_price(),
+        ));
 
-        let cfg = Config::preload();
         let vm = WasmVm::new(&cfg.schedule);
         let loader = WasmLoader::new(&cfg.schedule);
         let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
@@ -920,11 +2281,27 @@ impl<T: Trait> Module<T> {
             DirectAccountDb.commit(ctx.overlay.into_change_set());
         }
 
+        if let Some(ref callee) = callee {
+            let consumed = gas_limit.saturating_sub(gas_meter.gas_left());
+            <ContractGasConsumed<T>>::mutate(callee, |total| {
+                *total = total.saturating_add(consumed)
+            });
+        }
+
         // Refund cost of the unused gas.
         //
         // NOTE: This should go after the commit to the storage, since the storage changes
         // can alter the balance of the caller.
-        gas::refund_unused_gas::<T>(&pay_gas, gas_meter);
+        match gas_token {
+            Some(ref token) => gas::refund_unused_gas_with_token::<T>(&pay_gas, token, gas_meter),
+            None if from_allowance => gas::refund_unused_gas_to_allowance::<T>(&pay_gas, gas_meter),
+            None => match refund_to {
+                Some(ref beneficiary) => {
+                    gas::refund_unused_gas_to_beneficiary::<T>(&pay_gas, beneficiary, gas_meter)
+                }
+                None => gas::refund_unused_gas::<T>(&pay_gas, gas_meter),
+            },
+        }
 
         // Execute deferred actions.
         ctx.deferred.into_iter().for_each(|deferred| {
@@ -941,12 +2318,26 @@ impl<T: Trait> Module<T> {
                     );
                 }
                 DispatchRuntimeCall { origin: who, call } => {
+                    // only the call index is recorded, the params may carry sensitive payloads
+                    let call_index = Self::call_index_of(&call);
                     debug!(
-                        "[deferred_dispatch_runtime_call]origin:{:?}|call:{:?}",
-                        who, call
+                        "[deferred_dispatch_runtime_call]origin:{:?}|call_index:{:?}",
+                        who, call_index
                     );
                     let result = call.dispatch(RawOrigin::Signed(who.clone()).into());
-                    Self::deposit_event(RawEvent::Dispatched(who, result.is_ok()));
+                    if let Err(e) = result {
+                        warn!(
+                            "[deferred_dispatch_runtime_call]dispatch failed|origin:{:?}|call_index:{:?}|err:{:}",
+                            who, call_index, e
+                        );
+                    }
+                    Self::deposit_event(RawEvent::Dispatched(who.clone(), result.is_ok()));
+                    Self::deposit_event(RawEvent::DispatchedDetailed(
+                        who,
+                        call_index,
+                        result.is_ok(),
+                        result.err().map(|e| e.as_bytes().to_vec()),
+                    ));
                 }
                 RestoreTo {
                     donor,
@@ -957,12 +2348,20 @@ impl<T: Trait> Module<T> {
                 } => {
                     let _result = Self::restore_to(donor, dest, code_hash, rent_allowance, delta);
                 }
+                SetOwner { contract, owner } => {
+                    <ContractOwnerOf<T>>::insert(contract, owner);
+                }
             }
         });
 
         result
     }
 
+    /// Return the `[module_index, call_index]` of the encoded call, without any params.
+    fn call_index_of(call: &<T as Trait>::Call) -> Vec<u8> {
+        call.using_encoded(|encoded| encoded.iter().take(2).cloned().collect())
+    }
+
     fn restore_to(
         origin: T::AccountId,
         dest: T::AccountId,
@@ -970,6 +2369,10 @@ impl<T: Trait> Module<T> {
         rent_allowance: T::Balance,
         delta: Vec<exec::StorageKey>,
     ) -> Result {
+        if delta.len() > T::MaxRestoreDelta::get() as usize {
+            return Err("Too many storage keys in the restoration delta");
+        }
+
         let mut origin_contract = <ContractInfoOf<T>>::get(&origin)
             .and_then(|c| c.get_alive())
             .ok_or("Cannot restore from inexisting or tombstone contract")?;
@@ -1021,6 +2424,7 @@ impl<T: Trait> Module<T> {
             .sum::<u32>();
 
         <ContractInfoOf<T>>::remove(&origin);
+        Self::remove_from_tombstone_index(&dest);
         <ContractInfoOf<T>>::insert(
             &dest,
             ContractInfo::Alive(RawAliveContractInfo {
@@ -1040,6 +2444,187 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    fn migrate_trie_of(
+        contract: T::AccountId,
+        keys: Vec<exec::StorageKey>,
+        gas_limit: Gas,
+    ) -> Result {
+        if keys.len() > T::MaxRestoreDelta::get() as usize {
+            return Err("Too many storage keys in a page of the migration");
+        }
+
+        let mut contract_info = <ContractInfoOf<T>>::get(&contract)
+            .and_then(|c| c.get_alive())
+            .ok_or("Cannot migrate the trie of inexisting or tombstone contract")?;
+
+        let current_block = <system::Module<T>>::block_number();
+        if contract_info.last_write == Some(current_block) {
+            return Err("Contract TrieId written in the current block");
+        }
+
+        let key_values = keys
+            .iter()
+            .filter_map(|key| {
+                child::get_raw(&contract_info.trie_id, &blake2_256(key)).map(|value| (key, value))
+            })
+            .collect::<Vec<_>>();
+
+        let schedule = Self::current_schedule();
+        let gas_needed = key_values.iter().fold(0 as Gas, |acc, (_, value)| {
+            acc.saturating_add(schedule.sandbox_data_read_cost)
+                .saturating_add(
+                    schedule
+                        .sandbox_data_write_cost
+                        .saturating_mul(value.len() as Gas),
+                )
+        });
+        ensure_with_errorlog!(
+            gas_needed <= gas_limit,
+            "Not enough gas to migrate the contract trie",
+            "contract:{:?}|gas_needed:{:}|gas_limit:{:}",
+            contract,
+            gas_needed,
+            gas_limit
+        );
+
+        let (new_trie_id, copied) = Self::pending_trie_migration(&contract)
+            .unwrap_or_else(|| (T::TrieIdGenerator::trie_id(&contract), 0));
+        for (key, value) in key_values.iter() {
+            child::put_raw(&new_trie_id, &blake2_256(&key[..]), value);
+        }
+        let copied = copied + key_values.len() as u32;
+
+        // This operation is cheap enough because last_write is not this block as it has been
+        // checked earlier.
+        if runtime_io::child_storage_root(&contract_info.trie_id)
+            != runtime_io::child_storage_root(&new_trie_id)
+        {
+            debug!(
+                "[migrate_contract_trie]|wait for more pages|contract:{:?}|copied:{:}",
+                contract, copied
+            );
+            <PendingTrieMigration<T>>::insert(&contract, (new_trie_id, copied));
+            return Ok(());
+        }
+
+        <PendingTrieMigration<T>>::remove(&contract);
+        child::kill_storage(&contract_info.trie_id);
+        contract_info.trie_id = new_trie_id;
+        contract_info.last_write = Some(current_block);
+        <ContractInfoOf<T>>::insert(&contract, ContractInfo::Alive(contract_info));
+
+        info!(
+            "[migrate_contract_trie]|contract:{:?}|keys:{:}|gas_needed:{:}",
+            contract, copied, gas_needed
+        );
+        Self::deposit_event(RawEvent::ContractTrieMigrated(contract, copied));
+        Ok(())
+    }
+
+    fn import_contract_impl(address: T::AccountId, snapshot: ContractSnapshotOf<T>) -> Result {
+        if snapshot.storage.len() > T::MaxRestoreDelta::get() as usize {
+            return Err("Too many storage keys in the snapshot");
+        }
+        if <ContractInfoOf<T>>::exists(&address) {
+            return Err("Cannot import the snapshot to an existing contract");
+        }
+        if !<CodeStorage<T>>::exists(&snapshot.code_hash) {
+            return Err("The code of the snapshot is not stored");
+        }
+
+        let mut header = snapshot;
+        let storage = rstd::mem::replace(&mut header.storage, Vec::new());
+        let trie_id = match Self::pending_contract_import(&address) {
+            Some((trie_id, pending)) => {
+                if pending != header {
+                    return Err("The snapshot doesn't match the pending import");
+                }
+                trie_id
+            }
+            None => T::TrieIdGenerator::trie_id(&address),
+        };
+
+        for (key, value) in storage.iter() {
+            child::put_raw(&trie_id, &blake2_256(key), value);
+        }
+        if runtime_io::child_storage_root(&trie_id) != header.storage_root {
+            debug!(
+                "[import_contract]|wait for more pages|contract:{:?}|keys:{:}",
+                address,
+                storage.len()
+            );
+            <PendingContractImport<T>>::insert(&address, (trie_id, header));
+            return Ok(());
+        }
+
+        <PendingContractImport<T>>::remove(&address);
+        <ContractInfoOf<T>>::insert(
+            &address,
+            ContractInfo::Alive(RawAliveContractInfo {
+                trie_id,
+                storage_size: header.storage_size,
+                code_hash: header.code_hash,
+                rent_allowance: header.rent_allowance,
+                deduct_block: header.deduct_block,
+                last_write: Some(<system::Module<T>>::block_number()),
+            }),
+        );
+        info!(
+            "[import_contract]|contract:{:?}|code_hash:{:?}",
+            address, header.code_hash
+        );
+        Ok(())
+    }
+
+    fn add_to_tombstone_index(who: &T::AccountId) {
+        TombstoneIndex::<T>::mutate(|index| {
+            if !index.contains(who) {
+                index.push(who.clone());
+            }
+        });
+    }
+
+    fn remove_from_tombstone_index(who: &T::AccountId) {
+        TombstoneIndex::<T>::mutate(|index| index.retain(|v| v != who));
+    }
+
+    fn set_gas_allowance(contract: &T::AccountId, allowance: T::Balance) {
+        if allowance.is_zero() {
+            <ContractGasAllowance<T>>::remove(contract);
+        } else {
+            <ContractGasAllowance<T>>::insert(contract, allowance);
+        }
+    }
+
+    /// Release the remaining gas allowance of the contract to its owner, or to the council if
+    /// the owner is unknown, when the contract is reaped or evicted.
+    fn release_gas_allowance(contract: &T::AccountId) {
+        let allowance = <ContractGasAllowance<T>>::take(contract);
+        if allowance.is_zero() {
+            return;
+        }
+        let to = Self::contract_owner_of(contract)
+            .unwrap_or_else(xaccounts::Module::<T>::council_account);
+        if let Err(e) = xassets::Module::<T>::pcx_move_balance(
+            contract,
+            AssetType::ReservedCurrency,
+            &to,
+            AssetType::Free,
+            allowance,
+        ) {
+            error!(
+                "[release_gas_allowance]|fail to release the gas allowance|contract:{:?}|to:{:?}|allowance:{:}|err:{:}",
+                contract, to, allowance, e.info()
+            );
+            return;
+        }
+        Self::deposit_event(RawEvent::ContractGasReleased(
+            contract.clone(),
+            to,
+            allowance,
+        ));
+    }
+
     fn transfer_to_council(slashed_account: &T::AccountId, value: T::Balance) {
         let council = xaccounts::Module::<T>::council_account();
         let _ = <xassets::Module<T>>::pcx_move_free_balance(&slashed_account, &council, value);
@@ -1069,8 +2654,54 @@ decl_event! {
         /// successful execution or not.
         Dispatched(AccountId, bool),
 
+        /// Detailed result of a call dispatched from the given account: the
+        /// `[module_index, call_index]` of the call, whether it was successful and the
+        /// error on failure.
+        DispatchedDetailed(AccountId, Vec<u8>, bool, Option<Vec<u8>>),
+
         /// An event deposited upon execution of a contract from the account.
         ContractExecution(AccountId, Vec<u8>),
+
+        /// The xrc20 binding of a token is migrated from the old address to the new one,
+        /// along with the reserved balance.
+        Xrc20Migrated(Token, AccountId, AccountId, Balance),
+
+        /// The storage of the contract is migrated to a new trie, along with the number of keys
+        /// copied.
+        ContractTrieMigrated(AccountId, u32),
+
+        /// The caller tried to call a contract which has been evicted and left a tombstone.
+        CallToTombstone(AccountId, AccountId),
+
+        /// The beneficiary authorized the sponsor to pay the gas of its calls to the contract,
+        /// with the max gas of each call, or revoked it (None).
+        SponsorSet(AccountId, AccountId, AccountId, Option<Gas>),
+
+        /// An alive contract is removed since its free balance is zero, along with the size of
+        /// the storage freed.
+        ContractReaped(AccountId, u32),
+
+        /// The funder reserved the gas allowance for the contract.
+        ContractGasFunded(AccountId, AccountId, Balance),
+
+        /// The gas allowance of the contract is withdrawn by the owner, or released since the
+        /// contract is reaped or evicted.
+        ContractGasReleased(AccountId, AccountId, Balance),
+
+        /// The gas price applied to an execution, along with the account paying the gas.
+        GasPriceApplied(AccountId, Balance),
+
+        /// The result of the xrc20 call could not be decoded, along with the raw output,
+        /// which usually means the xrc20 contract does not match the expected abi.
+        Xrc20ResultDecodeFailed(Token, XRC20Selector, Vec<u8>),
+
+        /// The result of the xrc777 call could not be decoded, along with the raw output,
+        /// which usually means the xrc777 contract does not match the expected abi.
+        Xrc777ResultDecodeFailed(Token, XRC777Selector, Vec<u8>),
+
+        /// Contract has been evicted by a surcharge claim, the bool signals whether it left a
+        /// tombstone (true) or was removed altogether (false).
+        Evicted(AccountId, bool),
     }
 }
 
@@ -1078,8 +2709,18 @@ decl_storage! {
     trait Store for Module<T: Trait> as XContracts {
         /// Gas spent so far in this block.
         GasSpent get(gas_spent): Gas;
+        /// Gas spent in the last block.
+        pub LastGasSpent get(last_gas_spent): Gas;
+        /// The ratio in parts per million of the spent gas fee paid to the council rather than destroyed, all by default.
+        pub GasFeeTreasuryRatio get(gas_fee_treasury_ratio): u32 = 1_000_000;
+        /// The ratio in parts per million of the surcharge reward paid to the claimer, all by default.
+        pub SurchargeRewardRatio get(surcharge_reward_ratio): u32 = 1_000_000;
+        /// The cumulative gas consumed by the calls to each contract.
+        pub ContractGasConsumed get(contract_gas_consumed): map T::AccountId => Gas;
         /// Current cost schedule for contracts.
         CurrentSchedule get(current_schedule) config(): Schedule = Schedule::default();
+        /// The floors of the critical limits of the schedule, protecting the deployed contracts.
+        pub ScheduleFloors get(schedule_limit_floors): ScheduleLimitFloors;
         /// A mapping from an original code hash to the original code, untouched by instrumentation.
         pub PristineCode: map CodeHash<T> => Option<Vec<u8>>;
         /// A mapping between an original code hash and instrumented wasm code, ready for execution.
@@ -1090,6 +2731,20 @@ decl_storage! {
         pub ContractInfoOf: map T::AccountId => Option<ContractInfo<T>>;
         /// The price of one unit of gas.
         pub GasPrice get(gas_price) config(): T::Balance = 5.into();
+        /// The (floor, ceiling) of the gas price could be set.
+        pub GasPriceBounds get(gas_price_bounds): (T::Balance, T::Balance) = (1.into(), 1_000_000.into());
+        /// Whether the gas price is dynamic, i.e., decays toward `BaselineGasPrice`.
+        pub DynamicGasPrice get(dynamic_gas_price): bool;
+        /// The gas price the dynamic gas price decays toward.
+        pub BaselineGasPrice get(baseline_gas_price): T::Balance;
+        /// The gas price effective until each block, indexed in ascending order of the block.
+        pub GasPriceHistory get(gas_price_history): map u32 => Option<(T::BlockNumber, T::Balance)>;
+        /// The index of the oldest record in `GasPriceHistory`.
+        pub GasPriceHistoryHead get(gas_price_history_head): u32;
+        /// The index of the next record in `GasPriceHistory`.
+        pub GasPriceHistoryTail get(gas_price_history_tail): u32;
+        /// How many recent blocks the gas price history covers.
+        pub GasPriceHistoryWindow get(gas_price_history_window): T::BlockNumber = T::BlockNumber::saturated_from::<u64>(DEFAULT_GAS_PRICE_HISTORY_WINDOW);
 
         // ChainX modify
         // the map of token and token contract instance
@@ -1102,14 +2757,65 @@ decl_storage! {
         // xrc20
         /// The XRC20 contract of a token name.
         pub XRC20InfoOfToken get(xrc20_of_token): map Token => Option<(T::AccountId, BTreeMap<XRC20Selector, Selector>)>;
-        // XRC777 (in future)
+        /// The max nesting depth when calling the xrc20 instance, which is no more than `MaxDepth`.
+        pub XRC20MaxDepth get(xrc20_max_depth): u32 = DEFAULT_XRC20_MAX_DEPTH;
+        // xrc777
+        /// The XRC777 contract of a token name, a token is bound to either an XRC20 or an XRC777.
+        pub XRC777InfoOfToken get(xrc777_of_token): map Token => Option<(T::AccountId, BTreeMap<XRC777Selector, Selector>)>;
+        /// Accounts of the contracts which are tombstones now, could be restored.
+        pub TombstoneIndex get(tombstone_index): Vec<T::AccountId>;
+        /// The new trie of the contract being filled by the pages of `migrate_contract_trie`,
+        /// with the number of the keys copied so far.
+        pub PendingTrieMigration get(pending_trie_migration): map T::AccountId => Option<(TrieId, u32)>;
+        /// The trie of the address being filled by the pages of `import_contract`, with the
+        /// snapshot of the import whose storage is left empty.
+        pub PendingContractImport get(pending_contract_import): map T::AccountId => Option<(TrieId, ContractSnapshotOf<T>)>;
+        /// The max gas of each call to the contract the sponsor is authorized to pay by the
+        /// beneficiary, keyed by (beneficiary, sponsor, contract).
+        pub SponsorAllowance get(sponsor_allowance): map (T::AccountId, T::AccountId, T::AccountId) => Option<Gas>;
+        /// Where the unused gas of the calls sponsored by the account is refunded to.
+        pub GasRefundPolicyOf get(gas_refund_policy_of): map T::AccountId => GasRefundPolicy;
+        /// The tokens other than PCX accepted for paying the gas.
+        pub GasPaymentTokens get(gas_payment_tokens): Vec<Token>;
+        /// The code hashes allowed to be instantiated in mainnet, no restriction if empty.
+        pub ApprovedCodeHashes get(approved_code_hashes): Vec<CodeHash<T>>;
+        /// The PCX reserved for paying the gas of the calls made by the contract itself.
+        pub ContractGasAllowance get(contract_gas_allowance): map T::AccountId => T::Balance;
+        /// The number of instantiations made by the account, including the nested ones and the
+        /// failed ones whose address has been derived.
+        pub InstantiationNonce get(instantiation_nonce): map T::AccountId => u64;
+        /// The max times of `put_code` per account within the block window in testnet, (count, window).
+        pub PutCodeRateLimit get(put_code_rate_limit): (u32, T::BlockNumber);
+        /// The start of the current window and the accounts which have put code within it.
+        pub PutCodeWindow get(put_code_window): (T::BlockNumber, Vec<T::AccountId>);
+        /// The times of the successful `put_code` of each account within the current window.
+        pub PutCodeCountOf get(put_code_count_of): map T::AccountId => u32;
+        /// The registered event layouts of the contract, keyed by (contract, selector of the event).
+        pub ContractEventLayouts get(contract_event_layout): map (T::AccountId, Selector) => Option<ContractEventLayout>;
+        /// The account which instantiated the contract.
+        pub ContractOwnerOf get(contract_owner_of): map T::AccountId => Option<T::AccountId>;
+        /// The max number of calls to the contract per block, set by the contract owner.
+        pub CallLimitPerBlock get(call_limit_per_block): map T::AccountId => Option<u32>;
+        /// The number of calls to the contract within the current block, cleared on finalize.
+        pub CallCountOf get(call_count_of): map T::AccountId => u32;
+        /// The contracts with a call limit called within the current block, cleared on finalize.
+        pub CalledContracts get(called_contracts): Vec<T::AccountId>;
     }
 }
 
 impl<T: Trait> OnFreeBalanceZero<T::AccountId> for Module<T> {
     fn on_free_balance_zero(who: &T::AccountId) {
-        if let Some(ContractInfo::Alive(info)) = <ContractInfoOf<T>>::take(who) {
-            child::kill_storage(&info.trie_id);
+        match <ContractInfoOf<T>>::take(who) {
+            Some(ContractInfo::Alive(info)) => {
+                child::kill_storage(&info.trie_id);
+                Self::release_gas_allowance(who);
+                Self::deposit_event(RawEvent::ContractReaped(who.clone(), info.storage_size));
+            }
+            Some(ContractInfo::Tombstone(_)) => {
+                Self::release_gas_allowance(who);
+                Self::remove_from_tombstone_index(who);
+            }
+            None => {}
         }
     }
 }
@@ -1123,6 +2829,7 @@ pub struct Config<T: Trait> {
     pub existential_deposit: T::Balance,
     pub max_depth: u32,
     pub max_value_size: u32,
+    pub max_write_bytes_per_call: u32,
     pub contract_account_instantiate_fee: T::Balance,
     pub account_create_fee: T::Balance,
     pub transfer_fee: T::Balance,
@@ -1147,11 +2854,20 @@ impl<T: Trait> Config<T> {
             existential_deposit,
             max_depth: T::MaxDepth::get(),
             max_value_size: T::MaxValueSize::get(),
+            max_write_bytes_per_call: T::MaxWriteBytesPerCall::get(),
             contract_account_instantiate_fee: T::Balance::zero(),
             account_create_fee: T::Balance::zero(),
             transfer_fee: T::Balance::zero(),
         }
     }
+
+    /// The config for calling the xrc20 instance, of which the max depth is capped by
+    /// `XRC20MaxDepth`.
+    fn preload_for_xrc20() -> Config<T> {
+        let mut cfg = Self::preload();
+        cfg.max_depth = cfg.max_depth.min(<Module<T>>::xrc20_max_depth());
+        cfg
+    }
 }
 
 /// Definition of the cost schedule and other parameterizations for wasm vm.
@@ -1217,6 +2933,44 @@ pub struct Schedule {
     pub max_subject_len: u32,
 }
 
+/// The floors of the schedule limits, lowering them further could break the deployed contracts.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq)]
+pub struct ScheduleLimitFloors {
+    /// The minimum of `max_stack_height`.
+    pub max_stack_height: u32,
+    /// The minimum of `max_memory_pages`.
+    pub max_memory_pages: u32,
+    /// The minimum of `max_table_size`.
+    pub max_table_size: u32,
+}
+
+impl Default for ScheduleLimitFloors {
+    fn default() -> Self {
+        ScheduleLimitFloors {
+            max_stack_height: 64 * 1024,
+            max_memory_pages: 16,
+            max_table_size: 16 * 1024,
+        }
+    }
+}
+
+impl ScheduleLimitFloors {
+    /// Check that the limits of the schedule are not below the floors.
+    pub fn check(&self, schedule: &Schedule) -> Result {
+        if schedule.max_stack_height < self.max_stack_height {
+            return Err("max_stack_height of the new schedule is below the floor");
+        }
+        if schedule.max_memory_pages < self.max_memory_pages {
+            return Err("max_memory_pages of the new schedule is below the floor");
+        }
+        if schedule.max_table_size < self.max_table_size {
+            return Err("max_table_size of the new schedule is below the floor");
+        }
+        Ok(())
+    }
+}
+
 impl Default for Schedule {
     fn default() -> Schedule {
         if cfg!(test) {
@@ -137,6 +137,7 @@ fn try_evict_or_and_pay_rent<T: Trait>(
             <TombstoneContractInfo<T>>::new(&child_storage_root[..], contract.code_hash);
         let tombstone_info = ContractInfo::Tombstone(tombstone);
         <ContractInfoOf<T>>::insert(account, &tombstone_info);
+        Module::<T>::add_to_tombstone_index(account);
 
         runtime_io::kill_child_storage(&contract.trie_id);
 
@@ -173,7 +174,23 @@ pub fn pay_rent<T: Trait>(account: &T::AccountId) -> Option<ContractInfo<T>> {
 /// `handicap=1` can defer the eviction for 1 block.
 ///
 /// NOTE: This function acts eagerly.
-#[allow(unused)]
 pub fn try_evict<T: Trait>(account: &T::AccountId, handicap: T::BlockNumber) -> RentOutcome {
     try_evict_or_and_pay_rent::<T>(account, handicap, false).0
 }
+
+/// Pay the surcharge reward for evicting a contract out of the council, which collects the rent,
+/// so that the evicted contract keeps the subsistence threshold for its tombstone.
+/// `SurchargeRewardRatio` of it goes to the claimer, i.e. the block producer for an inherent
+/// claim or the user for a signed claim, and the rest is kept by the council. The reward is
+/// capped at the free balance of the council.
+pub fn pay_surcharge_reward<T: Trait>(rewarded: &T::AccountId, reward: T::Balance) {
+    let council = xaccounts::Module::<T>::council_account();
+    let ratio = u128::from(<Module<T>>::surcharge_reward_ratio());
+    let to_rewarded: T::Balance =
+        (reward.saturated_into::<u128>() * ratio / 1_000_000).saturated_into();
+    let to_rewarded = to_rewarded.min(xassets::Module::<T>::pcx_free_balance(&council));
+
+    if !to_rewarded.is_zero() {
+        let _ = xassets::Module::<T>::pcx_move_free_balance(&council, rewarded, to_rewarded);
+    }
+}
@@ -21,10 +21,12 @@
 
 use crate::account_db::{AccountDb, DirectAccountDb, OverlayAccountDb};
 use crate::{
+    AssetPowerFor,
     ComputeDispatchFee,
     ContractAddressFor,
     ContractInfo,
     ContractInfoOf,
+    GasSpent,
     GenesisConfig,
     Module,
     RawAliveContractInfo,
@@ -44,13 +46,14 @@ use runtime_io::with_externalities;
 use sr_primitives::{
     // weights::{DispatchInfo, DispatchClass},
     testing::{Digest, DigestItem, Header, UintAuthorityId, H256},
-    traits::{BlakeTwo256, Hash, IdentityLookup},
+    traits::{BlakeTwo256, Hash, IdentityLookup, OnFinalize, OnInitialize},
     BuildStorage,
     Perbill,
     StorageOverlay,
 };
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     sync::atomic::{AtomicUsize, Ordering},
 };
 use support::{
@@ -160,6 +163,7 @@ impl xassets::Trait for Test {
     /// Event
     type Balance = Balance;
     type OnNewAccount = ();
+    type OnFreeBalanceZero = Contracts;
     type Event = MetaEvent;
     type OnAssetChanged = ();
     type OnAssetRegisterOrRevoke = ();
@@ -230,6 +234,9 @@ parameter_types! {
     pub const InstantiateBaseFee: u64 = 175;
     pub const MaxDepth: u32 = 100;
     pub const MaxValueSize: u32 = 16_384;
+    pub const MaxWriteBytesPerCall: u32 = 65_536;
+    pub const MaxRestoreDelta: u32 = 4;
+    pub const MaxGasPerExtrinsic: u64 = 5_000;
 }
 impl Trait for Test {
     type Call = Call;
@@ -238,13 +245,18 @@ impl Trait for Test {
     type ComputeDispatchFee = DummyComputeDispatchFee;
     type TrieIdGenerator = DummyTrieIdGenerator;
     type SignedClaimHandicap = SignedClaimHandicap;
+    type SurchargeReward = SurchargeReward;
     type TombstoneDeposit = TombstoneDeposit;
     type StorageSizeOffset = StorageSizeOffset;
     type RentByteFee = RentByteFee;
     type RentDepositOffset = RentDepositOffset;
     type MaxDepth = MaxDepth;
     type MaxValueSize = MaxValueSize;
+    type MaxWriteBytesPerCall = MaxWriteBytesPerCall;
+    type MaxRestoreDelta = MaxRestoreDelta;
     type BlockGasLimit = BlockGasLimit;
+    type MaxGasPerExtrinsic = MaxGasPerExtrinsic;
+    type AssetPower = DummyAssetPower;
 }
 
 type XAssets = xassets::Module<Test>;
@@ -286,6 +298,18 @@ impl ComputeDispatchFee<Call, u64> for DummyComputeDispatchFee {
     }
 }
 
+pub struct DummyAssetPower;
+impl AssetPowerFor<u64> for DummyAssetPower {
+    fn asset_power(token: &xassets::Token) -> Option<u64> {
+        // 1 BTC = 2 PCX
+        if token.as_slice() == b"BTC" {
+            Some(2 * 100_000_000)
+        } else {
+            None
+        }
+    }
+}
+
 const ALICE: u64 = 1;
 const BOB: u64 = 2;
 const CHARLIE: u64 = 3;
@@ -601,6 +625,11 @@ fn instantiate_and_call_and_deposit_event() {
                         event: xassets(Move(pcx(), ALICE, Free, ALICE, GasPayment, 200000)),
                         topics: vec![],
                     },
+                    EventRecord {
+                        phase: Phase::ApplyExtrinsic(0),
+                        event: MetaEvent::contract(RawEvent::GasPriceApplied(ALICE, 2)),
+                        topics: vec![],
+                    },
                     EventRecord {
                         phase: Phase::ApplyExtrinsic(0),
                         event: xassets(Change(pcx(), ALICE, Free, SignedBalance::Negative(100))),
@@ -760,6 +789,11 @@ fn dispatch_call() {
                         event: xassets(Move(pcx(), ALICE, Free, ALICE, GasPayment, 200000)),
                         topics: vec![],
                     },
+                    EventRecord {
+                        phase: Phase::ApplyExtrinsic(0),
+                        event: MetaEvent::contract(RawEvent::GasPriceApplied(ALICE, 2)),
+                        topics: vec![],
+                    },
                     EventRecord {
                         phase: Phase::ApplyExtrinsic(0),
                         event: xassets(Change(pcx(), ALICE, Free, SignedBalance::Negative(100))),
@@ -800,6 +834,11 @@ fn dispatch_call() {
                         event: xassets(Move(pcx(), ALICE, Free, ALICE, GasPayment, 200000)),
                         topics: vec![],
                     },
+                    EventRecord {
+                        phase: Phase::ApplyExtrinsic(0),
+                        event: MetaEvent::contract(RawEvent::GasPriceApplied(ALICE, 2)),
+                        topics: vec![],
+                    },
                     EventRecord {
                         phase: Phase::ApplyExtrinsic(0),
                         event: xassets(Move(pcx(), ALICE, GasPayment, ALICE, Free, 199610)),
@@ -826,6 +865,16 @@ fn dispatch_call() {
                         phase: Phase::ApplyExtrinsic(0),
                         event: MetaEvent::contract(RawEvent::Dispatched(BOB, true)),
                         topics: vec![],
+                    },
+                    EventRecord {
+                        phase: Phase::ApplyExtrinsic(0),
+                        event: MetaEvent::contract(RawEvent::DispatchedDetailed(
+                            BOB,
+                            vec![0, 3],
+                            true,
+                            None
+                        )),
+                        topics: vec![],
                     }
                 ]
             );
@@ -833,6 +882,407 @@ fn dispatch_call() {
     );
 }
 
+const CODE_DISPATCH_CALL_INSUFFICIENT: &str = r#"
+(module
+    (import "env" "ext_dispatch_call" (func $ext_dispatch_call (param i32 i32)))
+    (import "env" "memory" (memory 1 1))
+
+    (func (export "call")
+        (call $ext_dispatch_call
+            (i32.const 8) ;; Pointer to the start of encoded call buffer
+            (i32.const 23) ;; Length of the buffer
+        )
+    )
+    (func (export "deploy"))
+
+    ;; transfer 1_000_000 PCX to CHARLIE, which is more than the contract owns
+    (data (i32.const 8) "\00\03\03\00\00\00\00\00\00\00\0C\50\43\58\40\42\0F\00\00\00\00\00\00")
+)
+"#;
+
+#[test]
+fn dispatch_call_failure_emits_detailed_event() {
+    let encoded = Encode::encode(&Call::XAssets(::xassets::Call::transfer(
+        CHARLIE,
+        pcx(),
+        1_000_000,
+        vec![],
+    )));
+    assert_eq!(
+        &encoded[..],
+        &hex!("000303000000000000000c50435840420f000000000000")[..]
+    );
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL_INSUFFICIENT).unwrap();
+
+    with_externalities(
+        &mut ExtBuilder::default().existential_deposit(50).build(),
+        || {
+            XAssets::pcx_issue(&ALICE, 1_000_000);
+            XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+            assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+            assert_ok!(Contracts::instantiate(
+                Origin::signed(ALICE),
+                100,
+                100_000,
+                code_hash.into(),
+                vec![],
+            ));
+
+            System::initialize(&5, &[0u8; 32].into(), &[0u8; 32].into());
+
+            // the contract itself runs well, only the dispatched call fails.
+            assert_ok!(Contracts::call(
+                Origin::signed(ALICE),
+                BOB, // newly created account
+                0,
+                100_000,
+                vec![],
+            ));
+
+            let events = System::events();
+            let len = events.len();
+            assert_eq!(
+                &events[len - 2..],
+                &[
+                    EventRecord {
+                        phase: Phase::ApplyExtrinsic(0),
+                        event: MetaEvent::contract(RawEvent::Dispatched(BOB, false)),
+                        topics: vec![],
+                    },
+                    EventRecord {
+                        phase: Phase::ApplyExtrinsic(0),
+                        event: MetaEvent::contract(RawEvent::DispatchedDetailed(
+                            BOB,
+                            vec![0, 3],
+                            false,
+                            Some(b"balance too low for this account".to_vec())
+                        )),
+                        topics: vec![],
+                    },
+                ]
+            );
+            assert_eq!(XAssets::pcx_free_balance(&BOB), 100);
+        },
+    );
+}
+
+#[test]
+fn put_code_rejects_gas_limit_below_floor() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+    // `put_code_per_byte_cost` is 1 in tests
+    let gas_floor = wasm.len() as u64;
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_err!(
+            Contracts::put_code(Origin::signed(ALICE), gas_floor - 1, wasm.clone()),
+            "[put_code]|gas_limit is too low to store the code"
+        );
+        // no gas is bought
+        assert_eq!(XAssets::pcx_free_balance(&ALICE), 1_000_000);
+        assert!(<super::PristineCode<Test>>::get(code_hash).is_none());
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert!(<super::PristineCode<Test>>::get(code_hash).is_some());
+    });
+}
+
+#[test]
+fn migrate_token_xrc20_moves_reserved_balance() {
+    use crate::XRC20Selector;
+    use AssetType::*;
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        // the old xrc20 instance is BOB and the new one is DJANGO
+        for origin in [ALICE, CHARLIE].iter() {
+            assert_ok!(Contracts::instantiate(
+                Origin::signed(*origin),
+                100,
+                100_000,
+                code_hash.into(),
+                vec![],
+            ));
+        }
+
+        let old_selectors = vec![(XRC20Selector::Issue, [1, 2, 3, 4])]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let new_selectors = vec![(XRC20Selector::Issue, [5, 6, 7, 8])]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+
+        assert_err!(
+            Contracts::migrate_token_xrc20(pcx(), DJANGO, new_selectors.clone()),
+            "no xrc20 binding for this token"
+        );
+
+        Contracts::set_token_xrc20(pcx(), BOB, old_selectors);
+        assert_ok!(XAssets::move_balance(
+            &pcx(),
+            &ALICE,
+            Free,
+            &BOB,
+            ReservedXRC20,
+            500
+        ));
+
+        assert_err!(
+            Contracts::migrate_token_xrc20(pcx(), BOB, new_selectors.clone()),
+            "new xrc20 address should not be the current one"
+        );
+        assert_err!(
+            Contracts::migrate_token_xrc20(pcx(), CHARLIE, new_selectors.clone()),
+            "new xrc20 address is not an alive contract"
+        );
+
+        assert_ok!(Contracts::migrate_token_xrc20(
+            pcx(),
+            DJANGO,
+            new_selectors.clone()
+        ));
+
+        assert_eq!(XAssets::asset_balance_of(&BOB, &pcx(), ReservedXRC20), 0);
+        assert_eq!(
+            XAssets::asset_balance_of(&DJANGO, &pcx(), ReservedXRC20),
+            500
+        );
+        assert_eq!(
+            Contracts::xrc20_of_token(pcx()),
+            Some((DJANGO, new_selectors))
+        );
+        assert_eq!(Contracts::token_of_addr(DJANGO), Some(pcx()));
+        assert_eq!(Contracts::token_of_addr(BOB), None);
+        assert_eq!(
+            System::events().last().map(|r| r.event.clone()),
+            Some(MetaEvent::contract(RawEvent::Xrc20Migrated(
+                pcx(),
+                BOB,
+                DJANGO,
+                500
+            )))
+        );
+    });
+}
+
+/// A mock xrc20 whose `issue` burns some gas and then returns `true`.
+const CODE_XRC20_ISSUE_BURN_GAS: &str = r#"
+(module
+    (import "env" "ext_scratch_write" (func $ext_scratch_write (param i32 i32)))
+    (import "env" "memory" (memory 1 1))
+
+    (func (export "call")
+        (local $i i32)
+        (set_local $i (i32.const 500))
+        (block $done
+            (loop $next
+                (br_if $done (i32.eqz (get_local $i)))
+                (set_local $i (i32.sub (get_local $i) (i32.const 1)))
+                (br $next)
+            )
+        )
+        ;; return `true`
+        (call $ext_scratch_write
+            (i32.const 8) ;; Pointer to the data
+            (i32.const 1) ;; Length of the data
+        )
+    )
+    (func (export "deploy"))
+
+    (data (i32.const 8) "\01")
+)
+"#;
+
+#[test]
+fn convert_to_xrc20_retries_issue_out_of_gas() {
+    use crate::XRC20Selector;
+    use AssetType::*;
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        // the xrc20 instance is BOB
+        Contracts::set_token_xrc20(pcx(), BOB, BTreeMap::new());
+
+        // selector missing is not related to gas, no retry
+        assert_err!(
+            Contracts::convert_to_xrc20(Origin::signed(ALICE), pcx(), 1_000, 2_500),
+            "no issue selector in xrc20 info for this token"
+        );
+
+        Contracts::set_xrc20_selector(
+            pcx(),
+            vec![(XRC20Selector::Issue, [0, 0, 0, 1])]
+                .into_iter()
+                .collect(),
+        );
+        // 2_500 is not enough for the issue
+        assert_err!(
+            Contracts::issue_to_xrc20(pcx(), ALICE, 1_000, 2_500).map_err(|e| e.reason),
+            "ran out of gas during contract execution"
+        );
+        assert_eq!(XAssets::asset_balance_of(&BOB, &pcx(), ReservedXRC20), 0);
+
+        let free = XAssets::pcx_free_balance(&ALICE);
+        assert_ok!(Contracts::convert_to_xrc20(
+            Origin::signed(ALICE),
+            pcx(),
+            1_000,
+            2_500
+        ));
+        assert_eq!(
+            XAssets::asset_balance_of(&BOB, &pcx(), ReservedXRC20),
+            1_000
+        );
+        // the gas of the failed attempt is charged as well
+        let gas_price = 2;
+        assert!(XAssets::pcx_free_balance(&ALICE) < free - 1_000 - 2_500 * gas_price);
+    });
+}
+
+#[test]
+fn call_sponsored_requires_beneficiary_authorization() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000);
+        XAssets::pcx_issue(&DJANGO, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+
+        // DJANGO sponsors the call made as CHARLIE.
+        assert_err!(
+            Contracts::call_sponsored(Origin::signed(DJANGO), BOB, 0, 100_000, vec![], CHARLIE),
+            "beneficiary has not authorized this sponsor"
+        );
+
+        assert_ok!(Contracts::set_sponsor(
+            Origin::signed(CHARLIE),
+            DJANGO,
+            BOB,
+            Some(100_000)
+        ));
+        assert_eq!(
+            Contracts::sponsor_allowance(&(CHARLIE, DJANGO, BOB)),
+            Some(100_000)
+        );
+        // the authorization is not symmetric
+        assert_err!(
+            Contracts::call_sponsored(Origin::signed(CHARLIE), BOB, 0, 100_000, vec![], DJANGO),
+            "beneficiary has not authorized this sponsor"
+        );
+        // the authorization is only for BOB
+        assert_err!(
+            Contracts::call_sponsored(Origin::signed(DJANGO), ALICE, 0, 100_000, vec![], CHARLIE),
+            "beneficiary has not authorized this sponsor"
+        );
+        // the sponsor can't move the balance of the beneficiary
+        assert_err!(
+            Contracts::call_sponsored(Origin::signed(DJANGO), BOB, 10, 100_000, vec![], CHARLIE),
+            "value is not allowed in a sponsored call"
+        );
+        assert_err!(
+            Contracts::call_sponsored(Origin::signed(DJANGO), BOB, 0, 100_001, vec![], CHARLIE),
+            "gas limit exceeds the gas authorized by the beneficiary"
+        );
+
+        let sponsor_free = XAssets::pcx_free_balance(&DJANGO);
+        assert_ok!(Contracts::call_sponsored(
+            Origin::signed(DJANGO),
+            BOB,
+            0,
+            100_000,
+            vec![],
+            CHARLIE
+        ));
+        // the gas is paid by the sponsor
+        assert_eq!(XAssets::pcx_free_balance(&CHARLIE), 1_000);
+        assert!(XAssets::pcx_free_balance(&DJANGO) < sponsor_free);
+
+        assert_ok!(Contracts::set_sponsor(
+            Origin::signed(CHARLIE),
+            DJANGO,
+            BOB,
+            None
+        ));
+        assert_eq!(Contracts::sponsor_allowance(&(CHARLIE, DJANGO, BOB)), None);
+        assert_err!(
+            Contracts::call_sponsored(Origin::signed(DJANGO), BOB, 0, 100_000, vec![], CHARLIE),
+            "beneficiary has not authorized this sponsor"
+        );
+    });
+}
+
+#[test]
+fn refund_to_asset_surfaces_asset_error() {
+    use crate::AssetMoveError;
+    use AssetType::*;
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        // the xrc20 instance is BOB
+        Contracts::set_token_xrc20(pcx(), BOB, BTreeMap::new());
+        assert_ok!(XAssets::move_balance(
+            &pcx(),
+            &ALICE,
+            Free,
+            &BOB,
+            ReservedXRC20,
+            500
+        ));
+
+        let err = Contracts::refund_to_asset(BOB, CHARLIE, 1_000).unwrap_err();
+        assert_eq!(err.reason, "balance too low for this account");
+        assert_eq!(
+            AssetMoveError::<u64, u64>::decode(&mut err.buffer.as_slice()),
+            Some(AssetMoveError {
+                err: AssetErr::NotEnough,
+                token: pcx(),
+                from: BOB,
+                from_type: ReservedXRC20,
+                available: 500,
+                to: CHARLIE,
+                to_type: Free,
+                value: 1_000,
+            })
+        );
+        assert_eq!(XAssets::asset_balance_of(&BOB, &pcx(), ReservedXRC20), 500);
+
+        assert_ok!(Contracts::refund_to_asset(BOB, CHARLIE, 500).map_err(|e| e.reason));
+        assert_eq!(XAssets::pcx_free_balance(&CHARLIE), 500);
+    });
+}
+
 const CODE_DISPATCH_CALL_THEN_TRAP: &str = r#"
 (module
     (import "env" "ext_dispatch_call" (func $ext_dispatch_call (param i32 i32)))
@@ -942,6 +1392,11 @@ fn dispatch_call_not_dispatched_after_top_level_transaction_failure() {
                         event: xassets(Move(pcx(), ALICE, Free, ALICE, GasPayment, 200000)),
                         topics: vec![],
                     },
+                    EventRecord {
+                        phase: Phase::ApplyExtrinsic(0),
+                        event: MetaEvent::contract(RawEvent::GasPriceApplied(ALICE, 2)),
+                        topics: vec![],
+                    },
                     EventRecord {
                         phase: Phase::ApplyExtrinsic(0),
                         event: xassets(Change(pcx(), ALICE, Free, SignedBalance::Negative(100))),
@@ -982,6 +1437,11 @@ fn dispatch_call_not_dispatched_after_top_level_transaction_failure() {
                         event: xassets(Move(pcx(), ALICE, Free, ALICE, GasPayment, 200000)),
                         topics: vec![],
                     },
+                    EventRecord {
+                        phase: Phase::ApplyExtrinsic(0),
+                        event: MetaEvent::contract(RawEvent::GasPriceApplied(ALICE, 2)),
+                        topics: vec![],
+                    },
                     EventRecord {
                         phase: Phase::ApplyExtrinsic(0),
                         event: xassets(Move(pcx(), ALICE, GasPayment, ALICE, Free, 199608)),
@@ -1343,6 +1803,11 @@ fn signed_claim_surcharge_contract_removals() {
 
 #[test]
 fn claim_surcharge_malus() {
+    // The contract is instantiated at block 1 with 12 bytes of storage and a balance of 100,
+    // so it could afford 84 over the subsistence threshold, i.e. one block of rent (48).
+    // When evicted it pays the last rent of 84 to the council and keeps the subsistence
+    // threshold of 16 for the tombstone, and the claimer is rewarded 150 by the council.
+
     // Test surcharge malus for inherent
     claim_surcharge(
         4,
@@ -1357,7 +1822,7 @@ fn claim_surcharge_malus() {
     claim_surcharge(
         2,
         || Contracts::claim_surcharge(Origin::NONE, BOB, Some(ALICE)).is_ok(),
-        true,
+        false,
     );
     claim_surcharge(
         1,
@@ -1365,31 +1830,34 @@ fn claim_surcharge_malus() {
         false,
     );
 
-    // Test surcharge malus for signed
+    // Test surcharge malus for signed, the eviction is checked `SignedClaimHandicap` blocks
+    // in the past.
     claim_surcharge(
-        4,
+        6,
         || Contracts::claim_surcharge(Origin::signed(ALICE), BOB, None).is_ok(),
         true,
     );
     claim_surcharge(
-        3,
+        5,
         || Contracts::claim_surcharge(Origin::signed(ALICE), BOB, None).is_ok(),
-        false,
+        true,
     );
     claim_surcharge(
-        2,
+        4,
         || Contracts::claim_surcharge(Origin::signed(ALICE), BOB, None).is_ok(),
         false,
     );
     claim_surcharge(
-        1,
+        3,
         || Contracts::claim_surcharge(Origin::signed(ALICE), BOB, None).is_ok(),
         false,
     );
 }
 
 /// Claim surcharge with the given trigger_call at the given blocks.
-/// if removes is true then assert that the contract is a tombstonedead
+/// if removes is true then assert that the contract is a tombstone and ALICE is rewarded
+/// by the council,
+/// otherwise assert that the contract is still alive and ALICE is not rewarded.
 fn claim_surcharge(blocks: u64, trigger_call: impl Fn() -> bool, removes: bool) {
     let (wasm, code_hash) = compile_module::<Test>(CODE_SET_RENT).unwrap();
 
@@ -1398,6 +1866,7 @@ fn claim_surcharge(blocks: u64, trigger_call: impl Fn() -> bool, removes: bool)
         || {
             // Create
             XAssets::pcx_issue(&ALICE, 1_000_000);
+            XAssets::pcx_issue(&COUNCIL, 1_000_000);
             assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
             assert_ok!(Contracts::instantiate(
                 Origin::signed(ALICE),
@@ -1411,19 +1880,35 @@ fn claim_surcharge(blocks: u64, trigger_call: impl Fn() -> bool, removes: bool)
             System::initialize(&blocks, &[0u8; 32].into(), &[0u8; 32].into());
 
             // Trigger rent through call
+            let alice_before = XAssets::pcx_free_balance(&ALICE);
             assert!(trigger_call());
 
+            let evicted = System::events()
+                .iter()
+                .any(|r| r.event == MetaEvent::contract(RawEvent::Evicted(BOB, true)));
             if removes {
-                // no tombstone in chainx
-                // assert!(ContractInfoOf::<Test>::get(BOB)
-                //     .unwrap()
-                //     .get_tombstone()
-                //     .is_some());
+                assert!(ContractInfoOf::<Test>::get(BOB)
+                    .unwrap()
+                    .get_tombstone()
+                    .is_some());
+                assert!(evicted);
+                // the whole surcharge reward goes to the claimer by default, and the tombstone
+                // keeps the subsistence threshold.
+                assert_eq!(
+                    XAssets::pcx_free_balance(&ALICE),
+                    alice_before + <Test as Trait>::SurchargeReward::get()
+                );
+                assert_eq!(
+                    XAssets::pcx_free_balance(&BOB),
+                    <Test as Trait>::TombstoneDeposit::get()
+                );
             } else {
                 assert!(ContractInfoOf::<Test>::get(BOB)
                     .unwrap()
                     .get_alive()
                     .is_some());
+                assert!(!evicted);
+                assert_eq!(XAssets::pcx_free_balance(&ALICE), alice_before);
             }
         },
     );
@@ -2006,54 +2491,235 @@ fn restoration(test_different_storage: bool, test_restore_to_with_dirty_storage:
     );
 }
 
-const CODE_STORAGE_SIZE: &str = r#"
-(module
-    (import "env" "ext_get_storage" (func $ext_get_storage (param i32) (result i32)))
-	(import "env" "ext_set_storage" (func $ext_set_storage (param i32 i32 i32)))
-    (import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
-    (import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
-    (import "env" "memory" (memory 16 16))
+#[test]
+fn tombstone_index_tracks_evict_and_restore() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
 
-    (func $assert (param i32)
-        (block $ok
-            (br_if $ok
-                (get_local 0)
-            )
-            (unreachable)
-        )
-    )
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
 
-    (func (export "call")
-        ;; assert $ext_scratch_size == 8
-        (call $assert
-            (i32.eq
-                (call $ext_scratch_size)
-                (i32.const 4)
-            )
-        )
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        // `BOB` would be evicted and `DJANGO` is used to restore it.
+        for origin in [ALICE, CHARLIE].iter() {
+            assert_ok!(Contracts::instantiate(
+                Origin::signed(*origin),
+                100,
+                100_000,
+                code_hash.into(),
+                vec![],
+            ));
+        }
+        // make `BOB` could not afford the rent.
+        ContractInfoOf::<Test>::mutate(BOB, |info| {
+            if let Some(ContractInfo::Alive(ref mut alive)) = info {
+                alive.storage_size = 1_000;
+                alive.rent_allowance = 0;
+            }
+        });
+        assert!(Contracts::tombstone_contracts(0, 10).is_empty());
 
-        ;; copy contents of the scratch buffer into the contract's memory.
-        (call $ext_scratch_read
-            (i32.const 32)		;; Pointer in memory to the place where to copy.
-            (i32.const 0)		;; Offset from the start of the scratch buffer.
-            (i32.const 4)		;; Count of bytes to copy.
-        )
+        System::initialize(&5, &[0u8; 32].into(), &[0u8; 32].into());
 
-        ;; place a garbage value in storage, the size of which is specified by the call input.
-        (call $ext_set_storage
-            (i32.const 0)		;; Pointer to storage key
-            (i32.const 0)		;; Pointer to value
-            (i32.load (i32.const 32))	;; Size of value
-        )
+        assert!(crate::rent::try_evict::<Test>(&BOB, 0) == crate::rent::RentOutcome::Evicted);
+        assert!(ContractInfoOf::<Test>::get(BOB)
+            .unwrap()
+            .get_tombstone()
+            .is_some());
+        assert_eq!(Contracts::tombstone_index(), vec![BOB]);
+        assert_eq!(Contracts::tombstone_contracts(0, 10), vec![BOB]);
+        assert!(Contracts::tombstone_contracts(1, 10).is_empty());
+        assert!(Contracts::tombstone_contracts(0, 0).is_empty());
+
+        assert_ok!(Contracts::restore_to(
+            DJANGO,
+            BOB,
+            code_hash.into(),
+            50,
+            vec![]
+        ));
+        assert!(ContractInfoOf::<Test>::get(BOB)
+            .unwrap()
+            .get_alive()
+            .is_some());
+        assert!(Contracts::tombstone_index().is_empty());
+        assert!(Contracts::tombstone_contracts(0, 10).is_empty());
+    });
+}
 
-        (call $assert
-            (i32.eq
-                (call $ext_get_storage
-                    (i32.const 0)		;; Pointer to storage key
-                )
-                (i32.const 0)
-            )
-        )
+#[test]
+fn restore_to_rejects_oversized_delta() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        for origin in [ALICE, CHARLIE].iter() {
+            assert_ok!(Contracts::instantiate(
+                Origin::signed(*origin),
+                100,
+                100_000,
+                code_hash.into(),
+                vec![],
+            ));
+        }
+        ContractInfoOf::<Test>::mutate(BOB, |info| {
+            if let Some(ContractInfo::Alive(ref mut alive)) = info {
+                alive.storage_size = 1_000;
+                alive.rent_allowance = 0;
+            }
+        });
+
+        System::initialize(&5, &[0u8; 32].into(), &[0u8; 32].into());
+        assert!(crate::rent::try_evict::<Test>(&BOB, 0) == crate::rent::RentOutcome::Evicted);
+
+        let delta = |len: u32| (0..len).map(|i| [i as u8; 32]).collect::<Vec<_>>();
+        assert_err!(
+            Contracts::restore_to(
+                DJANGO,
+                BOB,
+                code_hash.into(),
+                50,
+                delta(MaxRestoreDelta::get() + 1)
+            ),
+            "Too many storage keys in the restoration delta"
+        );
+        assert!(ContractInfoOf::<Test>::get(BOB)
+            .unwrap()
+            .get_tombstone()
+            .is_some());
+
+        assert_ok!(Contracts::restore_to(
+            DJANGO,
+            BOB,
+            code_hash.into(),
+            50,
+            delta(MaxRestoreDelta::get())
+        ));
+        assert!(ContractInfoOf::<Test>::get(BOB)
+            .unwrap()
+            .get_alive()
+            .is_some());
+    });
+}
+
+#[test]
+fn reaping_alive_contract_emits_event() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        for origin in [ALICE, CHARLIE].iter() {
+            assert_ok!(Contracts::instantiate(
+                Origin::signed(*origin),
+                100,
+                100_000,
+                code_hash.into(),
+                vec![],
+            ));
+        }
+
+        let storage_size = ContractInfoOf::<Test>::get(BOB)
+            .unwrap()
+            .get_alive()
+            .unwrap()
+            .storage_size;
+        // the alive contract is reaped once its free balance is transferred away.
+        let bob_free = XAssets::pcx_free_balance(&BOB);
+        assert_ok!(XAssets::transfer(
+            Origin::signed(BOB),
+            ALICE,
+            pcx(),
+            bob_free,
+            vec![]
+        ));
+        assert!(ContractInfoOf::<Test>::get(BOB).is_none());
+        assert_eq!(
+            System::events().last().unwrap().event,
+            MetaEvent::contract(RawEvent::ContractReaped(BOB, storage_size))
+        );
+
+        // tombstones have no alive storage to free.
+        ContractInfoOf::<Test>::mutate(DJANGO, |info| {
+            if let Some(ContractInfo::Alive(ref mut alive)) = info {
+                alive.storage_size = 1_000;
+                alive.rent_allowance = 0;
+            }
+        });
+        System::initialize(&5, &[0u8; 32].into(), &[0u8; 32].into());
+        assert!(crate::rent::try_evict::<Test>(&DJANGO, 0) == crate::rent::RentOutcome::Evicted);
+        let django_free = XAssets::pcx_free_balance(&DJANGO);
+        assert_ok!(XAssets::transfer(
+            Origin::signed(DJANGO),
+            CHARLIE,
+            pcx(),
+            django_free,
+            vec![]
+        ));
+        assert!(ContractInfoOf::<Test>::get(DJANGO).is_none());
+        assert!(!System::events().iter().any(|record| match record.event {
+            MetaEvent::contract(RawEvent::ContractReaped(..)) => true,
+            _ => false,
+        }));
+    });
+}
+
+const CODE_STORAGE_SIZE: &str = r#"
+(module
+    (import "env" "ext_get_storage" (func $ext_get_storage (param i32) (result i32)))
+	(import "env" "ext_set_storage" (func $ext_set_storage (param i32 i32 i32)))
+    (import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+    (import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+    (import "env" "memory" (memory 16 16))
+
+    (func $assert (param i32)
+        (block $ok
+            (br_if $ok
+                (get_local 0)
+            )
+            (unreachable)
+        )
+    )
+
+    (func (export "call")
+        ;; assert $ext_scratch_size == 8
+        (call $assert
+            (i32.eq
+                (call $ext_scratch_size)
+                (i32.const 4)
+            )
+        )
+
+        ;; copy contents of the scratch buffer into the contract's memory.
+        (call $ext_scratch_read
+            (i32.const 32)		;; Pointer in memory to the place where to copy.
+            (i32.const 0)		;; Offset from the start of the scratch buffer.
+            (i32.const 4)		;; Count of bytes to copy.
+        )
+
+        ;; place a garbage value in storage, the size of which is specified by the call input.
+        (call $ext_set_storage
+            (i32.const 0)		;; Pointer to storage key
+            (i32.const 0)		;; Pointer to value
+            (i32.load (i32.const 32))	;; Size of value
+        )
+
+        (call $assert
+            (i32.eq
+                (call $ext_get_storage
+                    (i32.const 0)		;; Pointer to storage key
+                )
+                (i32.const 0)
+            )
+        )
 
         (call $assert
             (i32.eq
@@ -2479,6 +3145,11 @@ fn deploy_and_call_other_contract() {
                 200_000,
                 callee_code_hash.as_ref().to_vec(),
             ));
+            // the nested instantiations bump the nonce of BOB, the two failed ones as well.
+            assert_eq!(Contracts::instantiation_nonce(BOB), 3);
+            // the contract instantiated by a contract is owned by it.
+            assert_eq!(Contracts::contract_owner_of(BOB), Some(ALICE));
+            assert_eq!(Contracts::contract_owner_of(CHARLIE), Some(BOB));
         },
     );
 }
@@ -3218,3 +3889,1996 @@ fn crypto_hashes() {
         },
     )
 }
+
+#[test]
+fn call_with_token_pays_gas_in_accepted_token() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        let btc = b"BTC".to_vec();
+        let asset = Asset::new(
+            btc.clone(),
+            b"X-BTC".to_vec(),
+            Chain::Bitcoin,
+            8,
+            b"ChainX's Cross-chain Bitcoin".to_vec(),
+        )
+        .unwrap();
+        XAssets::bootstrap_register_asset(asset, true, true).unwrap();
+
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_ok!(XAssets::issue(&btc, &CHARLIE, 1_000_000));
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+
+        assert_err!(
+            Contracts::call_with_token(
+                Origin::signed(CHARLIE),
+                BOB,
+                0,
+                100_000,
+                vec![],
+                btc.clone()
+            ),
+            "this token is not accepted for paying the gas"
+        );
+        assert_err!(
+            Contracts::set_gas_payment_token(pcx(), true),
+            "should not use chainx token here"
+        );
+        assert_ok!(Contracts::set_gas_payment_token(btc.clone(), true));
+        assert_eq!(Contracts::gas_payment_tokens(), vec![btc.clone()]);
+
+        let council_btc = XAssets::free_balance_of(&COUNCIL, &btc);
+        let total_btc = XAssets::all_type_total_asset_balance(&btc);
+        assert_ok!(Contracts::call_with_token(
+            Origin::signed(CHARLIE),
+            BOB,
+            0,
+            100_000,
+            vec![],
+            btc.clone()
+        ));
+        // the gas is paid in BTC and no PCX is required.
+        assert_eq!(XAssets::pcx_free_balance(&CHARLIE), 0);
+        let spent = 1_000_000 - XAssets::free_balance_of(&CHARLIE, &btc);
+        assert!(spent > 0);
+        // at most 100_000 * 2 PCX, i.e., 100_000 BTC.
+        assert!(spent <= 100_000);
+        assert_eq!(
+            XAssets::asset_balance_of(&CHARLIE, &btc, AssetType::GasPayment),
+            0
+        );
+        // the spent BTC is destroyed rather than paid to anyone.
+        assert_eq!(XAssets::free_balance_of(&COUNCIL, &btc), council_btc);
+        assert_eq!(
+            XAssets::all_type_total_asset_balance(&btc),
+            total_btc - spent
+        );
+
+        assert_ok!(Contracts::set_gas_payment_token(btc.clone(), false));
+        assert!(Contracts::gas_payment_tokens().is_empty());
+    });
+}
+
+#[test]
+fn dynamic_gas_price_decays_toward_baseline() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        let next_block = |n: u64, gas_spent: u64| {
+            <Contracts as OnInitialize<u64>>::on_initialize(n);
+            GasSpent::<Test>::put(gas_spent);
+            <Contracts as OnFinalize<u64>>::on_finalize(n);
+        };
+
+        // spike
+        assert_ok!(Contracts::set_gas_price(1_000));
+        // disabled by default
+        next_block(1, 0);
+        assert_eq!(Contracts::gas_price(), 1_000);
+
+        assert_ok!(Contracts::set_dynamic_gas_price(true, 2));
+        let mut last_price = Contracts::gas_price();
+        for n in 2..12 {
+            next_block(n, 0);
+            let price = Contracts::gas_price();
+            assert!(price < last_price);
+            last_price = price;
+        }
+        assert_eq!(last_price, 353);
+
+        // the price stays in the block after a busy one.
+        next_block(12, BlockGasLimit::get() / 2);
+        assert_eq!(Contracts::gas_price(), 318);
+        next_block(13, 0);
+        assert_eq!(Contracts::gas_price(), 318);
+
+        for n in 14..100 {
+            next_block(n, 0);
+        }
+        assert_eq!(Contracts::gas_price(), 2);
+
+        assert_ok!(Contracts::set_gas_price(1_000));
+        assert_ok!(Contracts::set_dynamic_gas_price(false, 2));
+        next_block(100, 0);
+        assert_eq!(Contracts::gas_price(), 1_000);
+    });
+}
+
+#[test]
+fn funded_contract_pays_gas_from_allowance() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        for origin in [ALICE, CHARLIE].iter() {
+            assert_ok!(Contracts::instantiate(
+                Origin::signed(*origin),
+                100,
+                100_000,
+                code_hash.into(),
+                vec![],
+            ));
+        }
+
+        assert_err!(
+            Contracts::fund_contract_gas(Origin::signed(CHARLIE), ALICE, 1_000),
+            "unable to fund the gas for a non-alive contract"
+        );
+        // enough for a single call with 100_000 gas at price 2.
+        assert_ok!(Contracts::fund_contract_gas(
+            Origin::signed(CHARLIE),
+            BOB,
+            201_000
+        ));
+        assert_eq!(Contracts::contract_gas_allowance(&BOB), 201_000);
+        assert_eq!(
+            XAssets::pcx_type_balance(&BOB, AssetType::ReservedCurrency),
+            201_000
+        );
+
+        // the contract `BOB` calls `DJANGO` as the caller, e.g. by a dispatched runtime call.
+        assert_ok!(Contracts::call(
+            Origin::signed(BOB),
+            DJANGO,
+            0,
+            100_000,
+            vec![]
+        ));
+        let allowance = Contracts::contract_gas_allowance(&BOB);
+        assert!(allowance < 201_000);
+        assert_eq!(
+            XAssets::pcx_type_balance(&BOB, AssetType::ReservedCurrency),
+            allowance
+        );
+        assert_eq!(XAssets::pcx_free_balance(&BOB), 100);
+
+        // the allowance is not enough, the gas is bought from the free balance.
+        assert_err!(
+            Contracts::call(Origin::signed(BOB), DJANGO, 0, 100_000, vec![]),
+            "balance too low for this account"
+        );
+        XAssets::pcx_issue(&BOB, 1_000_000);
+        assert_ok!(Contracts::call(
+            Origin::signed(BOB),
+            DJANGO,
+            0,
+            100_000,
+            vec![]
+        ));
+        assert_eq!(Contracts::contract_gas_allowance(&BOB), allowance);
+        assert!(XAssets::pcx_free_balance(&BOB) < 1_000_100);
+
+        // only the owner could withdraw the allowance.
+        assert_eq!(Contracts::contract_owner_of(&BOB), Some(ALICE));
+        assert_err!(
+            Contracts::withdraw_contract_gas(Origin::signed(CHARLIE), BOB, 1),
+            "only the owner of the contract could withdraw the gas allowance"
+        );
+        assert_err!(
+            Contracts::withdraw_contract_gas(Origin::signed(ALICE), BOB, allowance + 1),
+            "not enough gas allowance for this contract"
+        );
+        let alice_free = XAssets::pcx_free_balance(&ALICE);
+        assert_ok!(Contracts::withdraw_contract_gas(
+            Origin::signed(ALICE),
+            BOB,
+            allowance
+        ));
+        assert_eq!(XAssets::pcx_free_balance(&ALICE), alice_free + allowance);
+        assert!(!crate::ContractGasAllowance::<Test>::exists(&BOB));
+        assert_eq!(
+            XAssets::pcx_type_balance(&BOB, AssetType::ReservedCurrency),
+            0
+        );
+    });
+}
+
+#[test]
+fn gas_allowance_is_released_when_contract_reaped() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        assert_ok!(Contracts::fund_contract_gas(
+            Origin::signed(CHARLIE),
+            BOB,
+            1_000
+        ));
+
+        // the contract is reaped once its free balance is transferred away.
+        let alice_free = XAssets::pcx_free_balance(&ALICE);
+        let bob_free = XAssets::pcx_free_balance(&BOB);
+        assert_ok!(XAssets::transfer(
+            Origin::signed(BOB),
+            CHARLIE,
+            pcx(),
+            bob_free,
+            vec![]
+        ));
+        assert!(ContractInfoOf::<Test>::get(BOB).is_none());
+        // the allowance goes to the owner of the contract.
+        assert_eq!(XAssets::pcx_free_balance(&ALICE), alice_free + 1_000);
+        assert_eq!(Contracts::contract_gas_allowance(&BOB), 0);
+        assert_eq!(
+            XAssets::pcx_type_balance(&BOB, AssetType::ReservedCurrency),
+            0
+        );
+    });
+}
+
+#[test]
+fn mainnet_instantiate_requires_approved_code_hash() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+    let (other_wasm, other_code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+        xsystem::NetworkProps::<Test>::put((xsystem::NetworkType::Mainnet, 0));
+
+        assert_ok!(Contracts::put_code(Origin::signed(COUNCIL), 100_000, wasm));
+        assert_ok!(Contracts::put_code(
+            Origin::signed(COUNCIL),
+            100_000,
+            other_wasm
+        ));
+
+        // no restriction with an empty allow-list.
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            other_code_hash.into(),
+            vec![],
+        ));
+
+        assert_ok!(Contracts::set_approved_code_hash(code_hash.into(), true));
+        assert_eq!(Contracts::approved_code_hashes(), vec![code_hash]);
+        assert_err!(
+            Contracts::instantiate(
+                Origin::signed(CHARLIE),
+                100,
+                100_000,
+                other_code_hash.into(),
+                vec![],
+            ),
+            "[instantiate]|in mainnet, only the approved code hash could be instantiated."
+        );
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(CHARLIE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+
+        // testnet is unaffected.
+        xsystem::NetworkProps::<Test>::put((xsystem::NetworkType::Testnet, 0));
+        XAssets::pcx_issue(&5, 1_000_000);
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(5),
+            100,
+            100_000,
+            other_code_hash.into(),
+            vec![],
+        ));
+    });
+}
+
+#[test]
+fn mainnet_nested_instantiate_requires_approved_code_hash() {
+    let (callee_wasm, callee_code_hash) = compile_module::<Test>(CODE_RETURN_WITH_DATA).unwrap();
+    let (caller_wasm, caller_code_hash) = compile_module::<Test>(CODE_CALLER_CONTRACT).unwrap();
+
+    with_externalities(
+        &mut ExtBuilder::default().existential_deposit(50).build(),
+        || {
+            XAssets::pcx_issue(&ALICE, 1_000_000);
+            xsystem::NetworkProps::<Test>::put((xsystem::NetworkType::Mainnet, 0));
+
+            assert_ok!(Contracts::put_code(
+                Origin::signed(ALICE),
+                100_000,
+                callee_wasm
+            ));
+            assert_ok!(Contracts::put_code(
+                Origin::signed(ALICE),
+                100_000,
+                caller_wasm
+            ));
+            assert_ok!(Contracts::set_approved_code_hash(
+                caller_code_hash.into(),
+                true
+            ));
+            assert_ok!(Contracts::instantiate(
+                Origin::signed(ALICE),
+                100_000,
+                100_000,
+                caller_code_hash.into(),
+                vec![],
+            ));
+
+            // BOB can't instantiate the callee code which is not approved.
+            assert!(Contracts::call(
+                Origin::signed(ALICE),
+                BOB,
+                0,
+                200_000,
+                callee_code_hash.as_ref().to_vec(),
+            )
+            .is_err());
+            assert!(ContractInfoOf::<Test>::get(CHARLIE).is_none());
+
+            assert_ok!(Contracts::set_approved_code_hash(
+                callee_code_hash.into(),
+                true
+            ));
+            assert_ok!(Contracts::call(
+                Origin::signed(ALICE),
+                BOB,
+                0,
+                200_000,
+                callee_code_hash.as_ref().to_vec(),
+            ));
+            assert!(ContractInfoOf::<Test>::get(CHARLIE).is_some());
+        },
+    );
+}
+
+#[test]
+fn total_xrc20_reserved_matches_converted_amount() {
+    use crate::XRC20Selector;
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        assert_eq!(Contracts::total_xrc20_reserved(&pcx()), 0);
+        assert!(Contracts::all_xrc20_reserved().is_empty());
+
+        // the xrc20 instance is BOB
+        Contracts::set_token_xrc20(
+            pcx(),
+            BOB,
+            vec![(XRC20Selector::Issue, [0, 0, 0, 1])]
+                .into_iter()
+                .collect(),
+        );
+        assert_ok!(Contracts::convert_to_xrc20(
+            Origin::signed(ALICE),
+            pcx(),
+            1_000,
+            100_000
+        ));
+        assert_ok!(Contracts::convert_to_xrc20(
+            Origin::signed(ALICE),
+            pcx(),
+            234,
+            100_000
+        ));
+
+        assert_eq!(Contracts::total_xrc20_reserved(&pcx()), 1_234);
+        assert_eq!(
+            Contracts::all_xrc20_reserved(),
+            vec![(pcx(), 1_234)].into_iter().collect()
+        );
+    });
+}
+
+#[test]
+fn set_println_is_rejected_in_mainnet() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        xsystem::NetworkProps::<Test>::put((xsystem::NetworkType::Mainnet, 0));
+        assert_err!(
+            Contracts::set_println(true),
+            "[set_println]|in mainnet, println could not be enabled."
+        );
+        // disabling is always allowed.
+        assert_ok!(Contracts::set_println(false));
+        assert!(!Contracts::current_schedule().enable_println);
+        assert_err!(
+            Contracts::set_println(true),
+            "[set_println]|in mainnet, println could not be enabled."
+        );
+        assert!(!Contracts::current_schedule().enable_println);
+
+        xsystem::NetworkProps::<Test>::put((xsystem::NetworkType::Testnet, 0));
+        assert_ok!(Contracts::set_println(true));
+        assert!(Contracts::current_schedule().enable_println);
+    });
+}
+
+#[test]
+fn get_storage_multi_matches_get_storage() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        let trie_id = <Test as Trait>::TrieIdGenerator::trie_id(&BOB);
+        let (key1, key2, key3) = ([1; 32], [2; 32], [3; 32]);
+
+        assert!(match Contracts::get_storage_multi(BOB, vec![key1]) {
+            Err(crate::GetStorageError::ContractDoesntExist) => true,
+            _ => false,
+        });
+
+        // the tombstone is rejected before any key lookup.
+        ContractInfoOf::<Test>::insert(
+            CHARLIE,
+            ContractInfo::Tombstone(crate::TombstoneContractInfo::<Test>::new(
+                &[],
+                H256::default(),
+            )),
+        );
+        assert!(match Contracts::get_storage_multi(CHARLIE, vec![key1]) {
+            Err(crate::GetStorageError::IsTombstone) => true,
+            _ => false,
+        });
+
+        ContractInfoOf::<Test>::insert(
+            BOB,
+            &ContractInfo::Alive(RawAliveContractInfo {
+                trie_id: trie_id.clone(),
+                storage_size: <Test as Trait>::StorageSizeOffset::get(),
+                deduct_block: System::block_number(),
+                code_hash: H256::repeat_byte(1),
+                rent_allowance: 40,
+                last_write: None,
+            }),
+        );
+        let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+        overlay.set_storage(&BOB, key1, Some(b"1".to_vec()));
+        overlay.set_storage(&BOB, key2, Some(b"2".to_vec()));
+        DirectAccountDb.commit(overlay.into_change_set());
+
+        let keys = vec![key2, key3, key1];
+        let values = Contracts::get_storage_multi(BOB, keys.clone())
+            .ok()
+            .unwrap();
+        assert_eq!(values, vec![Some(b"2".to_vec()), None, Some(b"1".to_vec())]);
+        for (key, value) in keys.into_iter().zip(values) {
+            assert_eq!(Contracts::get_storage(BOB, key).ok().unwrap(), value);
+        }
+
+        // too many keys are rejected rather than truncated.
+        let keys = vec![key1; crate::MAX_STORAGE_KEYS_PER_QUERY];
+        assert_eq!(
+            Contracts::get_storage_multi(BOB, keys).ok().unwrap().len(),
+            crate::MAX_STORAGE_KEYS_PER_QUERY
+        );
+        let keys = vec![key1; crate::MAX_STORAGE_KEYS_PER_QUERY + 1];
+        assert!(match Contracts::get_storage_multi(BOB, keys) {
+            Err(crate::GetStorageError::TooManyKeys) => true,
+            _ => false,
+        });
+    });
+}
+
+#[test]
+fn migrate_contract_trie_should_preserve_storage() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        let trie_id = <Test as Trait>::TrieIdGenerator::trie_id(&BOB);
+        let (key1, key2, key3) = ([1; 32], [2; 32], [3; 32]);
+
+        ContractInfoOf::<Test>::insert(
+            BOB,
+            &ContractInfo::Alive(RawAliveContractInfo {
+                trie_id: trie_id.clone(),
+                storage_size: <Test as Trait>::StorageSizeOffset::get(),
+                deduct_block: System::block_number(),
+                code_hash: H256::repeat_byte(1),
+                rent_allowance: 40,
+                last_write: None,
+            }),
+        );
+        let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+        overlay.set_storage(&BOB, key1, Some(b"1".to_vec()));
+        overlay.set_storage(&BOB, key2, Some(b"22".to_vec()));
+        overlay.set_storage(&BOB, key3, Some(b"333".to_vec()));
+        DirectAccountDb.commit(overlay.into_change_set());
+
+        System::set_block_number(2);
+
+        assert_err!(
+            Contracts::migrate_contract_trie(BOB, vec![key1; 5], 100),
+            "Too many storage keys in a page of the migration"
+        );
+
+        // the unfinished migration could be dropped.
+        assert_ok!(Contracts::migrate_contract_trie(BOB, vec![key1], 100));
+        let (pending, _) = Contracts::pending_trie_migration(BOB).unwrap();
+        assert_ok!(Contracts::cancel_pending_contract_trie(BOB));
+        assert_eq!(Contracts::pending_trie_migration(BOB), None);
+        assert_eq!(
+            child::get_raw(&pending, &runtime_io::blake2_256(&key1)),
+            None
+        );
+
+        // the keys are paged, the trie is kept until they cover the whole trie.
+        assert_ok!(Contracts::migrate_contract_trie(BOB, vec![key1, key2], 100));
+        assert_eq!(Contracts::pending_trie_migration(BOB).unwrap().1, 2);
+        assert_eq!(
+            ContractInfoOf::<Test>::get(BOB)
+                .and_then(|c| c.get_alive())
+                .unwrap()
+                .trie_id,
+            trie_id
+        );
+        // each key costs a read and a write per byte.
+        assert_err!(
+            Contracts::migrate_contract_trie(BOB, vec![key3], 3),
+            "Not enough gas to migrate the contract trie"
+        );
+        assert_eq!(
+            ContractInfoOf::<Test>::get(BOB)
+                .and_then(|c| c.get_alive())
+                .unwrap()
+                .trie_id,
+            trie_id
+        );
+
+        assert_ok!(Contracts::migrate_contract_trie(BOB, vec![key3], 4));
+        assert_eq!(Contracts::pending_trie_migration(BOB), None);
+        assert_eq!(
+            System::events().last().unwrap().event,
+            MetaEvent::contract(RawEvent::ContractTrieMigrated(BOB, 3))
+        );
+        let new_trie_id = ContractInfoOf::<Test>::get(BOB)
+            .and_then(|c| c.get_alive())
+            .unwrap()
+            .trie_id;
+        assert_ne!(new_trie_id, trie_id);
+        assert_eq!(
+            Contracts::get_storage_multi(BOB, vec![key1, key2, key3])
+                .ok()
+                .unwrap(),
+            vec![
+                Some(b"1".to_vec()),
+                Some(b"22".to_vec()),
+                Some(b"333".to_vec())
+            ]
+        );
+        // the old trie is killed.
+        assert_eq!(
+            child::get_raw(&trie_id, &runtime_io::blake2_256(&key1)),
+            None
+        );
+
+        // the trie has been written in this block.
+        assert_err!(
+            Contracts::migrate_contract_trie(BOB, vec![key1, key2, key3], 9),
+            "Contract TrieId written in the current block"
+        );
+    });
+}
+
+#[test]
+fn instantiation_nonce_increments_per_instantiation() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_eq!(Contracts::instantiation_nonce(ALICE), 0);
+
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        assert_eq!(Contracts::instantiation_nonce(ALICE), 1);
+
+        // the mock determiner always yields the same address, the failed instantiation is
+        // counted as well since the address has been derived.
+        assert!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        )
+        .is_err());
+        assert_eq!(Contracts::instantiation_nonce(ALICE), 2);
+        assert_eq!(Contracts::instantiation_nonce(BOB), 0);
+
+        let hash_with_nonce =
+            |nonce| crate::contract_address_hash::<Test>(&code_hash, &[], &[1; 32], nonce);
+        assert_ne!(hash_with_nonce(0), hash_with_nonce(1));
+        assert_ne!(hash_with_nonce(1), hash_with_nonce(2));
+    });
+}
+
+#[test]
+fn bare_instantiate_should_return_address() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_RETURN_WITH_DATA).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        let free = XAssets::pcx_free_balance(&ALICE);
+
+        // the constructor fails, nothing is committed except the gas.
+        let (address, output) =
+            Contracts::bare_instantiate(ALICE, 100, 100_000, code_hash.into(), vec![1, 0, 0, 0])
+                .unwrap();
+        assert_eq!(address, BOB);
+        assert!(!output.is_success());
+        assert!(ContractInfoOf::<Test>::get(BOB).is_none());
+        assert_eq!(Contracts::instantiation_nonce(&ALICE), 1);
+        let after_failure = XAssets::pcx_free_balance(&ALICE);
+        assert!(after_failure < free);
+        // the unused gas is refunded.
+        assert!(after_failure > free - 100_000 * Contracts::gas_price());
+
+        let (address, output) = Contracts::bare_instantiate(
+            ALICE,
+            100,
+            100_000,
+            code_hash.into(),
+            vec![0, 0, 0, 0, 1, 2, 3],
+        )
+        .unwrap();
+        assert_eq!(address, BOB);
+        assert!(output.is_success());
+        assert_eq!(output.data, vec![1, 2, 3]);
+        assert!(ContractInfoOf::<Test>::get(BOB)
+            .unwrap()
+            .get_alive()
+            .is_some());
+        assert_eq!(Contracts::instantiation_nonce(&ALICE), 2);
+        assert_eq!(XAssets::pcx_free_balance(&BOB), 100);
+    });
+}
+
+#[test]
+fn validate_instantiate_should_not_commit() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_RETURN_WITH_DATA).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        let free = XAssets::pcx_free_balance(&ALICE);
+
+        // the first 4 bytes are the exit status of the constructor.
+        let valid_data = vec![0, 0, 0, 0, 1, 2, 3];
+        assert_eq!(
+            Contracts::validate_instantiate(
+                ALICE,
+                code_hash.into(),
+                100,
+                100_000,
+                valid_data.clone()
+            )
+            .ok(),
+            Some(BOB)
+        );
+        assert!(ContractInfoOf::<Test>::get(BOB).is_none());
+        assert_eq!(XAssets::pcx_free_balance(&ALICE), free);
+
+        let malformed_data = vec![1, 0, 0, 0, 1, 2, 3];
+        let err =
+            Contracts::validate_instantiate(ALICE, code_hash.into(), 100, 100_000, malformed_data)
+                .err()
+                .unwrap();
+        assert_eq!(
+            err.reason,
+            "fail to create contract, maybe instantiate data decode error"
+        );
+        assert_eq!(err.buffer, vec![1, 2, 3]);
+
+        // the predicted address is what the instantiation yields.
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            valid_data,
+        ));
+        assert!(ContractInfoOf::<Test>::get(BOB).is_some());
+    });
+}
+
+#[test]
+fn gas_price_applied_matches_stored_gas_price() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+
+        assert_ok!(Contracts::set_gas_price(3));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        let applied = System::events()
+            .into_iter()
+            .filter_map(|record| match record.event {
+                MetaEvent::contract(RawEvent::GasPriceApplied(who, price)) => Some((who, price)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(applied, vec![(ALICE, Contracts::gas_price())]);
+        assert_eq!(Contracts::gas_price(), 3);
+    });
+}
+
+#[test]
+fn put_code_and_instantiate_should_work() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+
+        // the endowment can not be afforded, the stored code is reverted as well.
+        assert!(Contracts::put_code_and_instantiate(
+            Origin::signed(ALICE),
+            100_000_000,
+            100_000,
+            wasm.clone(),
+            vec![],
+            vec![],
+        )
+        .is_err());
+        assert!(<super::PristineCode<Test>>::get(code_hash).is_none());
+        assert!(<super::CodeStorage<Test>>::get(code_hash).is_none());
+        assert_eq!(Contracts::instantiation_nonce(ALICE), 1);
+
+        assert_ok!(Contracts::put_code_and_instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            wasm.clone(),
+            vec![],
+            vec![1, 2, 3],
+        ));
+        assert!(<super::PristineCode<Test>>::get(code_hash).is_some());
+        assert!(ContractInfoOf::<Test>::get(BOB)
+            .and_then(|info| info.as_alive().cloned())
+            .is_some());
+        assert_eq!(Contracts::instantiation_nonce(ALICE), 2);
+
+        // the code stored before is reused and kept even if the instantiation fails.
+        assert!(Contracts::put_code_and_instantiate(
+            Origin::signed(ALICE),
+            100_000_000,
+            100_000,
+            wasm,
+            vec![],
+            vec![],
+        )
+        .is_err());
+        assert!(<super::PristineCode<Test>>::get(code_hash).is_some());
+        assert!(<super::CodeStorage<Test>>::get(code_hash).is_some());
+        assert_eq!(Contracts::instantiation_nonce(ALICE), 3);
+    });
+}
+
+const CODE_STORAGE_WRITE_KEYS: &str = r#"
+(module
+    (import "env" "ext_set_storage" (func $ext_set_storage (param i32 i32 i32)))
+    (import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+    (import "env" "memory" (memory 16 16))
+
+    (func (export "call")
+        (local $i i32)
+
+        ;; the count of keys to write is specified by the call input.
+        (call $ext_scratch_read
+            (i32.const 32)		;; Pointer in memory to the place where to copy.
+            (i32.const 0)		;; Offset from the start of the scratch buffer.
+            (i32.const 4)		;; Count of bytes to copy.
+        )
+
+        (block $done
+            (loop $write
+                (br_if $done
+                    (i32.ge_u (get_local $i) (i32.load (i32.const 32)))
+                )
+
+                ;; use the loop counter as the first byte of the storage key.
+                (i32.store8 (i32.const 0) (get_local $i))
+                (call $ext_set_storage
+                    (i32.const 0)		;; Pointer to storage key
+                    (i32.const 64)		;; Pointer to value
+                    (i32.const 16384)	;; Size of value
+                )
+
+                (set_local $i (i32.add (get_local $i) (i32.const 1)))
+                (br $write)
+            )
+        )
+    )
+
+    (func (export "deploy"))
+)
+"#;
+
+#[test]
+fn dry_run_call_should_not_commit() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_STORAGE_WRITE_KEYS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 100_000_000);
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            30_000,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        let free = XAssets::pcx_free_balance(&ALICE);
+        let events = System::events().len();
+
+        // write one key, the first key is all zero.
+        let (result, gas_used) = Contracts::dry_run_call(ALICE, BOB, 0, 100_000, 1u32.encode());
+        assert!(result.unwrap().is_success());
+        assert!(gas_used > 0);
+        assert_eq!(Contracts::get_storage(BOB, [0; 32]).ok(), Some(None));
+        assert_eq!(XAssets::pcx_free_balance(&ALICE), free);
+        assert_eq!(System::events().len(), events);
+
+        assert!(Contracts::bare_call(ALICE, BOB, 0, 100_000, 1u32.encode())
+            .unwrap()
+            .is_success());
+        assert!(Contracts::get_storage(BOB, [0; 32]).ok().unwrap().is_some());
+
+        let (result, gas_used) = Contracts::dry_run_call(ALICE, CHARLIE, 0, 100_000, vec![]);
+        assert!(result.is_err());
+        assert_eq!(gas_used, 0);
+    });
+}
+
+#[test]
+fn storage_write_bytes_per_call_limit() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_STORAGE_WRITE_KEYS).unwrap();
+
+    with_externalities(
+        &mut ExtBuilder::default().existential_deposit(50).build(),
+        || {
+            XAssets::pcx_issue(&ALICE, 100_000_000);
+            assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+            assert_ok!(Contracts::instantiate(
+                Origin::signed(ALICE),
+                30_000,
+                100_000,
+                code_hash.into(),
+                vec![],
+            ));
+
+            // (32 + 16_384) * 3 bytes are within the budget.
+            assert_ok!(Contracts::call(
+                Origin::signed(ALICE),
+                BOB,
+                0,
+                1_000_000,
+                Encode::encode(&3u32),
+            ));
+
+            // (32 + 16_384) * 4 bytes exceed the budget, the call traps.
+            assert_err!(
+                Contracts::call(
+                    Origin::signed(ALICE),
+                    BOB,
+                    0,
+                    1_000_000,
+                    Encode::encode(&4u32),
+                ),
+                "during execution|Failed to invoke an exported function for some reason|wrong selector, decode params fail or inner error"
+            );
+        },
+    );
+}
+
+/// A mock xrc20 whose `issue` returns nothing, which could not be decoded as `bool`.
+const CODE_XRC20_ISSUE_RETURN_NOTHING: &str = r#"
+(module
+    (import "env" "ext_scratch_write" (func $ext_scratch_write (param i32 i32)))
+    (import "env" "memory" (memory 1 1))
+
+    (func (export "call")
+        (call $ext_scratch_write
+            (i32.const 8) ;; Pointer to the data
+            (i32.const 0) ;; Length of the data
+        )
+    )
+    (func (export "deploy"))
+)
+"#;
+
+#[test]
+fn issue_to_xrc20_emits_event_on_undecodable_result() {
+    use crate::{XRC20Selector, XRC777Selector};
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_RETURN_NOTHING).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        // the xrc20 instance is BOB
+        Contracts::set_token_xrc20(
+            pcx(),
+            BOB,
+            vec![(XRC20Selector::Issue, [0, 0, 0, 1])]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_err!(
+            Contracts::issue_to_xrc20(pcx(), ALICE, 1_000, 100_000).map_err(|e| e.reason),
+            "fail decode wasm result to bool"
+        );
+        assert!(System::events().iter().any(|record| record.event
+            == MetaEvent::contract(RawEvent::Xrc20ResultDecodeFailed(
+                pcx(),
+                XRC20Selector::Issue,
+                vec![]
+            ))));
+
+        // the same for the xrc777 instance.
+        assert_ok!(Contracts::remove_token_xrc20(pcx()));
+        assert_ok!(Contracts::set_token_xrc777(
+            pcx(),
+            BOB,
+            vec![(XRC777Selector::Mint, [0, 0, 0, 1])]
+                .into_iter()
+                .collect(),
+        ));
+        assert_err!(
+            Contracts::issue_to_xrc20(pcx(), ALICE, 1_000, 100_000).map_err(|e| e.reason),
+            "fail decode wasm result to bool"
+        );
+        assert!(System::events().iter().any(|record| record.event
+            == MetaEvent::contract(RawEvent::Xrc777ResultDecodeFailed(
+                pcx(),
+                XRC777Selector::Mint,
+                vec![]
+            ))));
+    });
+}
+
+#[test]
+fn rent_status_should_work() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+
+        // alive
+        let info = ContractInfoOf::<Test>::get(BOB)
+            .unwrap()
+            .get_alive()
+            .unwrap();
+        assert_eq!(
+            Contracts::rent_status(BOB),
+            Some((info.rent_allowance, info.deduct_block, info.storage_size))
+        );
+
+        // tombstone
+        ContractInfoOf::<Test>::insert(
+            CHARLIE,
+            ContractInfo::Tombstone(crate::TombstoneContractInfo::<Test>::new(
+                &[],
+                H256::default(),
+            )),
+        );
+        assert_eq!(Contracts::rent_status(CHARLIE), None);
+
+        // missing
+        assert_eq!(Contracts::rent_status(DJANGO), None);
+    });
+}
+
+#[test]
+fn xrc20_selectors_should_work() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        assert_eq!(Contracts::xrc20_selectors(&pcx()), None);
+
+        let selectors = vec![
+            (XRC20Selector::BalanceOf, [1, 2, 3, 4]),
+            (XRC20Selector::TotalSupply, [5, 6, 7, 8]),
+            (XRC20Selector::Issue, [9, 10, 11, 12]),
+        ]
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+        Contracts::set_token_xrc20(pcx(), BOB, selectors.clone());
+        assert_eq!(Contracts::xrc20_selectors(&pcx()), Some(selectors));
+
+        assert_eq!(Contracts::xrc20_selectors(&b"XBTC".to_vec()), None);
+    });
+}
+
+#[test]
+fn export_then_import_contract_should_reproduce_it() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+    let (key1, key2, key3) = ([1; 32], [2; 32], [3; 32]);
+
+    let snapshot = with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_ok!(Contracts::put_code(
+            Origin::signed(ALICE),
+            100_000,
+            wasm.clone()
+        ));
+        assert!(Contracts::export_contract(BOB, vec![key1]).is_none());
+
+        ContractInfoOf::<Test>::insert(
+            BOB,
+            &ContractInfo::Alive(RawAliveContractInfo {
+                trie_id: <Test as Trait>::TrieIdGenerator::trie_id(&BOB),
+                storage_size: <Test as Trait>::StorageSizeOffset::get(),
+                deduct_block: System::block_number(),
+                code_hash: code_hash.into(),
+                rent_allowance: 40,
+                last_write: None,
+            }),
+        );
+        let mut overlay = OverlayAccountDb::<Test>::new(&DirectAccountDb);
+        overlay.set_storage(&BOB, key1, Some(b"1".to_vec()));
+        overlay.set_storage(&BOB, key2, Some(b"2".to_vec()));
+        DirectAccountDb.commit(overlay.into_change_set());
+
+        let snapshot = Contracts::export_contract(BOB, vec![key1, key2, key3]).unwrap();
+        assert_eq!(
+            snapshot.storage,
+            vec![(key1, b"1".to_vec()), (key2, b"2".to_vec())]
+        );
+        snapshot
+    });
+
+    // import on a fresh chain.
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_err!(
+            Contracts::import_contract(BOB, snapshot.clone()),
+            "The code of the snapshot is not stored"
+        );
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+
+        // the storage is imported in pages, the contract is created once it's complete.
+        let mut first = snapshot.clone();
+        let last = first.storage.pop().unwrap();
+        assert_ok!(Contracts::import_contract(BOB, first));
+        assert!(ContractInfoOf::<Test>::get(BOB).is_none());
+        assert!(Contracts::pending_contract_import(BOB).is_some());
+
+        let mut mismatched = snapshot.clone();
+        mismatched.rent_allowance += 1;
+        mismatched.storage = vec![last.clone()];
+        assert_err!(
+            Contracts::import_contract(BOB, mismatched),
+            "The snapshot doesn't match the pending import"
+        );
+
+        let mut second = snapshot.clone();
+        second.storage = vec![last];
+        assert_ok!(Contracts::import_contract(BOB, second));
+        assert!(Contracts::pending_contract_import(BOB).is_none());
+        assert_eq!(
+            Contracts::export_contract(BOB, vec![key1, key2, key3]),
+            Some(snapshot.clone())
+        );
+        assert_err!(
+            Contracts::import_contract(BOB, snapshot.clone()),
+            "Cannot import the snapshot to an existing contract"
+        );
+
+        // the whole snapshot in a page.
+        assert_ok!(Contracts::import_contract(CHARLIE, snapshot.clone()));
+        assert_eq!(
+            Contracts::export_contract(CHARLIE, vec![key1, key2, key3]).map(|s| s.storage),
+            Some(snapshot.storage)
+        );
+    });
+}
+
+#[test]
+fn call_tombstone_should_fail_with_specific_error() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        ContractInfoOf::<Test>::insert(
+            CHARLIE,
+            ContractInfo::Tombstone(crate::TombstoneContractInfo::<Test>::new(
+                &[],
+                H256::default(),
+            )),
+        );
+
+        assert_eq!(
+            Contracts::bare_call(ALICE, CHARLIE, 0, 100_000, vec![])
+                .err()
+                .map(|e| e.reason),
+            Some("contract is a tombstone")
+        );
+        assert_eq!(
+            System::events().last().unwrap().event,
+            MetaEvent::contract(RawEvent::CallToTombstone(ALICE, CHARLIE))
+        );
+        assert_err!(
+            Contracts::call(Origin::signed(ALICE), CHARLIE, 0, 100_000, vec![]),
+            "contract is a tombstone"
+        );
+
+        // missing contract is still reported as inexisting.
+        assert_err!(
+            Contracts::call(Origin::signed(ALICE), DJANGO, 0, 100_000, vec![]),
+            "unable to call dest contract as it does not exist"
+        );
+    });
+}
+
+#[test]
+fn estimate_put_code_gas_matches_consumed_gas() {
+    let (wasm, _code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        let estimated = Contracts::estimate_put_code_gas(wasm.len() as u32);
+        assert!(estimated > 0);
+
+        let free = XAssets::pcx_free_balance(&ALICE);
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        // the unused gas is refunded, the gas price is 2.
+        let consumed = (free - XAssets::pcx_free_balance(&ALICE)) / 2;
+        assert_eq!(consumed, estimated);
+    });
+}
+
+#[test]
+fn call_sponsored_refunds_to_configured_destination() {
+    use crate::GasRefundPolicy;
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000);
+        XAssets::pcx_issue(&DJANGO, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        assert_ok!(Contracts::set_sponsor(
+            Origin::signed(CHARLIE),
+            DJANGO,
+            BOB,
+            Some(100_000)
+        ));
+
+        // refund to the payer by default
+        assert_eq!(
+            Contracts::gas_refund_policy_of(&DJANGO),
+            GasRefundPolicy::Payer
+        );
+        let sponsor_free = XAssets::pcx_free_balance(&DJANGO);
+        assert_ok!(Contracts::call_sponsored(
+            Origin::signed(DJANGO),
+            BOB,
+            0,
+            100_000,
+            vec![],
+            CHARLIE
+        ));
+        let spent = sponsor_free - XAssets::pcx_free_balance(&DJANGO);
+        assert!(spent < 100_000 * 2);
+        assert_eq!(XAssets::pcx_free_balance(&CHARLIE), 1_000);
+
+        // refund to the beneficiary
+        assert_ok!(Contracts::set_gas_refund_policy(
+            Origin::signed(DJANGO),
+            GasRefundPolicy::Beneficiary
+        ));
+        let sponsor_free = XAssets::pcx_free_balance(&DJANGO);
+        assert_ok!(Contracts::call_sponsored(
+            Origin::signed(DJANGO),
+            BOB,
+            0,
+            100_000,
+            vec![],
+            CHARLIE
+        ));
+        // the sponsor pays the whole gas limit, and the unused part goes to the beneficiary.
+        assert_eq!(
+            sponsor_free - XAssets::pcx_free_balance(&DJANGO),
+            100_000 * 2
+        );
+        assert_eq!(
+            XAssets::pcx_free_balance(&CHARLIE),
+            1_000 + 100_000 * 2 - spent
+        );
+    });
+}
+
+#[test]
+fn decode_contract_event_should_work() {
+    use xr_primitives::{ContractEventFieldType, ContractEventFieldValue, ContractEventLayout};
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        let selector = [1, 2, 3, 4];
+        let layout = ContractEventLayout {
+            name: b"Transfer".to_vec(),
+            fields: vec![
+                (b"to".to_vec(), ContractEventFieldType::AccountId),
+                (b"value".to_vec(), ContractEventFieldType::U64),
+                (b"memo".to_vec(), ContractEventFieldType::Bytes),
+            ],
+        };
+        // only root or the contract owner could register the layout.
+        <super::ContractOwnerOf<Test>>::insert(BOB, ALICE);
+        assert_err!(
+            Contracts::set_contract_event_layout(
+                Origin::signed(CHARLIE),
+                BOB,
+                selector,
+                Some(layout.clone())
+            ),
+            "only the owner of the contract could set the event layout"
+        );
+        assert_ok!(Contracts::set_contract_event_layout(
+            Origin::signed(ALICE),
+            BOB,
+            selector,
+            Some(layout)
+        ));
+
+        let mut data = selector.to_vec();
+        data.extend(([7u8; 32], 100u64, b"hi".to_vec()).encode());
+        assert_eq!(
+            Contracts::decode_contract_event(BOB, data.clone()),
+            Some((
+                b"Transfer".to_vec(),
+                vec![
+                    (
+                        b"to".to_vec(),
+                        ContractEventFieldValue::AccountId([7u8; 32])
+                    ),
+                    (b"value".to_vec(), ContractEventFieldValue::U64(100)),
+                    (
+                        b"memo".to_vec(),
+                        ContractEventFieldValue::Bytes(b"hi".to_vec())
+                    ),
+                ]
+            ))
+        );
+
+        // the layout is registered for BOB only
+        assert_eq!(
+            Contracts::decode_contract_event(CHARLIE, data.clone()),
+            None
+        );
+        // unknown selector
+        let mut unknown = vec![4, 3, 2, 1];
+        unknown.extend_from_slice(&data[4..]);
+        assert_eq!(Contracts::decode_contract_event(BOB, unknown), None);
+        // mismatched payload
+        assert_eq!(
+            Contracts::decode_contract_event(BOB, data[..data.len() - 1].to_vec()),
+            None
+        );
+
+        assert_ok!(Contracts::set_contract_event_layout(
+            system::RawOrigin::Root.into(),
+            BOB,
+            selector,
+            None
+        ));
+        assert_eq!(Contracts::decode_contract_event(BOB, data), None);
+    });
+}
+
+#[test]
+fn update_schedule_below_limit_floors_should_fail() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        let mut schedule = Schedule::default();
+        schedule.version = 1;
+        schedule.max_table_size = 1024;
+        assert_err!(
+            Contracts::update_schedule(system::RawOrigin::Root.into(), schedule.clone()),
+            "max_table_size of the new schedule is below the floor"
+        );
+
+        // lower the floor then the schedule is acceptable
+        assert_ok!(Contracts::set_schedule_limit_floors(
+            crate::ScheduleLimitFloors {
+                max_table_size: 1024,
+                ..Default::default()
+            }
+        ));
+        assert_ok!(Contracts::update_schedule(
+            system::RawOrigin::Root.into(),
+            schedule
+        ));
+        assert_eq!(Contracts::current_schedule().max_table_size, 1024);
+    });
+}
+
+#[test]
+fn put_code_rate_limit_in_testnet() {
+    let (wasm, _) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+    let (wasm2, _) = compile_module::<Test>(CODE_RETURN_WITH_DATA).unwrap();
+    let (wasm3, _) = compile_module::<Test>(CODE_RETURN_FROM_START_FN).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        xsystem::NetworkProps::<Test>::put((xsystem::NetworkType::Testnet, 0));
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&BOB, 1_000_000);
+        assert_ok!(Contracts::set_put_code_rate_limit(1, 10));
+
+        System::initialize(&1, &[0u8; 32].into(), &[0u8; 32].into());
+        assert_ok!(Contracts::put_code(
+            Origin::signed(ALICE),
+            100_000,
+            wasm.clone()
+        ));
+        assert_err!(
+            Contracts::put_code(Origin::signed(ALICE), 100_000, wasm2.clone()),
+            "[put_code]|too many put_code from this account in the current window."
+        );
+        // other account is not affected, and the failed put_code is not counted
+        assert!(Contracts::put_code(Origin::signed(BOB), 100_000, wasm.clone()).is_err());
+        assert_eq!(Contracts::put_code_count_of(BOB), 0);
+        assert_ok!(Contracts::put_code(
+            Origin::signed(BOB),
+            100_000,
+            wasm3.clone()
+        ));
+        assert_eq!(Contracts::put_code_window(), (0, vec![ALICE, BOB]));
+
+        // a new window, the counts of the former window are removed
+        System::initialize(&11, &[0u8; 32].into(), &[0u8; 32].into());
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm2));
+        assert_eq!(Contracts::put_code_window(), (10, vec![ALICE]));
+        assert_eq!(Contracts::put_code_count_of(ALICE), 1);
+        assert_eq!(Contracts::put_code_count_of(BOB), 0);
+    });
+}
+
+#[test]
+fn call_limit_per_block_should_work() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        assert_eq!(Contracts::contract_owner_of(BOB), Some(ALICE));
+
+        // backfill the owner by root
+        assert_err!(
+            Contracts::set_contract_owner(DJANGO, Some(CHARLIE)),
+            "the contract does not exist"
+        );
+        assert_ok!(Contracts::set_contract_owner(BOB, None));
+        assert_eq!(Contracts::contract_owner_of(BOB), None);
+        assert_ok!(Contracts::set_contract_owner(BOB, Some(ALICE)));
+
+        assert_err!(
+            Contracts::set_call_limit(Origin::signed(CHARLIE), BOB, Some(2)),
+            "only the owner of the contract could set the call limit"
+        );
+        assert_ok!(Contracts::set_call_limit(
+            Origin::signed(ALICE),
+            BOB,
+            Some(2)
+        ));
+
+        System::initialize(&1, &[0u8; 32].into(), &[0u8; 32].into());
+        for _ in 0..2 {
+            assert_ok!(Contracts::call(
+                Origin::signed(CHARLIE),
+                BOB,
+                0,
+                100_000,
+                vec![]
+            ));
+        }
+        assert_err!(
+            Contracts::call(Origin::signed(CHARLIE), BOB, 0, 100_000, vec![]),
+            "too many calls to this contract in the current block"
+        );
+        assert_eq!(Contracts::call_count_of(BOB), 2);
+        assert_eq!(Contracts::called_contracts(), vec![BOB]);
+
+        // the counts are cleared on finalize
+        Contracts::on_finalize(1);
+        assert_eq!(Contracts::call_count_of(BOB), 0);
+        assert!(Contracts::called_contracts().is_empty());
+
+        System::initialize(&2, &[0u8; 32].into(), &[0u8; 32].into());
+        assert_ok!(Contracts::call(
+            Origin::signed(CHARLIE),
+            BOB,
+            0,
+            100_000,
+            vec![]
+        ));
+    });
+}
+
+#[test]
+fn gas_price_history_should_work() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        System::initialize(&5, &[0u8; 32].into(), &[0u8; 32].into());
+        assert_ok!(Contracts::set_gas_price(10));
+        assert_eq!(Contracts::gas_price_at(4), Some(2));
+        assert_eq!(Contracts::gas_price_at(5), Some(10));
+        // the later change in the same block doesn't override the history
+        assert_ok!(Contracts::set_gas_price(12));
+        assert_eq!(Contracts::gas_price_at(4), Some(2));
+        assert_eq!(Contracts::gas_price_at(6), None);
+
+        System::initialize(&8, &[0u8; 32].into(), &[0u8; 32].into());
+        assert_ok!(Contracts::set_gas_price(20));
+        assert_eq!(Contracts::gas_price_at(1), Some(2));
+        assert_eq!(Contracts::gas_price_at(6), Some(12));
+        assert_eq!(Contracts::gas_price_at(7), Some(12));
+        assert_eq!(Contracts::gas_price_at(8), Some(20));
+
+        // out of the window
+        assert_ok!(Contracts::set_gas_price_history_window(3));
+        assert_eq!(Contracts::gas_price_at(4), None);
+        assert_eq!(Contracts::gas_price_at(5), Some(12));
+
+        // the records out of the window are dropped on the next change
+        assert_eq!(Contracts::gas_price_history_head(), 0);
+        assert_eq!(Contracts::gas_price_history_tail(), 2);
+        System::initialize(&9, &[0u8; 32].into(), &[0u8; 32].into());
+        assert_ok!(Contracts::set_gas_price(25));
+        assert_eq!(Contracts::gas_price_history(0), None);
+        assert_eq!(Contracts::gas_price_history(1), Some((7, 12)));
+        assert_eq!(Contracts::gas_price_history(2), Some((8, 20)));
+        assert_eq!(Contracts::gas_price_history_head(), 1);
+        assert_eq!(Contracts::gas_price_history_tail(), 3);
+        assert_eq!(Contracts::gas_price_at(8), Some(20));
+        assert_eq!(Contracts::gas_price_at(9), Some(25));
+    });
+}
+
+#[test]
+fn min_put_code_gas_should_work() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_DISPATCH_CALL).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        assert_eq!(
+            Contracts::min_put_code_gas(&[0, 1, 2, 3]),
+            Err("Can't decode wasm code")
+        );
+
+        let min_gas = Contracts::min_put_code_gas(&wasm).unwrap();
+        assert!(min_gas > 0);
+
+        assert_err!(
+            Contracts::put_code(Origin::signed(ALICE), min_gas - 1, wasm.clone()),
+            "[put_code]|gas_limit is too low to store the code"
+        );
+        assert!(<super::PristineCode<Test>>::get(code_hash).is_none());
+
+        assert_ok!(Contracts::put_code(
+            Origin::signed(ALICE),
+            min_gas,
+            wasm.clone()
+        ));
+        assert!(<super::PristineCode<Test>>::get(code_hash).is_some());
+        assert_eq!(
+            Contracts::min_put_code_gas(&wasm),
+            Err("the code is already stored on chain")
+        );
+    });
+}
+
+#[test]
+fn contract_gas_consumed_should_accumulate() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        assert_eq!(Contracts::contract_gas_consumed(BOB), 0);
+
+        let mut expected = 0;
+        for _ in 0..3 {
+            let free = XAssets::pcx_free_balance(&CHARLIE);
+            assert_ok!(Contracts::call(
+                Origin::signed(CHARLIE),
+                BOB,
+                0,
+                100_000,
+                vec![]
+            ));
+            // the unused gas is refunded, the gas price is 2.
+            let consumed = (free - XAssets::pcx_free_balance(&CHARLIE)) / 2;
+            assert!(consumed > 0);
+            expected += consumed;
+            assert_eq!(Contracts::contract_gas_consumed(BOB), expected);
+        }
+    });
+}
+
+#[test]
+fn gas_fee_treasury_ratio_should_work() {
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&CHARLIE, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+
+        assert_err!(
+            Contracts::set_gas_fee_treasury_ratio(1_000_001),
+            "gas fee treasury ratio should not be greater than 1_000_000"
+        );
+        assert_ok!(Contracts::set_gas_fee_treasury_ratio(200_000));
+
+        let call_in_block = |n: u64| {
+            System::initialize(&n, &[0u8; 32].into(), &[0u8; 32].into());
+            let council = XAssets::pcx_free_balance(&COUNCIL);
+            let total = XAssets::pcx_total_balance();
+            let free = XAssets::pcx_free_balance(&CHARLIE);
+            assert_ok!(Contracts::call(
+                Origin::signed(CHARLIE),
+                BOB,
+                0,
+                100_000,
+                vec![]
+            ));
+            Contracts::on_finalize(n);
+
+            let fee = free - XAssets::pcx_free_balance(&CHARLIE);
+            let for_council = XAssets::pcx_free_balance(&COUNCIL) - council;
+            let burned = total - XAssets::pcx_total_balance();
+            (fee, for_council, burned)
+        };
+
+        let (fee, for_council, burned) = call_in_block(1);
+        // the fee is what the block spent at the gas price
+        assert_eq!(fee, Contracts::last_gas_spent() * Contracts::gas_price());
+        assert!(fee > 0);
+        assert_eq!(for_council, fee * 200_000 / 1_000_000);
+        assert_eq!(burned, fee - for_council);
+
+        // all burned
+        assert_ok!(Contracts::set_gas_fee_treasury_ratio(0));
+        let (fee, for_council, burned) = call_in_block(2);
+        assert!(fee > 0);
+        assert_eq!(for_council, 0);
+        assert_eq!(burned, fee);
+
+        // all to the council
+        assert_ok!(Contracts::set_gas_fee_treasury_ratio(1_000_000));
+        let (fee, for_council, burned) = call_in_block(3);
+        assert!(fee > 0);
+        assert_eq!(for_council, fee);
+        assert_eq!(burned, 0);
+    });
+}
+
+#[test]
+fn surcharge_reward_ratio_should_work() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        assert_err!(
+            Contracts::set_surcharge_reward_ratio(1_000_001),
+            "surcharge reward ratio should not be greater than 1_000_000"
+        );
+
+        // the reward is paid out of the council.
+        assert_ok!(XAssets::pcx_issue(&COUNCIL, 1_500));
+
+        // all to the claimer by default.
+        let producer_before = XAssets::pcx_free_balance(&DJANGO);
+        crate::rent::pay_surcharge_reward::<Test>(&DJANGO, 1_000);
+        assert_eq!(XAssets::pcx_free_balance(&DJANGO), producer_before + 1_000);
+        assert_eq!(XAssets::pcx_free_balance(&COUNCIL), 500);
+
+        assert_ok!(Contracts::set_surcharge_reward_ratio(300_000));
+
+        // inherent claim, the block producer is rewarded, the rest is kept by the council.
+        crate::rent::pay_surcharge_reward::<Test>(&DJANGO, 1_000);
+        assert_eq!(
+            XAssets::pcx_free_balance(&DJANGO),
+            producer_before + 1_000 + 300
+        );
+        assert_eq!(XAssets::pcx_free_balance(&COUNCIL), 200);
+
+        // signed claim, the user is rewarded, capped at the 200 left in the council.
+        let free = XAssets::pcx_free_balance(&CHARLIE);
+        crate::rent::pay_surcharge_reward::<Test>(&CHARLIE, 1_000);
+        assert_eq!(XAssets::pcx_free_balance(&CHARLIE), free + 200);
+        assert_eq!(XAssets::pcx_free_balance(&COUNCIL), 0);
+    });
+}
+
+#[test]
+fn query_xrc20_balances_should_match_individual_queries() {
+    use crate::{XRC20Selector, MAX_XRC20_BALANCE_HOLDERS};
+
+    // the balance of a holder is the holder itself.
+    let (wasm, code_hash) = compile_module::<Test>(CODE_RETURN_WITH_DATA).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 10_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![0, 0, 0, 0],
+        ));
+
+        let holders = vec![ALICE, CHARLIE, DJANGO, 42];
+        // no xrc20 instance yet
+        assert!(
+            Contracts::query_xrc20_balances(pcx(), ALICE, 100_000, holders.clone())
+                .unwrap()
+                .iter()
+                .all(
+                    |r| r.as_ref().map_err(|e| e.reason) == Err("no xrc20 instance for this token")
+                )
+        );
+
+        // the xrc20 instance is BOB
+        Contracts::set_token_xrc20(
+            pcx(),
+            BOB,
+            vec![(XRC20Selector::BalanceOf, [0, 0, 0, 0])]
+                .into_iter()
+                .collect(),
+        );
+
+        let individual = holders
+            .iter()
+            .map(|holder| {
+                let output = Contracts::call_xrc20(
+                    pcx(),
+                    ALICE,
+                    100_000,
+                    XRC20Selector::BalanceOf,
+                    holder.encode(),
+                )
+                .unwrap();
+                u64::decode(&mut output.data.as_slice()).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(individual, holders);
+
+        let batch = Contracts::query_xrc20_balances(pcx(), ALICE, 100_000, holders)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(batch, individual);
+
+        let at_most = vec![ALICE; MAX_XRC20_BALANCE_HOLDERS];
+        assert_eq!(
+            Contracts::query_xrc20_balances(pcx(), ALICE, 100_000, at_most)
+                .unwrap()
+                .len(),
+            MAX_XRC20_BALANCE_HOLDERS
+        );
+        let too_many = vec![ALICE; MAX_XRC20_BALANCE_HOLDERS + 1];
+        assert_eq!(
+            Contracts::query_xrc20_balances(pcx(), ALICE, 100_000, too_many).err(),
+            Some("too many holders in a query")
+        );
+    });
+}
+
+#[test]
+fn xrc20_balance_conserved_across_round_trip() {
+    use crate::XRC20Selector;
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        // the xrc20 instance is BOB
+        Contracts::set_token_xrc20(
+            pcx(),
+            BOB,
+            vec![(XRC20Selector::Issue, [0, 0, 0, 1])]
+                .into_iter()
+                .collect(),
+        );
+
+        let total = Contracts::xrc20_convertible_total(&pcx());
+        assert!(Contracts::xrc20_balance_conserved(&pcx(), total));
+
+        assert_ok!(Contracts::convert_to_xrc20(
+            Origin::signed(ALICE),
+            pcx(),
+            1_000,
+            100_000
+        ));
+        assert_eq!(Contracts::total_xrc20_reserved(&pcx()), 1_000);
+        assert!(Contracts::xrc20_balance_conserved(&pcx(), total));
+
+        let free = XAssets::pcx_free_balance(&ALICE);
+        assert_ok!(Contracts::convert_to_asset(
+            Origin::signed(BOB),
+            ALICE,
+            1_000
+        ));
+        assert_eq!(Contracts::total_xrc20_reserved(&pcx()), 0);
+        assert_eq!(XAssets::pcx_free_balance(&ALICE), free + 1_000);
+        assert!(Contracts::xrc20_balance_conserved(&pcx(), total));
+        assert!(!Contracts::xrc20_balance_conserved(&pcx(), total + 1));
+    });
+}
+
+#[test]
+fn xrc777_issue_and_refund_round_trip() {
+    use crate::{TokenContractKind, XRC20Selector, XRC777Selector};
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+        XAssets::pcx_issue(&COUNCIL, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        // the xrc777 instance is BOB
+        assert_ok!(Contracts::set_token_xrc777(
+            pcx(),
+            BOB,
+            vec![(XRC777Selector::Mint, [0, 0, 0, 1])]
+                .into_iter()
+                .collect(),
+        ));
+        assert_eq!(
+            Contracts::token_contract_of(&pcx()),
+            Some((TokenContractKind::XRC777, BOB))
+        );
+        // a token is bound to one kind of contract only.
+        assert_err!(
+            Contracts::set_token_xrc20(
+                pcx(),
+                BOB,
+                vec![(XRC20Selector::Issue, [0, 0, 0, 1])]
+                    .into_iter()
+                    .collect(),
+            ),
+            "token is already bound to an xrc777 instance"
+        );
+
+        let total = Contracts::xrc20_convertible_total(&pcx());
+        assert_ok!(Contracts::convert_to_xrc20(
+            Origin::signed(ALICE),
+            pcx(),
+            1_000,
+            100_000
+        ));
+        assert_eq!(Contracts::total_xrc20_reserved(&pcx()), 1_000);
+        assert!(Contracts::xrc20_balance_conserved(&pcx(), total));
+
+        let free = XAssets::pcx_free_balance(&ALICE);
+        assert_ok!(Contracts::convert_to_asset(
+            Origin::signed(BOB),
+            ALICE,
+            1_000
+        ));
+        assert_eq!(Contracts::total_xrc20_reserved(&pcx()), 0);
+        assert_eq!(XAssets::pcx_free_balance(&ALICE), free + 1_000);
+        assert!(Contracts::xrc20_balance_conserved(&pcx(), total));
+
+        assert_ok!(Contracts::remove_token_xrc777(pcx()));
+        assert_eq!(Contracts::token_contract_of(&pcx()), None);
+        assert_eq!(Contracts::token_of_addr(&BOB), None);
+        assert_err!(
+            Contracts::convert_to_xrc20(Origin::signed(ALICE), pcx(), 1_000, 100_000),
+            "no token contract instance for this token"
+        );
+    });
+}
+
+#[test]
+fn convert_to_asset_only_from_registered_xrc20() {
+    use crate::XRC20Selector;
+
+    let (wasm, code_hash) = compile_module::<Test>(CODE_XRC20_ISSUE_BURN_GAS).unwrap();
+
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        XAssets::pcx_issue(&ALICE, 1_000_000);
+
+        assert_ok!(Contracts::put_code(Origin::signed(ALICE), 100_000, wasm));
+        assert_ok!(Contracts::instantiate(
+            Origin::signed(ALICE),
+            100,
+            100_000,
+            code_hash.into(),
+            vec![],
+        ));
+        let selectors = vec![(XRC20Selector::Issue, [0, 0, 0, 1])]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        // the xrc20 instance is BOB
+        Contracts::set_token_xrc20(pcx(), BOB, selectors.clone());
+        assert_ok!(Contracts::convert_to_xrc20(
+            Origin::signed(ALICE),
+            pcx(),
+            1_000,
+            100_000
+        ));
+
+        // unregistered origin
+        assert_err!(
+            Contracts::convert_to_asset(Origin::signed(CHARLIE), ALICE, 1_000),
+            "no token for this contract address"
+        );
+        // an account which is not a contract is registered by mistake
+        Contracts::set_token_xrc20(pcx(), CHARLIE, selectors.clone());
+        assert_err!(
+            Contracts::convert_to_asset(Origin::signed(CHARLIE), ALICE, 1_000),
+            "only the registered token contract could convert to asset"
+        );
+        // the stale binding of BOB is no longer the registered one
+        assert_err!(
+            Contracts::convert_to_asset(Origin::signed(BOB), ALICE, 1_000),
+            "only the registered token contract could convert to asset"
+        );
+
+        Contracts::set_token_xrc20(pcx(), BOB, selectors);
+        let free = XAssets::pcx_free_balance(&ALICE);
+        assert_ok!(Contracts::convert_to_asset(
+            Origin::signed(BOB),
+            ALICE,
+            1_000
+        ));
+        assert_eq!(XAssets::pcx_free_balance(&ALICE), free + 1_000);
+    });
+}
+
+#[test]
+fn gas_price_bounds_should_work() {
+    with_externalities(&mut ExtBuilder::default().build(), || {
+        assert_eq!(Contracts::gas_price_bounds(), (1, 1_000_000));
+        assert_err!(
+            Contracts::set_gas_price(0),
+            "gas price is out of the bounds"
+        );
+        assert_err!(
+            Contracts::set_gas_price(1_000_001),
+            "gas price is out of the bounds"
+        );
+        assert_ok!(Contracts::set_gas_price(1_000_000));
+        assert_eq!(Contracts::gas_price(), 1_000_000);
+
+        assert_err!(
+            Contracts::set_gas_price_bounds(0, 10),
+            "gas price floor should be greater than zero"
+        );
+        assert_err!(
+            Contracts::set_gas_price_bounds(11, 10),
+            "gas price floor should not be greater than the ceiling"
+        );
+        assert_ok!(Contracts::set_gas_price_bounds(2, 10));
+        assert_err!(
+            Contracts::set_gas_price(1),
+            "gas price is out of the bounds"
+        );
+        assert_err!(
+            Contracts::set_gas_price(11),
+            "gas price is out of the bounds"
+        );
+        assert_ok!(Contracts::set_gas_price(10));
+        assert_eq!(Contracts::gas_price(), 10);
+    });
+}
@@ -51,6 +51,24 @@ impl<T: Trait> Token<T> for PutCodeToken {
     }
 }
 
+/// The least gas that `save` would charge for storing code of the given length.
+pub fn save_gas_floor<T: Trait>(code_len: usize, schedule: &Schedule) -> Gas {
+    <PutCodeToken as Token<T>>::calculate_amount(&PutCodeToken(code_len as u32), schedule)
+}
+
+/// The exact gas that `save` would charge for storing the given code, or the error `save`
+/// would fail with no matter how much gas is given.
+pub fn save_gas<T: Trait>(original_code: &[u8], schedule: &Schedule) -> Result<Gas, &'static str> {
+    let code_hash = T::Hashing::hash(original_code);
+    if <PristineCode<T>>::exists(code_hash) {
+        return Err("the code is already stored on chain");
+    }
+
+    prepare::prepare_contract::<Env>(original_code, schedule)?;
+
+    Ok(save_gas_floor::<T>(original_code.len(), schedule))
+}
+
 /// Put code in the storage. The hash of code is used as a key and is returned
 /// as a result of this function.
 ///
@@ -36,6 +36,8 @@ use self::code_cache::load as load_code;
 use self::runtime::{to_execution_result, Runtime};
 
 pub use self::code_cache::save as save_code;
+pub use self::code_cache::save_gas as save_code_gas;
+pub use self::code_cache::save_gas_floor as save_code_gas_floor;
 
 /// A prepared wasm module ready for execution.
 #[derive(Clone, Encode, Decode)]
@@ -102,6 +102,7 @@ impl xassets::Trait for Test {
     type Event = ();
     type Balance = u64;
     type OnNewAccount = Indices;
+    type OnFreeBalanceZero = ();
     type OnAssetChanged = ();
     type OnAssetRegisterOrRevoke = ();
     type DetermineTokenJackpotAccountId = ();
@@ -24,6 +24,16 @@ use xsupport::{info, trace, warn};
 
 pub use self::types::CallSwitcher;
 
+/// The max percent of the claim fee discount, so that a claim is never free.
+pub const MAX_CLAIM_FEE_DISCOUNT_PERCENT: u32 = 90;
+
+/// Complete the stored switcher map with all the known switchers, the absent ones are closed.
+pub fn switch_store(switcher: &BTreeMap<CallSwitcher, bool>) -> BTreeMap<CallSwitcher, bool> {
+    CallSwitcher::iterator()
+        .map(|s| (s.clone(), switcher.get(s).copied().unwrap_or(false)))
+        .collect()
+}
+
 /// Simple payment making trait, operating on a single generic `AccountId` type.
 pub trait MakePayment<AccountId> {
     /// Make some sort of payment concerning `who` for an extrinsic (transaction) of encoded length
@@ -102,6 +112,18 @@ decl_module! {
             });
         }
 
+        /// Set the claim fee discount, (threshold of the reserved staking balance, discount percent),
+        /// `None` for no discount.
+        fn set_claim_fee_discount(discount: Option<(T::Balance, u32)>) -> Result {
+            if let Some((_, percent)) = discount {
+                if percent > MAX_CLAIM_FEE_DISCOUNT_PERCENT {
+                    return Err("the discount percent should not be larger than 90");
+                }
+            }
+            <ClaimFeeDiscount<T>>::put(discount);
+            Ok(())
+        }
+
         // due history reasons, can't export call in runtime for module `XAccounts`,
         // thus let `XAccounts` root call in `XFeeManager` module
         // blocked_accounts
@@ -136,6 +158,9 @@ decl_storage! {
         pub TransactionBaseFee get(transaction_base_fee) config(): T::Balance;
         /// The fee to be paid for making a transaction; the per-byte portion.
         pub TransactionByteFee get(transaction_byte_fee) config(): T::Balance;
+        /// The fee power of claim is discounted by the percent when the reserved staking balance
+        /// of the caller reaches the threshold, (threshold, percent).
+        pub ClaimFeeDiscount get(claim_fee_discount): Option<(T::Balance, u32)>;
     }
     add_extra_genesis {
         build(|_: &mut primitives::StorageOverlay, _: &mut primitives::ChildrenStorageOverlay, config: &GenesisConfig<T>| {
@@ -173,11 +198,35 @@ impl<T: Trait> Module<T> {
         Self::switcher().get(&switcher).copied().unwrap_or(false)
     }
 
+    /// The current open/close state of every switcher.
+    pub fn current_switch_store() -> BTreeMap<CallSwitcher, bool> {
+        switch_store(&Self::switcher())
+    }
+
     pub fn transaction_fee(power: u64, encoded_len: u64) -> T::Balance {
         Self::transaction_base_fee() * power.into()
             + Self::transaction_byte_fee() * encoded_len.into()
     }
 
+    /// The fee power of a claim for `who` after the staking discount.
+    pub fn claim_fee_power(who: &T::AccountId, power: u64) -> u64 {
+        match Self::claim_fee_discount() {
+            Some((threshold, percent)) => {
+                let staking = xassets::Module::<T>::pcx_type_balance(
+                    who,
+                    xassets::AssetType::ReservedStaking,
+                );
+                if staking >= threshold {
+                    let percent = percent.min(MAX_CLAIM_FEE_DISCOUNT_PERCENT);
+                    (power * u64::from(100 - percent) / 100).max(1)
+                } else {
+                    power
+                }
+            }
+            None => power,
+        }
+    }
+
     fn calc_fee_and_check(
         transactor: &T::AccountId,
         encoded_len: usize,
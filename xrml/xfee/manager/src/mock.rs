@@ -38,6 +38,11 @@ impl consensus::Trait for Test {
     type InherentOfflineReport = ();
 }
 
+impl timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+}
+
 impl xsystem::Trait for Test {
     type ValidatorList = MockValidatorList;
     type Validator = MockValidator;
@@ -80,6 +85,7 @@ impl xaccounts::IntentionJackpotAccountIdFor<u64> for MockDeterminator {
 impl xassets::Trait for Test {
     type Balance = u64;
     type OnNewAccount = ();
+    type OnFreeBalanceZero = ();
     type Event = ();
     type OnAssetChanged = ();
     type OnAssetRegisterOrRevoke = ();
@@ -6,7 +6,7 @@ use super::mock::*;
 use super::*;
 
 use runtime_io::with_externalities;
-use support::assert_ok;
+use support::{assert_err, assert_ok};
 
 #[test]
 fn test_fee() {
@@ -53,3 +53,68 @@ fn test_fee_not_divisible() {
         assert_eq!(XAssets::pcx_free_balance(&1000), fee * 9 / 10 + 1); // 111 * 9 / 10 = 99 + 1 = 100
     });
 }
+
+#[test]
+fn test_switch_store() {
+    with_externalities(&mut new_test_ext(), || {
+        let store = XFeeManager::current_switch_store();
+        assert_eq!(store.len(), CallSwitcher::iterator().count());
+        assert!(store.values().all(|open| !open));
+
+        assert_ok!(XFeeManager::modify_switcher(CallSwitcher::XBTC, true));
+        assert_ok!(XFeeManager::modify_switcher(CallSwitcher::Spot, true));
+        assert_ok!(XFeeManager::modify_switcher(CallSwitcher::Spot, false));
+
+        let store = XFeeManager::current_switch_store();
+        for switcher in CallSwitcher::iterator() {
+            assert_eq!(store[switcher], XFeeManager::get_switcher(switcher.clone()));
+        }
+        assert!(store[&CallSwitcher::XBTC]);
+        assert!(!store[&CallSwitcher::Spot]);
+        assert_eq!(store, switch_store(&XFeeManager::switcher()));
+    });
+}
+
+#[test]
+fn test_claim_fee_discount() {
+    with_externalities(&mut new_test_ext(), || {
+        // no discount by default
+        assert_eq!(XFeeManager::claim_fee_power(&1, 10), 10);
+
+        XAssets::pcx_move_balance(
+            &1,
+            xassets::AssetType::Free,
+            &1,
+            xassets::AssetType::ReservedStaking,
+            500,
+        )
+        .unwrap();
+        XAssets::pcx_move_balance(
+            &2,
+            xassets::AssetType::Free,
+            &2,
+            xassets::AssetType::ReservedStaking,
+            100,
+        )
+        .unwrap();
+
+        assert_err!(
+            XFeeManager::set_claim_fee_discount(Some((500, 100))),
+            "the discount percent should not be larger than 90"
+        );
+        assert_ok!(XFeeManager::set_claim_fee_discount(Some((500, 40))));
+
+        // heavily-staked
+        assert_eq!(XFeeManager::claim_fee_power(&1, 10), 6);
+        // lightly-staked
+        assert_eq!(XFeeManager::claim_fee_power(&2, 10), 10);
+
+        // the claim is never free even with the max discount.
+        assert_ok!(XFeeManager::set_claim_fee_discount(Some((
+            500,
+            MAX_CLAIM_FEE_DISCOUNT_PERCENT
+        ))));
+        assert_eq!(XFeeManager::claim_fee_power(&1, 10), 1);
+        assert_eq!(XFeeManager::claim_fee_power(&1, 3), 1);
+    });
+}
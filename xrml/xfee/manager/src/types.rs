@@ -1,6 +1,7 @@
 // Copyright 2018-2019 Chainpool.
 
 use parity_codec::{Decode, Encode};
+use rstd::slice::Iter;
 
 #[derive(PartialEq, PartialOrd, Ord, Eq, Clone, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(Debug))]
@@ -14,3 +15,19 @@ pub enum CallSwitcher {
     XMiningStaking,
     XMiningTokens,
 }
+
+impl CallSwitcher {
+    pub fn iterator() -> Iter<'static, CallSwitcher> {
+        static SWITCHERS: &[CallSwitcher] = &[
+            CallSwitcher::Global,
+            CallSwitcher::Spot,
+            CallSwitcher::XBTC,
+            CallSwitcher::XBTCLockup,
+            CallSwitcher::SDOT,
+            CallSwitcher::XContracts,
+            CallSwitcher::XMiningStaking,
+            CallSwitcher::XMiningTokens,
+        ];
+        SWITCHERS.iter()
+    }
+}
@@ -45,21 +45,37 @@ impl<T: Trait> ComputeWeight<T::AccountId> for Module<T> {
 impl<T: Trait> Claim<T::AccountId, T::Balance> for Module<T> {
     type Claimee = T::AccountId;
 
+    /// The dividend of the intention claimed by itself is split among its configured
+    /// destinations, the rest goes to the intention itself.
     fn allocate_dividend(
         claimer: &T::AccountId,
-        _claimee: &Self::Claimee,
+        claimee: &Self::Claimee,
         claimee_jackpot: &T::AccountId,
         dividend: T::Balance,
     ) -> Result {
-        xassets::Module::<T>::pcx_move_free_balance(claimee_jackpot, claimer, dividend)
+        let move_dividend = |dest: &T::AccountId, value: T::Balance| {
+            xassets::Module::<T>::pcx_move_free_balance(claimee_jackpot, dest, value)
                     .map_err(|e| {
                         error!(
                             "[allocate staker dividend] fail to move {:?} from jackpot_addr to some nominator as current jackpot_balance is not sufficient: {:?}",
-                            dividend,
+                            value,
                             xassets::Module::<T>::pcx_free_balance(claimee_jackpot),
                         );
                         e.info()
                     })
+        };
+
+        if claimer != claimee {
+            return move_dividend(claimer, dividend);
+        }
+
+        let mut remainder = dividend;
+        for (dest, ratio) in Self::reward_split_of(claimer) {
+            let share = ratio * dividend;
+            move_dividend(&dest, share)?;
+            remainder -= share;
+        }
+        move_dividend(claimer, remainder)
     }
 
     fn claim(claimer: &T::AccountId, claimee: &Self::Claimee) -> Result {
@@ -22,6 +22,7 @@ use parity_codec::Compact;
 
 // Substrate
 use primitives::traits::{Lookup, SaturatedConversion, StaticLookup, Zero};
+use primitives::Permill;
 use rstd::prelude::*;
 use rstd::result;
 use support::{
@@ -37,7 +38,7 @@ use xr_primitives::{Name, XString, URL};
 use xsession::SessionKeyUsability;
 #[cfg(feature = "std")]
 use xsupport::who;
-use xsupport::{debug, error, info};
+use xsupport::{debug, error, info, warn};
 
 pub use self::traits::*;
 pub use self::types::*;
@@ -45,6 +46,7 @@ pub use self::vote_weight::*;
 
 const DEFAULT_MINIMUM_VALIDATOR_COUNT: u32 = 4;
 const SESSIONS_PER_ROUND: u64 = 210_000;
+const MAX_REWARD_SPLIT_DESTINATIONS: usize = 16;
 
 pub trait Trait: xsystem::Trait + xsession::Trait + xassets::Trait {
     /// The overarching event type.
@@ -75,18 +77,33 @@ decl_module! {
             let target = system::ChainContext::<T>::default().lookup(target)?;
 
             xassets::is_valid_memo::<T>(&memo)?;
-            ensure!(!value.is_zero(), "Cannot nominate zero.");
-            ensure!(
+            Self::ensure_nomination(
+                !value.is_zero(),
+                &who,
+                &target,
+                NominationRejectReason::ZeroValue,
+                "Cannot nominate zero."
+            )?;
+            Self::ensure_nomination(
                 Self::is_intention(&target),
+                &who,
+                &target,
+                NominationRejectReason::NotIntention,
                 "Cannot nominate a non-intention."
-            );
-            ensure!(
+            )?;
+            Self::ensure_nomination(
                 value <= <xassets::Module<T>>::pcx_free_balance(&who),
+                &who,
+                &target,
+                NominationRejectReason::InsufficientBalance,
                 "Cannot nominate if greater than your avaliable free balance."
-            );
+            )?;
 
             if !Self::is_nominating_intention_itself(&who, &target) {
-                Self::wont_reach_upper_bound(&target, value)?;
+                Self::wont_reach_upper_bound(&target, value)
+                    .or_else(|e| Self::reject_nomination(&who, &target, NominationRejectReason::UpperBoundReached, e))?;
+                Self::wont_exceed_nomination_cap(&target, value)
+                    .or_else(|e| Self::reject_nomination(&who, &target, NominationRejectReason::NominationCapReached, e))?;
             }
 
             Self::apply_nominate(&who, &target, value)?;
@@ -106,32 +123,61 @@ decl_module! {
             let to = context.lookup(to)?;
 
             xassets::is_valid_memo::<T>(&memo)?;
-            ensure!(!value.is_zero(), "Cannot renominate zero.");
-            if !Self::is_intention(&from) || !Self::is_intention(&to) {
-                return Err("Cannot renominate against non-inentions.")
-            }
+            Self::ensure_nomination(
+                !value.is_zero(),
+                &who,
+                &to,
+                NominationRejectReason::ZeroValue,
+                "Cannot renominate zero."
+            )?;
+            Self::ensure_nomination(
+                Self::is_intention(&from) && Self::is_intention(&to),
+                &who,
+                &to,
+                NominationRejectReason::NotIntention,
+                "Cannot renominate against non-inentions."
+            )?;
 
             let key = (who.clone(), from.clone());
-            ensure!(
+            Self::ensure_nomination(
                 Self::nomination_record_exists(&key),
+                &who,
+                &to,
+                NominationRejectReason::NotNominee,
                 "Cannot renominate if the from party is not your nominee."
-            );
-            if Self::is_intention(&who) && who == from {
-                return Err("Cannot renominate the intention self-bonded.");
-            }
-            ensure!(
+            )?;
+            Self::ensure_nomination(
+                !(Self::is_intention(&who) && who == from),
+                &who,
+                &to,
+                NominationRejectReason::SelfBonded,
+                "Cannot renominate the intention self-bonded."
+            )?;
+            Self::ensure_nomination(
                 value <= Self::revokable_of(&key),
+                &who,
+                &to,
+                NominationRejectReason::ExceedRevokable,
                 "Cannot renominate if greater than your current nomination."
-            );
+            )?;
 
             if !Self::is_nominating_intention_itself(&who, &to) {
-                Self::wont_reach_upper_bound(&to, value)?;
+                Self::wont_reach_upper_bound(&to, value)
+                    .or_else(|e| Self::reject_nomination(&who, &to, NominationRejectReason::UpperBoundReached, e))?;
+                Self::wont_exceed_nomination_cap(&to, value)
+                    .or_else(|e| Self::reject_nomination(&who, &to, NominationRejectReason::NominationCapReached, e))?;
             }
 
             let bonding_duration = Self::bonding_duration();
             let current_block = <system::Module<T>>::block_number();
             if let Some(last_renomination) = Self::last_renomination_of(&who) {
-                ensure!(current_block > last_renomination + bonding_duration, "Cannot renominate if your last renomination is not expired.");
+                Self::ensure_nomination(
+                    current_block > last_renomination + bonding_duration,
+                    &who,
+                    &to,
+                    NominationRejectReason::RenominationNotExpired,
+                    "Cannot renominate if your last renomination is not expired."
+                )?;
             }
 
             Self::apply_renominate(&who, &from, &to, value, current_block)?;
@@ -148,22 +194,62 @@ decl_module! {
             let target = system::ChainContext::<T>::default().lookup(target)?;
 
             xassets::is_valid_memo::<T>(&memo)?;
-            ensure!(!value.is_zero(), "Cannot unnominate zero.");
-            ensure!(Self::is_intention(&target), "Cannot unnominate against non-intention.");
+            Self::ensure_nomination(
+                !value.is_zero(),
+                &who,
+                &target,
+                NominationRejectReason::ZeroValue,
+                "Cannot unnominate zero."
+            )?;
+            Self::ensure_nomination(
+                Self::is_intention(&target),
+                &who,
+                &target,
+                NominationRejectReason::NotIntention,
+                "Cannot unnominate against non-intention."
+            )?;
 
             let key = (who.clone(), target.clone());
-            ensure!(
+            Self::ensure_nomination(
                 Self::nomination_record_exists(&key),
+                &who,
+                &target,
+                NominationRejectReason::NotNominee,
                 "Cannot unnominate if target is not your nominee."
-            );
-            ensure!(
+            )?;
+            Self::ensure_nomination(
                 value <= Self::revokable_of(&key),
+                &who,
+                &target,
+                NominationRejectReason::ExceedRevokable,
                 "Cannot unnominate if greater than your revokable nomination."
-            );
-            ensure!(
+            )?;
+            Self::ensure_nomination(
                 Self::revocations_of(&key).len() < Self::max_unbond_entries_per_intention() as usize,
+                &who,
+                &target,
+                NominationRejectReason::UnbondEntriesLimitReached,
                 "Cannot unnomiate if the limit of max unbond entries is reached."
-            );
+            )?;
+
+            if Self::auto_claim_on_unnominate(&who) {
+                let current_block = <system::Module<T>>::block_number().saturated_into::<u64>();
+                // Nothing to claim if the vote weight is zero, e.g., already claimed in this block.
+                let (vote_weight, _) = <Self as ComputeWeight<T::AccountId>>::settle_claimer_weight(
+                    &who,
+                    &target,
+                    current_block
+                );
+                if vote_weight > 0 {
+                    debug!(target: "claim", "[unnominate claim] who: {:?}, target: {:?}", who, who!(target));
+                    if let Err(e) = <Self as Claim<T::AccountId, T::Balance>>::claim(&who, &target) {
+                        warn!(
+                            "[unnominate]|fail to claim the dividend, skip it|who:{:?}|target:{:?}|err:{:}",
+                            who, who!(target), e
+                        );
+                    }
+                }
+            }
 
             Self::apply_unnominate(&who, &target, value)?;
         }
@@ -228,7 +314,7 @@ decl_module! {
                 }
             }
 
-            Self::deposit_event(RawEvent::Unfreeze(who, target));
+            Self::deposit_event(RawEvent::Unfreeze(who, target, value));
         }
 
         /// Update the url, desire to join in elections of intention and session key.
@@ -278,17 +364,52 @@ decl_module! {
             Self::apply_refresh(&who, url, desire_to_run, next_key, about);
         }
 
-        /// Register to be an intention.
-        fn register(origin, name: Name) {
+        /// Whether to be forced inactive and kicked out of the validator set when the jackpot can't
+        /// afford the offline penalty, the offline slashing is applied anyway.
+        fn set_auto_kick(origin, auto_kick: bool) {
             let who = ensure_signed(origin)?;
+            ensure!(Self::is_intention(&who), "Cannot set auto kick if transactor is not an intention.");
 
-            xaccounts::is_valid_name(&name)?;
+            if auto_kick {
+                <AutoKickDisabled<T>>::remove(&who);
+            } else {
+                <AutoKickDisabled<T>>::insert(&who, true);
+            }
+        }
 
-            ensure!(!Self::is_intention(&who), "Cannot register if transactor is an intention already.");
-            ensure!(!Self::name_exists(&name), "This name has already been taken.");
-            ensure!(Self::intention_set().len() < Self::maximum_intention_count() as usize, "Cannot register if there are already too many intentions");
+        /// Send a portion of the dividend the intention claims from its own jackpot to the given
+        /// destinations, the rest is kept by the intention itself. An empty `split` removes the
+        /// configuration.
+        fn set_reward_split(origin, split: Vec<(T::AccountId, Permill)>) -> Result {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::is_intention(&who), "Cannot set reward split if transactor is not an intention.");
+
+            Self::check_reward_split(&split)?;
 
-            Self::apply_register(&who, name)?;
+            if split.is_empty() {
+                <RewardSplitOf<T>>::remove(&who);
+            } else {
+                <RewardSplitOf<T>>::insert(&who, split);
+            }
+            Ok(())
+        }
+
+        /// Whether to claim your accrued dividend automatically on `unnominate`, a failed claim
+        /// is skipped and the unnomination goes on.
+        fn set_auto_claim_on_unnominate(origin, enabled: bool) {
+            let who = ensure_signed(origin)?;
+
+            if enabled {
+                <AutoClaimOnUnnominate<T>>::insert(&who, true);
+            } else {
+                <AutoClaimOnUnnominate<T>>::remove(&who);
+            }
+        }
+
+        /// Register to be an intention.
+        fn register(origin, name: Name) {
+            let who = ensure_signed(origin)?;
+            Self::check_and_apply_register(&who, name)?;
         }
 
         /// Set the number of sessions in an era.
@@ -313,6 +434,12 @@ decl_module! {
             <MissedBlockSeverity<T>>::put(new);
         }
 
+        /// Set the number of offline reports tolerated before slashing.
+        fn set_offline_grace_period(new: Compact<u32>) {
+            let new: u32 = new.into();
+            <OfflineGracePeriod<T>>::put(new);
+        }
+
         /// The maximum number of intentions.
         fn set_maximum_intention_count(new: Compact<u32>) {
             let new: u32 = new.into();
@@ -324,6 +451,19 @@ decl_module! {
             <MinimumPenalty<T>>::put(new);
         }
 
+        /// Set the slash ratio of the offence in permill of the jackpot balance, `None` for the
+        /// default penalty.
+        fn set_slash_ratio(offence: OffenceKind, ratio: Option<u32>) -> Result {
+            match ratio {
+                Some(ratio) => {
+                    ensure!(ratio <= 1000, "The slash ratio can not exceed 1000 permill.");
+                    <SlashRatios<T>>::insert(offence, ratio);
+                }
+                None => <SlashRatios<T>>::remove(offence),
+            }
+            Ok(())
+        }
+
         /// Set the distribution ratio between cross-chain assets and native assets.
         pub fn set_distribution_ratio(new: (u32, u32)) {
             ensure!(new.0 > 0 && new.1 > 0, "DistributionRatio can not be zero.");
@@ -340,6 +480,86 @@ decl_module! {
             <UpperBoundFactor<T>>::put(new);
         }
 
+        /// Set the cap of the total nomination of each validator, the nomination of the intention
+        /// itself is not limited.
+        fn set_max_nomination_per_validator(new: NominationCap<T::Balance>) -> Result {
+            if let NominationCap::Fraction(parts) = new {
+                ensure!(parts <= 10_000, "The fraction of nomination cap can not exceed 10_000.");
+            }
+            <MaxNominationPerValidator<T>>::put(new);
+            Ok(())
+        }
+
+        /// Set the number of recent sessions whose validator set are kept.
+        fn set_validator_set_history_depth(new: Compact<u32>) {
+            let new: u32 = new.into();
+            <ValidatorSetHistoryDepth<T>>::put(new);
+        }
+
+        /// Set the number of the vote weight checkpoints kept for each intention.
+        fn set_weight_checkpoint_depth(new: Compact<u32>) {
+            let new: u32 = new.into();
+            <WeightCheckpointDepth<T>>::put(new);
+        }
+
+        /// Set the number of recent blocks the uptime score covers.
+        fn set_uptime_window(new: Compact<u32>) {
+            let new: u32 = new.into();
+            <UptimeWindow<T>>::put(new);
+        }
+
+        /// Whether to emit `NominationRejected` for the rejected nomination attempts, for debugging.
+        fn set_nomination_rejection_events(enabled: bool) {
+            <NominationRejectionEvents<T>>::put(enabled);
+        }
+
+        /// Set the reward curve, (start session index, reward per session) in ascending order of the
+        /// session index, empty for the default halving.
+        fn set_reward_curve(curve: Vec<(T::BlockNumber, T::Balance)>) -> Result {
+            ensure!(
+                curve.windows(2).all(|w| w[0].0 < w[1].0),
+                "The session indexes of the reward curve should be strictly ascending."
+            );
+            <RewardCurve<T>>::put(curve);
+            Ok(())
+        }
+
+        /// Set how the dividend is rounded.
+        fn set_dividend_rounding(new: DividendRounding) {
+            <DividendRoundingMode<T>>::put(new);
+        }
+
+        /// Set the names which could not be registered as an intention name.
+        fn set_reserved_names(names: Vec<Name>) {
+            <ReservedNames<T>>::put(names);
+        }
+
+        /// Move the dust of an intention's jackpot below `threshold` to the council.
+        ///
+        /// Only allowed when there is no outstanding vote weight to be claimed.
+        fn sweep_jackpot_dust(intention: T::AccountId, threshold: T::Balance) -> Result {
+            ensure!(Self::is_intention(&intention), "Cannot sweep the jackpot of a non-intention.");
+
+            let jackpot = Self::jackpot_accountid_for_unsafe(&intention);
+            let amount = xassets::Module::<T>::pcx_free_balance(&jackpot);
+            ensure!(amount < threshold, "The jackpot balance is not below the threshold.");
+
+            let current_block = <system::Module<T>>::block_number();
+            let (vote_weight, _) = <Self as ComputeWeight<T::AccountId>>::settle_claimee_weight(
+                &intention,
+                current_block.saturated_into::<u64>(),
+            );
+            ensure!(vote_weight == 0, "The jackpot still has outstanding vote weight.");
+
+            let council = xaccounts::Module::<T>::council_account();
+            xassets::Module::<T>::pcx_move_free_balance(&jackpot, &council, amount)
+                .map_err(|e| e.info())?;
+
+            Self::deposit_event(RawEvent::JackpotSwept(intention, amount));
+
+            Ok(())
+        }
+
         fn set_nomination_record(
             nominator: T::AccountId,
             nominee: T::AccountId,
@@ -452,6 +672,36 @@ decl_module! {
             <GlobalDistributionRatio<T>>::put(new);
         }
 
+        /// Backfill the nominator count of the intention recorded before NominatorCount existed.
+        ///
+        /// NominationRecords is not enumerable, so `nominators` should be all the nominators of the
+        /// intention, the ones without a non-zero nomination on chain are not counted.
+        fn backfill_nominator_count(intention: T::AccountId, nominators: Vec<T::AccountId>) {
+            ensure!(Self::is_intention(&intention), "Cannot backfill the nominator count of non-intention.");
+            let mut nominators = nominators;
+            nominators.sort();
+            nominators.dedup();
+            let count = nominators
+                .into_iter()
+                .filter(|nominator| !Self::revokable_of(&(nominator.clone(), intention.clone())).is_zero())
+                .count() as u32;
+            info!("[backfill_nominator_count]|intention:{:?}|old:{:}|new:{:}", intention, Self::nominator_count(&intention), count);
+            <NominatorCount<T>>::insert(&intention, count);
+        }
+
+        /// Register to be an intention and nominate `self_bond` to itself at the same time.
+        fn register_with_self_bond(origin, name: Name, self_bond: T::Balance) {
+            let who = ensure_signed(origin)?;
+
+            ensure!(!self_bond.is_zero(), "Cannot self-bond zero, use register instead.");
+            ensure!(
+                self_bond <= <xassets::Module<T>>::pcx_free_balance(&who),
+                "Cannot self-bond if greater than your avaliable free balance."
+            );
+
+            Self::check_and_apply_register(&who, name)?;
+            Self::apply_nominate(&who, &who, self_bond)?;
+        }
     }
 }
 
@@ -473,18 +723,25 @@ decl_event!(
         Nominate(AccountId, AccountId, Balance),
         Claim(u64, u64, Balance),
         Refresh(AccountId, Option<URL>, Option<bool>, Option<SessionKey>, Option<XString>),
-        Unfreeze(AccountId, AccountId),
+        /// Nominator unfreezes the revocation against the target, with the unfrozen amount.
+        Unfreeze(AccountId, AccountId, Balance),
         /// All rewards issued to all (psedu-)intentions.
         SessionReward(Balance, Balance, Balance, Balance),
         /// u128 version of Claim
         ClaimV1(u128, u128, Balance),
         RemoveZombieIntentions(Vec<AccountId>),
+        /// The dust of intention's jackpot has been swept to the council.
+        JackpotSwept(AccountId, Balance),
+        /// A nomination attempt (who, target) is rejected, only emitted when `NominationRejectionEvents` is on.
+        NominationRejected(AccountId, AccountId, NominationRejectReason),
     }
 );
 
 decl_storage! {
     trait Store for Module<T: Trait> as XStaking {
         pub InitialReward get(initial_reward) config(): T::Balance;
+        /// The session reward starting from each session index, overrides the default halving if not empty.
+        pub RewardCurve get(reward_curve): Vec<(T::BlockNumber, T::Balance)>;
 
         /// The ideal number of staking participants.
         pub ValidatorCount get(validator_count) config(): u32;
@@ -502,6 +759,28 @@ decl_storage! {
         /// Maximum number of intentions.
         pub MaximumIntentionCount get(maximum_intention_count) config(): u32;
 
+        /// Validator sets of the recent sessions, (session_index, validators), from the oldest to the latest.
+        pub ValidatorSetHistory get(validator_set_history): Vec<(T::BlockNumber, Vec<T::AccountId>)>;
+        /// The first block of each session in the ValidatorSetHistory.
+        pub SessionStartBlock get(session_start_block): map T::BlockNumber => Option<T::BlockNumber>;
+        /// Maximum number of sessions kept in the ValidatorSetHistory.
+        pub ValidatorSetHistoryDepth get(validator_set_history_depth): u32 = 32u32;
+        /// Vote weight checkpoints of each intention taken at the session rotation,
+        /// (block, total vote weight settled at the block, total nomination) in ascending order.
+        pub WeightCheckpoints get(weight_checkpoints): map T::AccountId => Vec<(T::BlockNumber, u128, T::Balance)>;
+        /// Maximum number of checkpoints kept in the WeightCheckpoints of each intention.
+        pub WeightCheckpointDepth get(weight_checkpoint_depth): u32 = 32u32;
+        /// Number of recent blocks the uptime score covers, bounded by the producer history in xsystem.
+        pub UptimeWindow get(uptime_window): u32 = xsystem::MAX_RECENT_BLOCK_PRODUCERS as u32;
+
+        /// Names of the special accounts, which are not allowed to be registered as an intention name.
+        pub ReservedNames get(reserved_names): Vec<Name> = vec![
+            b"Council".to_vec(),
+            b"Team".to_vec(),
+            b"Treasury".to_vec(),
+            b"ChainX".to_vec(),
+        ];
+
         pub SessionsPerEpoch get(sessions_per_epoch) config(): T::BlockNumber = T::BlockNumber::saturated_from::<u64>(10_000);
 
         /// The current era index.
@@ -534,8 +813,23 @@ decl_storage! {
         /// This is same with NominationRecords with the weight field extended from u64 to u128. Ref intention_profs! comments.
         pub NominationRecordsV1 get(nomination_records_v1): map (T::AccountId, T::AccountId) => Option<NominationRecordV1<T::Balance, T::BlockNumber>>;
 
+        /// Emit the events of the rejected nomination attempts, off by default.
+        pub NominationRejectionEvents get(nomination_rejection_events): bool;
+
+        /// Claim the dividend of the nominator before the nomination is adjusted on `unnominate`,
+        /// opted in by the nominator.
+        pub AutoClaimOnUnnominate get(auto_claim_on_unnominate): map T::AccountId => bool;
+
+        /// The number of the nominators with a non-zero nomination to the intention.
+        pub NominatorCount get(nominator_count): map T::AccountId => u32;
+
+        /// Rounding mode of the dividend computation.
+        pub DividendRoundingMode get(dividend_rounding): DividendRounding;
+
         /// The upper bound nominations of the intention that could absorb is up to the self-bonded.
         pub UpperBoundFactor get(upper_bound_factor): u32 = 10u32;
+        /// The cap of the total nomination of each validator, unlimited by default.
+        pub MaxNominationPerValidator get(max_nomination_per_validator): NominationCap<T::Balance>;
 
         /// Reported validators that did evil, reset per session.
         pub EvilValidatorsPerSession get(evil_validators): Vec<T::AccountId>;
@@ -554,6 +848,17 @@ decl_storage! {
         pub MissedOfPerSession get(missed_of_per_session): map T::AccountId => u32;
         /// The higher the severity, the more slash for missed blocks.
         pub MissedBlockSeverity get(missed_blocks_severity) config(): u32;
+        /// Number of offline reports tolerated before an active validator is noted offline, zero means no grace.
+        pub OfflineGracePeriod get(offline_grace_period): u32;
+        /// Offline reports of each validator since the last block it authored.
+        pub OfflineReportsInGrace get(offline_reports_in_grace): map T::AccountId => u32;
+        /// The slash ratio in permill of the jackpot balance for each offence, the offline offence
+        /// is per missed block. The default penalty applies if not set.
+        pub SlashRatios get(slash_ratio): map OffenceKind => Option<u32>;
+        /// Intentions opting out of being kicked for the offline slashing.
+        pub AutoKickDisabled get(auto_kick_disabled): map T::AccountId => bool;
+        /// Destinations of the claimed dividend of an intention with their ratios.
+        pub RewardSplitOf get(reward_split_of): map T::AccountId => Vec<(T::AccountId, Permill)>;
     }
 }
 
@@ -580,6 +885,18 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// Sum of the revocations which are due at the given block, i.e., could be unfrozen now.
+    pub fn unfreezable_amount(
+        who: &T::AccountId,
+        intention: &T::AccountId,
+        current_block: T::BlockNumber,
+    ) -> T::Balance {
+        Self::revocations_of(&(who.clone(), intention.clone()))
+            .into_iter()
+            .filter(|(block, _)| *block <= current_block)
+            .fold(Zero::zero(), |acc, (_, value)| acc + value)
+    }
+
     /// Try get NominationRecord, otherwise return Err(NominationRecordV1).
     pub fn try_get_nomination_record(
         key: &(T::AccountId, T::AccountId),
@@ -649,6 +966,13 @@ impl<T: Trait> Module<T> {
         <xaccounts::Module<T>>::intention_name_of(who).is_some()
     }
 
+    /// Reserved names are compared case-insensitively to prevent the impersonation.
+    pub fn is_reserved_name(name: &[u8]) -> bool {
+        Self::reserved_names()
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(name))
+    }
+
     pub fn name_exists(name: &Name) -> bool {
         <xaccounts::Module<T>>::intention_of(name).is_some()
     }
@@ -657,6 +981,10 @@ impl<T: Trait> Module<T> {
         <xaccounts::Module<T>>::intention_props_of(who).is_active
     }
 
+    pub fn auto_kick(who: &T::AccountId) -> bool {
+        !Self::auto_kick_disabled(who)
+    }
+
     pub fn is_able_to_apply_inactive() -> bool {
         let active = Self::intention_set()
             .into_iter()
@@ -829,6 +1157,37 @@ impl<T: Trait> Module<T> {
         ));
     }
 
+    /// Reject the nomination attempt with `err` unless `cond` holds.
+    fn ensure_nomination(
+        cond: bool,
+        who: &T::AccountId,
+        target: &T::AccountId,
+        reason: NominationRejectReason,
+        err: &'static str,
+    ) -> Result {
+        if cond {
+            Ok(())
+        } else {
+            Self::reject_nomination(who, target, reason, err)
+        }
+    }
+
+    fn reject_nomination(
+        who: &T::AccountId,
+        target: &T::AccountId,
+        reason: NominationRejectReason,
+        err: &'static str,
+    ) -> Result {
+        if Self::nomination_rejection_events() {
+            Self::deposit_event(RawEvent::NominationRejected(
+                who.clone(),
+                target.clone(),
+                reason,
+            ));
+        }
+        Err(err)
+    }
+
     fn wont_reach_upper_bound(nominee: &T::AccountId, value: T::Balance) -> Result {
         let total_nomination = Self::total_nomination_of(nominee);
         let upper_bound = Self::upper_bound_of(nominee);
@@ -840,6 +1199,49 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// Ensure the reward split has a limited number of destinations and sums up to no more than 100%.
+    fn check_reward_split(split: &[(T::AccountId, Permill)]) -> Result {
+        ensure!(
+            split.len() <= MAX_REWARD_SPLIT_DESTINATIONS,
+            "Too many reward split destinations."
+        );
+        let total = split
+            .iter()
+            .fold(0u64, |acc, (_, ratio)| acc + *ratio * 1_000_000u64);
+        ensure!(
+            total <= 1_000_000,
+            "The sum of the reward split can not exceed 100%."
+        );
+        Ok(())
+    }
+
+    /// The max total nomination of each validator, `None` if unlimited.
+    pub fn nomination_cap() -> Option<T::Balance> {
+        match Self::max_nomination_per_validator() {
+            NominationCap::Unlimited => None,
+            NominationCap::Absolute(cap) => Some(cap),
+            NominationCap::Fraction(parts) => {
+                let total_staked = Self::intention_set()
+                    .iter()
+                    .map(|i| Self::total_nomination_of(i).saturated_into::<u128>())
+                    .fold(0u128, |acc, x| acc.saturating_add(x));
+                let cap = total_staked.saturating_mul(u128::from(parts)) / 10_000;
+                Some(T::Balance::saturated_from::<u128>(cap))
+            }
+        }
+    }
+
+    fn wont_exceed_nomination_cap(nominee: &T::AccountId, value: T::Balance) -> Result {
+        if let Some(cap) = Self::nomination_cap() {
+            let total_nomination = Self::total_nomination_of(nominee);
+            if total_nomination + value > cap {
+                error!("Fail to (re)nominate, nomination cap is {:?}, current total_nomination of nominee({:?}): {:?}, want to nominate: {:?}", cap, nominee, total_nomination, value);
+                return Err("Cannot (re)nominate if the total nomination of the target would exceed the cap.");
+            }
+        }
+        Ok(())
+    }
+
     fn is_nominating_intention_itself(nominator: &T::AccountId, nominee: &T::AccountId) -> bool {
         Self::is_intention(nominator) && *nominator == *nominee
     }
@@ -870,6 +1272,30 @@ impl<T: Trait> Module<T> {
         <xaccounts::IntentionPropertiesOf<T>>::insert(intention, intention_props);
     }
 
+    /// Check the registration requirements before registering an intention.
+    fn check_and_apply_register(who: &T::AccountId, name: Name) -> Result {
+        xaccounts::is_valid_name(&name)?;
+
+        ensure!(
+            !Self::is_intention(who),
+            "Cannot register if transactor is an intention already."
+        );
+        ensure!(
+            !Self::name_exists(&name),
+            "This name has already been taken."
+        );
+        ensure!(
+            !Self::is_reserved_name(&name),
+            "This name is reserved for the special accounts."
+        );
+        ensure!(
+            Self::intention_set().len() < Self::maximum_intention_count() as usize,
+            "Cannot register if there are already too many intentions"
+        );
+
+        Self::apply_register(who, name)
+    }
+
     /// Actually register an intention.
     fn apply_register(intention: &T::AccountId, name: Name) -> Result {
         let block_number = <system::Module<T>>::block_number();
@@ -919,6 +1345,107 @@ impl<T: Trait> Module<T> {
         xsession::Module::<T>::validators()
     }
 
+    /// The Aura slot of the moment, the slot duration is the doubled minimum period as Aura.
+    fn slot_at(moment: T::Moment) -> u64 {
+        let slot_duration = timestamp::Module::<T>::minimum_period()
+            .saturated_into::<u64>()
+            .saturating_mul(2)
+            .max(1);
+        moment.saturated_into::<u64>() / slot_duration
+    }
+
+    /// The validator expected to produce the block at the moment, i.e. the author of the Aura
+    /// slot of the moment among the given validators.
+    pub fn expected_producer_at(
+        validators: &[T::AccountId],
+        moment: T::Moment,
+    ) -> Option<T::AccountId> {
+        if validators.is_empty() {
+            return None;
+        }
+        let index = Self::slot_at(moment) % validators.len() as u64;
+        Some(validators[index as usize].clone())
+    }
+
+    /// The percentage of the Aura slots of the validator in the recent `UptimeWindow` blocks in
+    /// which it produces the block, capped at 100. The empty slots between two blocks are
+    /// assigned to the validators at the latter block.
+    ///
+    /// The blocks before the oldest session in the ValidatorSetHistory are not counted, unless no
+    /// session is recorded yet, then the current validators are expected.
+    pub fn uptime_score(validator: &T::AccountId) -> u32 {
+        let history = xsystem::Module::<T>::recent_production(Self::uptime_window());
+        let sessions = Self::validator_set_history()
+            .into_iter()
+            .filter_map(|(index, validators)| {
+                Self::session_start_block(index).map(|start| (start, validators))
+            })
+            .collect::<Vec<_>>();
+        let current = Self::validators()
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect::<Vec<_>>();
+
+        let mut produced = 0u64;
+        let mut expected = 0u64;
+        let mut last_slot = None;
+        for (block, producer, moment) in history.iter() {
+            let slot = Self::slot_at(*moment);
+            // the slots since the previous block, only the slot of the block for the oldest one.
+            let first_slot = last_slot.map(|last: u64| last + 1).unwrap_or(slot);
+            last_slot = Some(slot);
+            if first_slot > slot {
+                continue;
+            }
+
+            let validators = match sessions.iter().rev().find(|(start, _)| start <= block) {
+                Some((_, validators)) => validators,
+                None if sessions.is_empty() => &current,
+                None => continue,
+            };
+            let index = match validators.iter().position(|v| v == validator) {
+                Some(index) => index as u64,
+                None => continue,
+            };
+
+            // the number of the slots before `end` the validator is expected to produce.
+            let count = validators.len() as u64;
+            let assigned_before = |end: u64| end / count + u64::from(end % count > index);
+            expected += assigned_before(slot + 1) - assigned_before(first_slot);
+            if producer == validator
+                && Self::expected_producer_at(validators, *moment).as_ref() == Some(validator)
+            {
+                produced += 1;
+            }
+        }
+
+        if expected == 0 {
+            return 0;
+        }
+        let score = produced * 100 / expected;
+        rstd::cmp::min(score, 100) as u32
+    }
+
+    /// Validator set of the given session, empty if it's out of the ValidatorSetHistory.
+    pub fn validator_set_at_session(session_index: T::BlockNumber) -> Vec<T::AccountId> {
+        Self::validator_set_history()
+            .into_iter()
+            .find(|(index, _)| *index == session_index)
+            .map(|(_, validators)| validators)
+            .unwrap_or_default()
+    }
+
+    /// The nearest vote weight checkpoint of the intention at or before the given block.
+    pub fn weight_checkpoint_at(
+        intention: &T::AccountId,
+        block: T::BlockNumber,
+    ) -> Option<(T::BlockNumber, u128, T::Balance)> {
+        Self::weight_checkpoints(intention)
+            .into_iter()
+            .rev()
+            .find(|(at, _, _)| *at <= block)
+    }
+
     pub fn jackpot_accountid_for_unsafe(who: &T::AccountId) -> T::AccountId {
         T::DetermineIntentionJackpotAccountId::accountid_for_unsafe(who)
     }
@@ -954,6 +1481,24 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// The profile of the intention, `None` if it's not an intention.
+    pub fn validator_profile(
+        intention: &T::AccountId,
+    ) -> Option<ValidatorProfile<T::AccountId, T::Balance>> {
+        if !Self::is_intention(intention) {
+            return None;
+        }
+        let jackpot_account = Self::jackpot_accountid_for_unsafe(intention);
+        let jackpot_balance = xassets::Module::<T>::pcx_free_balance(&jackpot_account);
+        Some(ValidatorProfile {
+            total_nomination: Self::total_nomination_of(intention),
+            jackpot_account,
+            jackpot_balance,
+            is_active: Self::is_active(intention),
+            nominator_count: Self::nominator_count(intention),
+        })
+    }
+
     pub fn intentions_info_common(
     ) -> Vec<IntentionInfoCommon<T::AccountId, T::Balance, T::SessionKey, T::BlockNumber>> {
         Self::intention_set()
@@ -8,8 +8,18 @@ mod proposal09;
 
 impl<T: Trait> Module<T> {
     /// Get the reward for the session, assuming it ends with this block.
-    fn this_session_reward() -> T::Balance {
-        let current_index = <xsession::Module<T>>::current_index().saturated_into::<u64>();
+    ///
+    /// Follow the configured reward curve if any, otherwise halve the initial reward per round.
+    pub fn this_session_reward() -> T::Balance {
+        let current_index = <xsession::Module<T>>::current_index();
+        if let Some((_, reward)) = Self::reward_curve()
+            .into_iter()
+            .rev()
+            .find(|(start, _)| *start <= current_index)
+        {
+            return reward;
+        }
+        let current_index = current_index.saturated_into::<u64>();
         let reward = Self::initial_reward().into()
             / u64::from(u32::pow(2, (current_index / SESSIONS_PER_ROUND) as u32));
         reward.into()
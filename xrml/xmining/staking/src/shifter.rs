@@ -11,13 +11,16 @@ use xsupport::{validators, who};
 
 impl<T: Trait> Module<T> {
     /// Gather all the active intentions sorted by total nomination.
+    ///
+    /// Intentions with the same total nomination are ordered by account id, so that
+    /// the validator set is stable under ties.
     fn gather_candidates() -> Vec<(T::Balance, T::AccountId)> {
         let mut intentions = Self::intention_set()
             .into_iter()
             .filter(|v| Self::is_qualified_candidate(&v))
             .map(|v| (Self::total_nomination_of(&v), v))
             .collect::<Vec<_>>();
-        intentions.sort_by(|&(ref b1, _), &(ref b2, _)| b2.cmp(&b1));
+        intentions.sort_by(|(b1, a1), (b2, a2)| b2.cmp(b1).then_with(|| a1.cmp(a2)));
         intentions
     }
 
@@ -88,6 +91,51 @@ impl<T: Trait> Module<T> {
         } else if validators.len() < current_validator_count {
             Self::set_validators_on_non_era(validators);
         }
+
+        Self::note_validator_set(session_index);
+        Self::note_weight_checkpoints();
+    }
+
+    /// Record the validator set of the new session, only the latest ValidatorSetHistoryDepth sessions are kept.
+    fn note_validator_set(session_index: T::BlockNumber) {
+        let validators = <xsession::Module<T>>::validators()
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect::<Vec<_>>();
+        let depth = Self::validator_set_history_depth() as usize;
+        // the session is rotated at the end of the block, the new validators start from the next one.
+        let start = <system::Module<T>>::block_number() + One::one();
+        <SessionStartBlock<T>>::insert(&session_index, start);
+        <ValidatorSetHistory<T>>::mutate(|history| {
+            history.push((session_index, validators));
+            if history.len() > depth {
+                let excess = history.len() - depth;
+                for (index, _) in history.drain(..excess) {
+                    <SessionStartBlock<T>>::remove(&index);
+                }
+            }
+        });
+    }
+
+    /// Record the vote weight checkpoint of each intention, only the latest WeightCheckpointDepth
+    /// checkpoints are kept.
+    fn note_weight_checkpoints() {
+        let current_block = <system::Module<T>>::block_number();
+        let depth = Self::weight_checkpoint_depth() as usize;
+        for intention in Self::intention_set() {
+            let (vote_weight, _) = <Self as ComputeWeight<T::AccountId>>::settle_claimee_weight(
+                &intention,
+                current_block.saturated_into::<u64>(),
+            );
+            let total_nomination = Self::total_nomination_of(&intention);
+            <WeightCheckpoints<T>>::mutate(&intention, |checkpoints| {
+                checkpoints.push((current_block, vote_weight, total_nomination));
+                if checkpoints.len() > depth {
+                    let excess = checkpoints.len() - depth;
+                    checkpoints.drain(..excess);
+                }
+            });
+        }
     }
 
     /// We only reduce the offline validators on non-era session.
@@ -159,6 +207,22 @@ impl<T: Trait> Module<T> {
             return;
         }
 
+        // Tolerate the transient offline reports within the grace period.
+        let grace_period = Self::offline_grace_period();
+        if grace_period > 0 {
+            let reports = Self::offline_reports_in_grace(v) + 1;
+            <OfflineReportsInGrace<T>>::insert(v, reports);
+            if reports <= grace_period {
+                debug!(
+                    "[note_offline_validator]: offline validator {:?} is in grace period: {:?}/{:?}",
+                    who!(v),
+                    reports,
+                    grace_period
+                );
+                return;
+            }
+        }
+
         debug!(
             "[note_offline_validator]: active offline validator noted: {:?}",
             who!(v)
@@ -173,6 +237,13 @@ impl<T: Trait> Module<T> {
         let missed = Self::missed_of_per_session(v);
         <MissedOfPerSession<T>>::insert(v, missed + 1);
     }
+
+    /// Reset the offline reports in grace period once the validator authored a block.
+    pub fn on_validator_authored(v: &T::AccountId) {
+        if <OfflineReportsInGrace<T>>::exists(v) {
+            <OfflineReportsInGrace<T>>::remove(v);
+        }
+    }
 }
 
 impl<T: Trait> OnSessionChange<T::Moment> for Module<T> {
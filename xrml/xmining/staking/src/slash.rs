@@ -51,8 +51,14 @@ impl<T: Trait> Module<T> {
             return Err("Cannot slash if the reported double signer is not an intention");
         }
 
-        // Slash the whole jackpot of double signer.
-        let slashed = Self::slash_whole_jackpot(who);
+        // Slash the whole jackpot of double signer by default.
+        let slashed = match Self::ratio_slash_of(who, OffenceKind::DoubleSign) {
+            Some(should_slash) => match Self::try_slash_or_clear(who, should_slash) {
+                Ok(()) => should_slash,
+                Err(slashed) => slashed,
+            },
+            None => Self::slash_whole_jackpot(who),
+        };
         info!(
             "[slash_double_signer] {:?} is slashed: {:?}",
             who!(who),
@@ -74,6 +80,21 @@ impl<T: Trait> Module<T> {
         Ok(slashed)
     }
 
+    /// The penalty of the offence given the configured ratio of the jackpot balance,
+    /// `None` if the ratio of this offence is not set.
+    fn ratio_slash_of(who: &T::AccountId, offence: OffenceKind) -> Option<T::Balance> {
+        Self::slash_ratio(offence).map(|ratio| {
+            let jackpot = Self::jackpot_accountid_for_unsafe(who);
+            let jackpot_balance: u64 = <xassets::Module<T>>::pcx_free_balance(&jackpot).into();
+            ((u128::from(jackpot_balance) * u128::from(ratio) / 1000) as u64).into()
+        })
+    }
+
+    fn penalty_of_missed(per_missed: T::Balance, missed: u64) -> T::Balance {
+        let per_missed: u64 = per_missed.into();
+        per_missed.saturating_mul(missed).into()
+    }
+
     fn reward_of_per_block(session_reward: T::Balance) -> T::Balance {
         let session_length = <xsession::SessionLength<T>>::get().saturated_into::<u64>();
         let validators_count = <xsession::Validators<T>>::get().len() as u64;
@@ -91,10 +112,14 @@ impl<T: Trait> Module<T> {
         // Slash 10 times per block reward for each missed block.
         let missed = u64::from(<MissedOfPerSession<T>>::take(who));
         let reward_per_block = Self::reward_of_per_block(my_reward);
-        let total_slash = cmp::max(
-            (reward_per_block.into() * missed * u64::from(Self::missed_blocks_severity())).into(),
-            (Self::minimum_penalty().saturated_into::<u64>() * missed).into(),
-        );
+        let total_slash = match Self::ratio_slash_of(who, OffenceKind::Offline) {
+            Some(per_missed) => Self::penalty_of_missed(per_missed, missed),
+            None => cmp::max(
+                (reward_per_block.into() * missed * u64::from(Self::missed_blocks_severity()))
+                    .into(),
+                (Self::minimum_penalty().saturated_into::<u64>() * missed).into(),
+            ),
+        };
 
         let (_slashed, should_be_enforced) =
             if let Err(slashed) = Self::try_slash_or_clear(who, total_slash) {
@@ -110,6 +135,14 @@ impl<T: Trait> Module<T> {
             total_slash
         );
 
+        if should_be_enforced && !Self::auto_kick(who) {
+            info!(
+                "[slash_active_offline_validator] validator opts out of auto kick, keep it active: {:?}",
+                who!(who)
+            );
+            return;
+        }
+
         // Force those slashed yet can't afford the penalty to be inactive when the validators is not too few.
         // Then these inactive validators will not be rewarded.
         if should_be_enforced && validators.len() > Self::minimum_validator_count() as usize {
@@ -149,8 +182,14 @@ impl<T: Trait> Module<T> {
         ));
 
         for who in inactive_slashed.iter() {
-            let missed: T::Balance = u64::from(<MissedOfPerSession<T>>::take(who)).into();
-            let should_slash = missed * Self::minimum_penalty();
+            let missed = u64::from(<MissedOfPerSession<T>>::take(who));
+            let should_slash = match Self::ratio_slash_of(who, OffenceKind::Offline) {
+                Some(per_missed) => Self::penalty_of_missed(per_missed, missed),
+                None => {
+                    let missed: T::Balance = missed.into();
+                    missed * Self::minimum_penalty()
+                }
+            };
             let _ = Self::try_slash_or_clear(who, should_slash);
         }
     }
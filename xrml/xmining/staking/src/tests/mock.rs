@@ -152,6 +152,7 @@ impl xaccounts::IntentionJackpotAccountIdFor<u64> for DummyDetermineIntentionJac
 impl xassets::Trait for Test {
     type Balance = u64;
     type OnNewAccount = Indices;
+    type OnFreeBalanceZero = ();
     type Event = ();
     type OnAssetChanged = ();
     type OnAssetRegisterOrRevoke = ();
@@ -26,6 +26,66 @@ fn register_should_work() {
     });
 }
 
+#[test]
+fn register_with_self_bond_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        assert_noop!(
+            XStaking::register_with_self_bond(Origin::signed(1), b"name".to_vec(), 0),
+            "Cannot self-bond zero, use register instead."
+        );
+        assert_noop!(
+            XStaking::register_with_self_bond(Origin::signed(1), b"name".to_vec(), 11),
+            "Cannot self-bond if greater than your avaliable free balance."
+        );
+        assert!(!XStaking::is_intention(&1));
+
+        assert_ok!(XStaking::register_with_self_bond(
+            Origin::signed(1),
+            b"name".to_vec(),
+            6
+        ));
+        assert!(XStaking::is_intention(&1));
+        assert_eq!(XAssets::pcx_free_balance(&1), 10 - 6);
+        assert_eq!(
+            <NominationRecords<Test>>::get(&(1, 1)).unwrap(),
+            NominationRecord {
+                nomination: 6,
+                last_vote_weight: 0,
+                last_vote_weight_update: 1,
+                revocations: vec![],
+            }
+        );
+        assert_eq!(XStaking::total_nomination_of(&1), 6);
+    });
+}
+
+#[test]
+fn register_a_reserved_name_should_not_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        assert_noop!(
+            XStaking::register(Origin::signed(1), b"Council".to_vec(),),
+            "This name is reserved for the special accounts."
+        );
+        assert_noop!(
+            XStaking::register(Origin::signed(1), b"team".to_vec(),),
+            "This name is reserved for the special accounts."
+        );
+
+        assert_ok!(XStaking::set_reserved_names(vec![b"name".to_vec()]));
+        assert_noop!(
+            XStaking::register(Origin::signed(1), b"name".to_vec(),),
+            "This name is reserved for the special accounts."
+        );
+        assert_ok!(XStaking::register(Origin::signed(1), b"team".to_vec(),));
+    });
+}
+
 #[test]
 fn register_an_existing_name_should_not_work() {
     with_externalities(&mut new_test_ext(), || {
@@ -309,6 +369,199 @@ fn claim_should_work() {
     });
 }
 
+#[test]
+fn unnominate_with_auto_claim_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_ok!(XStaking::register(Origin::signed(2), b"name".to_vec(),));
+        assert_ok!(XStaking::refresh(
+            Origin::signed(2),
+            None,
+            Some(true),
+            None,
+            None
+        ));
+        assert!(!XStaking::auto_claim_on_unnominate(&2));
+        assert_ok!(XStaking::set_auto_claim_on_unnominate(
+            Origin::signed(2),
+            true
+        ));
+        // the other nominators are not opted in.
+        assert!(!XStaking::auto_claim_on_unnominate(&3));
+
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+        System::set_block_number(2);
+        XSession::check_rotate_session(System::block_number());
+        assert_ok!(XAssets::pcx_issue(&2, 10 * 100_000_000));
+        assert_ok!(XStaking::nominate(
+            Origin::signed(2),
+            2.into(),
+            10 * 100_000_000,
+            vec![]
+        ));
+
+        System::set_block_number(3);
+        XSession::check_rotate_session(System::block_number());
+        assert_eq!(XAssets::pcx_free_balance(&2), 20 + 26181818);
+        assert_ok!(XStaking::unnominate(
+            Origin::signed(2),
+            2.into(),
+            100_000_000,
+            vec![]
+        ));
+        // the dividend is credited as the explicit claim does.
+        assert_eq!(XAssets::pcx_free_balance(&2), 261818201);
+        assert_eq!(XStaking::revokable_of(&(2, 2)), 9 * 100_000_000);
+        assert_eq!(XStaking::revocations_of(&(2, 2)).len(), 1);
+
+        // nothing to claim in the same block, the unnomination still works.
+        assert_ok!(XStaking::unnominate(
+            Origin::signed(2),
+            2.into(),
+            100_000_000,
+            vec![]
+        ));
+        assert_eq!(XAssets::pcx_free_balance(&2), 261818201);
+        assert_eq!(XStaking::revokable_of(&(2, 2)), 8 * 100_000_000);
+    });
+}
+
+#[test]
+fn claim_with_reward_split_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_noop!(
+            XStaking::set_reward_split(Origin::signed(2), vec![(3, Permill::from_percent(10))]),
+            "Cannot set reward split if transactor is not an intention."
+        );
+
+        assert_ok!(XStaking::register(Origin::signed(2), b"name".to_vec(),));
+        assert_ok!(XStaking::refresh(
+            Origin::signed(2),
+            None,
+            Some(true),
+            None,
+            None
+        ));
+        assert_noop!(
+            XStaking::set_reward_split(
+                Origin::signed(2),
+                vec![
+                    (3, Permill::from_percent(60)),
+                    (4, Permill::from_percent(50))
+                ]
+            ),
+            "The sum of the reward split can not exceed 100%."
+        );
+        assert_ok!(XStaking::set_reward_split(
+            Origin::signed(2),
+            vec![
+                (3, Permill::from_percent(10)),
+                (4, Permill::from_percent(20))
+            ]
+        ));
+
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+        System::set_block_number(2);
+        XSession::check_rotate_session(System::block_number());
+        assert_ok!(XAssets::pcx_issue(&2, 10 * 100_000_000));
+        assert_ok!(XStaking::nominate(
+            Origin::signed(2),
+            2.into(),
+            10 * 100_000_000,
+            vec![]
+        ));
+
+        System::set_block_number(3);
+        XSession::check_rotate_session(System::block_number());
+        let before = (
+            XAssets::pcx_free_balance(&2),
+            XAssets::pcx_free_balance(&3),
+            XAssets::pcx_free_balance(&4),
+        );
+        assert_ok!(XStaking::claim(Origin::signed(2), 2.into()));
+        // the dividend 235636363 is split 10% to 3, 20% to 4 and the rest to 2.
+        assert_eq!(XAssets::pcx_free_balance(&3), before.1 + 23563636);
+        assert_eq!(XAssets::pcx_free_balance(&4), before.2 + 47127272);
+        assert_eq!(XAssets::pcx_free_balance(&2), before.0 + 164945455);
+
+        // the dividend claimed from other intentions is not split.
+        assert_ok!(XStaking::nominate(
+            Origin::signed(2),
+            10.into(),
+            100_000_000,
+            vec![]
+        ));
+        System::set_block_number(4);
+        XSession::check_rotate_session(System::block_number());
+        let before = (
+            XAssets::pcx_free_balance(&2),
+            XAssets::pcx_free_balance(&3),
+            XAssets::pcx_free_balance(&4),
+        );
+        assert_ok!(XStaking::claim(Origin::signed(2), 10.into()));
+        assert!(XAssets::pcx_free_balance(&2) > before.0);
+        assert_eq!(XAssets::pcx_free_balance(&3), before.1);
+        assert_eq!(XAssets::pcx_free_balance(&4), before.2);
+
+        // an empty split removes the configuration
+        assert_ok!(XStaking::set_reward_split(Origin::signed(2), vec![]));
+        assert!(XStaking::reward_split_of(&2).is_empty());
+    });
+}
+
+#[test]
+fn weight_checkpoint_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_ok!(XStaking::register(Origin::signed(2), b"name".to_vec(),));
+        assert_ok!(XStaking::refresh(
+            Origin::signed(2),
+            None,
+            Some(true),
+            None,
+            None
+        ));
+
+        System::set_block_number(2);
+        XSession::check_rotate_session(System::block_number());
+        assert_ok!(XAssets::pcx_issue(&2, 10 * 100_000_000));
+        assert_ok!(XStaking::nominate(
+            Origin::signed(2),
+            2.into(),
+            10 * 100_000_000,
+            vec![]
+        ));
+
+        for block in 3..=5 {
+            System::set_block_number(block);
+            XSession::check_rotate_session(System::block_number());
+        }
+
+        assert_eq!(XStaking::weight_checkpoint_at(&2, 1), None);
+        assert_eq!(XStaking::weight_checkpoint_at(&2, 2), Some((2, 0, 0)));
+        // the vote weight accumulates 10 * 100_000_000 per block since block 2.
+        assert_eq!(
+            XStaking::weight_checkpoint_at(&2, 4),
+            Some((4, 2 * 10 * 100_000_000, 10 * 100_000_000))
+        );
+        assert_eq!(
+            XStaking::weight_checkpoint_at(&2, 100),
+            Some((5, 3 * 10 * 100_000_000, 10 * 100_000_000))
+        );
+
+        // only the latest checkpoints are kept.
+        assert_ok!(XStaking::set_weight_checkpoint_depth(2.into()));
+        System::set_block_number(6);
+        XSession::check_rotate_session(System::block_number());
+        assert_eq!(XStaking::weight_checkpoints(&2).len(), 2);
+        assert_eq!(XStaking::weight_checkpoint_at(&2, 4), None);
+        assert_eq!(
+            XStaking::weight_checkpoint_at(&2, 5),
+            Some((5, 3 * 10 * 100_000_000, 10 * 100_000_000))
+        );
+    });
+}
+
 #[test]
 fn multiply_by_rational_should_work() {
     assert_eq!(XStaking::multiply_by_rational(100u64, 1, 3), 33);
@@ -399,6 +652,80 @@ fn minimum_candidate_threshold_should_work() {
     });
 }
 
+#[test]
+fn equal_stake_validators_should_be_ordered_by_account() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_ok!(XStaking::register(Origin::signed(6), b"name".to_vec(),));
+        assert_ok!(XStaking::refresh(
+            Origin::signed(6),
+            None,
+            Some(true),
+            None,
+            None
+        ));
+
+        // Make the total nomination of 6 be equal to 20.
+        assert_ok!(XAssets::pcx_issue(&1, 1_000_000_000));
+        assert_ok!(XAssets::pcx_issue(&6, 1_000_000_000));
+        assert_ok!(XStaking::nominate(
+            Origin::signed(6),
+            6.into(),
+            1_000_000_000,
+            vec![]
+        ));
+        assert_ok!(XStaking::nominate(
+            Origin::signed(1),
+            6.into(),
+            1_000_000_000,
+            vec![]
+        ));
+
+        let expected = vec![
+            (40, 4000000000),
+            (30, 3000000000),
+            (6, 2000000000),
+            (20, 2000000000),
+            (10, 1000000000),
+        ];
+
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+        assert_eq!(XStaking::validators(), expected);
+
+        System::set_block_number(2);
+        XSession::check_rotate_session(System::block_number());
+        assert_eq!(XStaking::validators(), expected);
+    });
+}
+
+#[test]
+fn sweep_jackpot_dust_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        assert_ok!(XStaking::register(Origin::signed(2), b"name".to_vec(),));
+
+        let jackpot = XStaking::jackpot_accountid_for_unsafe(&2);
+        let council = XAccounts::council_account();
+        assert_ok!(XAssets::pcx_issue(&jackpot, 5));
+        let council_balance = XAssets::pcx_free_balance(&council);
+
+        assert_noop!(
+            XStaking::sweep_jackpot_dust(2, 5),
+            "The jackpot balance is not below the threshold."
+        );
+
+        // Intention 10 has been nominated since genesis.
+        assert_noop!(
+            XStaking::sweep_jackpot_dust(10, u64::max_value()),
+            "The jackpot still has outstanding vote weight."
+        );
+
+        assert_ok!(XStaking::sweep_jackpot_dust(2, 6));
+        assert_eq!(XAssets::pcx_free_balance(&jackpot), 0);
+        assert_eq!(XAssets::pcx_free_balance(&council), council_balance + 5);
+    });
+}
+
 #[test]
 fn renominate_limitation_should_work() {
     with_externalities(&mut new_test_ext(), || {
@@ -517,6 +844,45 @@ fn max_unbond_entries_limit_should_work() {
     });
 }
 
+#[test]
+fn unfreezable_amount_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_ok!(XStaking::register(Origin::signed(1), b"name1".to_vec(),));
+        assert_ok!(XStaking::nominate(Origin::signed(3), 1.into(), 20, vec![]));
+
+        System::set_block_number(1);
+        assert_ok!(XStaking::unnominate(Origin::signed(3), 1.into(), 2, vec![]));
+        System::set_block_number(2);
+        assert_ok!(XStaking::unnominate(Origin::signed(3), 1.into(), 3, vec![]));
+
+        let bonding_duration = XStaking::bonding_duration();
+        assert_eq!(
+            XStaking::revocations_of(&(3, 1)),
+            vec![(1 + bonding_duration, 2), (2 + bonding_duration, 3)]
+        );
+        assert_eq!(XStaking::unfreezable_amount(&3, &1, bonding_duration), 0);
+        assert_eq!(
+            XStaking::unfreezable_amount(&3, &1, 1 + bonding_duration),
+            2
+        );
+        assert_eq!(
+            XStaking::unfreezable_amount(&3, &1, 2 + bonding_duration),
+            5
+        );
+        assert_eq!(
+            XStaking::unfreezable_amount(&2, &1, 2 + bonding_duration),
+            0
+        );
+
+        System::set_block_number(1 + bonding_duration);
+        assert_ok!(XStaking::unfreeze(Origin::signed(3), 1.into(), 0));
+        assert_eq!(
+            XStaking::unfreezable_amount(&3, &1, 2 + bonding_duration),
+            3
+        );
+    });
+}
+
 #[test]
 fn switch_to_u128_when_overflow() {
     with_externalities(&mut new_test_ext(), || {
@@ -707,3 +1073,426 @@ fn switch_to_u128_when_overflow() {
         );
     });
 }
+
+#[test]
+fn validator_set_history_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        assert!(XStaking::validator_set_history().is_empty());
+
+        assert_ok!(XStaking::register(Origin::signed(1), b"name1".to_vec(),));
+        assert_ok!(XStaking::nominate(Origin::signed(1), 1.into(), 10, vec![]));
+
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+        let first = XStaking::validators()
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect::<Vec<_>>();
+
+        assert_ok!(XStaking::nominate(Origin::signed(5), 1.into(), 50, vec![]));
+
+        System::set_block_number(2);
+        XSession::check_rotate_session(System::block_number());
+        let second = XStaking::validators()
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            XStaking::validator_set_history(),
+            vec![(1, first.clone()), (2, second.clone())]
+        );
+        assert_eq!(XStaking::validator_set_at_session(1), first);
+        assert_eq!(XStaking::validator_set_at_session(2), second);
+        assert!(XStaking::validator_set_at_session(3).is_empty());
+
+        assert_ok!(XStaking::set_validator_set_history_depth(2.into()));
+        System::set_block_number(3);
+        XSession::check_rotate_session(System::block_number());
+        assert_eq!(XStaking::validator_set_history().len(), 2);
+        assert!(XStaking::validator_set_at_session(1).is_empty());
+        assert_eq!(XStaking::validator_set_at_session(2), second);
+    });
+}
+
+#[test]
+fn offline_grace_period_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        // No grace period, noted as offline immediately.
+        XStaking::on_offline_validator(&10);
+        assert_eq!(XStaking::missed_of_per_session(&10), 1);
+        assert_eq!(XStaking::offline_validators_per_session(), vec![10]);
+
+        System::set_block_number(2);
+        XSession::check_rotate_session(System::block_number());
+        assert!(XStaking::offline_validators_per_session().is_empty());
+
+        assert_ok!(XStaking::set_offline_grace_period(2.into()));
+        XStaking::on_offline_validator(&20);
+        XStaking::on_offline_validator(&20);
+        assert_eq!(XStaking::offline_reports_in_grace(&20), 2);
+        assert_eq!(XStaking::missed_of_per_session(&20), 0);
+        assert!(XStaking::offline_validators_per_session().is_empty());
+
+        // Reset once the validator authored a block.
+        XStaking::on_validator_authored(&20);
+        assert_eq!(XStaking::offline_reports_in_grace(&20), 0);
+        XStaking::on_offline_validator(&20);
+        XStaking::on_offline_validator(&20);
+        assert!(XStaking::offline_validators_per_session().is_empty());
+
+        // Sustained offline reports exceed the grace period.
+        XStaking::on_offline_validator(&20);
+        assert_eq!(XStaking::missed_of_per_session(&20), 1);
+        assert_eq!(XStaking::offline_validators_per_session(), vec![20]);
+    });
+}
+
+#[test]
+fn dividend_rounding_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        // 50 * 1 / 3 = 16.67
+        assert_eq!(XStaking::dividend_rounding(), DividendRounding::Floor);
+        assert_eq!(compute_dividend::<Test>(1, 3, &5), 16);
+
+        assert_ok!(XStaking::set_dividend_rounding(DividendRounding::Nearest));
+        assert_eq!(compute_dividend::<Test>(1, 3, &5), 17);
+        // 50 * 1 / 4 = 12.5
+        assert_eq!(compute_dividend::<Test>(1, 4, &5), 13);
+        // 50 * 1 / 6 = 8.33
+        assert_eq!(compute_dividend::<Test>(1, 6, &5), 8);
+        // never more than the jackpot.
+        assert_eq!(compute_dividend::<Test>(3, 3, &5), 50);
+    });
+}
+
+#[test]
+fn offline_validator_with_auto_kick_disabled_should_stay_active() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        assert!(XStaking::auto_kick(&10));
+        assert_ok!(XStaking::set_auto_kick(Origin::signed(20), false));
+        assert!(!XStaking::auto_kick(&20));
+        assert_noop!(
+            XStaking::set_auto_kick(Origin::signed(5), false),
+            "Cannot set auto kick if transactor is not an intention."
+        );
+
+        let jackpot_10 = XStaking::jackpot_accountid_for_unsafe(&10);
+        let jackpot_20 = XStaking::jackpot_accountid_for_unsafe(&20);
+        assert_ok!(XAssets::pcx_issue(&jackpot_10, 100));
+        assert_ok!(XAssets::pcx_issue(&jackpot_20, 100));
+
+        let mut validators = vec![10, 20, 30, 40, 50];
+        for v in [10, 20].iter() {
+            <MissedOfPerSession<Test>>::insert(v, 1);
+            XStaking::slash_active_offline_validator(v, 0, &mut validators);
+        }
+
+        // Both jackpots can't afford the penalty and are slashed.
+        assert_eq!(XAssets::pcx_free_balance(&jackpot_10), 0);
+        assert_eq!(XAssets::pcx_free_balance(&jackpot_20), 0);
+
+        // Only the one with auto kick is kicked.
+        assert!(!XStaking::is_active(&10));
+        assert!(XStaking::is_active(&20));
+        assert_eq!(validators, vec![20, 30, 40, 50]);
+    });
+}
+
+#[test]
+fn slash_ratio_per_offence_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        assert_noop!(
+            XStaking::set_slash_ratio(OffenceKind::Offline, Some(1001)),
+            "The slash ratio can not exceed 1000 permill."
+        );
+        assert_ok!(XStaking::set_slash_ratio(OffenceKind::Offline, Some(100)));
+        assert_ok!(XStaking::set_slash_ratio(
+            OffenceKind::DoubleSign,
+            Some(200)
+        ));
+
+        let jackpot_10 = XStaking::jackpot_accountid_for_unsafe(&10);
+        let jackpot_20 = XStaking::jackpot_accountid_for_unsafe(&20);
+        let jackpot_30 = XStaking::jackpot_accountid_for_unsafe(&30);
+        for jackpot in [jackpot_10, jackpot_20, jackpot_30].iter() {
+            assert_ok!(XAssets::pcx_issue(jackpot, 1000));
+        }
+
+        let mut validators = vec![10, 20, 30, 40];
+        <MissedOfPerSession<Test>>::insert(&10, 1);
+        XStaking::slash_active_offline_validator(&10, 0, &mut validators);
+        assert_eq!(XAssets::pcx_free_balance(&jackpot_10), 900);
+
+        assert_eq!(XStaking::slash_double_signer(&20), Ok(200));
+        assert_eq!(XAssets::pcx_free_balance(&jackpot_20), 800);
+
+        // The whole jackpot of the double signer is slashed by default.
+        assert_ok!(XStaking::set_slash_ratio(OffenceKind::DoubleSign, None));
+        assert_eq!(XStaking::slash_double_signer(&30), Ok(1000));
+        assert_eq!(XAssets::pcx_free_balance(&jackpot_30), 0);
+    });
+}
+
+#[test]
+fn validator_profile_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        assert_eq!(XStaking::validator_profile(&1), None);
+
+        assert_ok!(XStaking::register_with_self_bond(
+            Origin::signed(1),
+            b"name".to_vec(),
+            6
+        ));
+        assert_ok!(XStaking::nominate(Origin::signed(2), 1.into(), 5, vec![]));
+        assert_ok!(XStaking::nominate(Origin::signed(2), 1.into(), 5, vec![]));
+
+        let jackpot_account = XStaking::jackpot_accountid_for_unsafe(&1);
+        assert_ok!(XAssets::pcx_issue(&jackpot_account, 100));
+
+        assert_eq!(
+            XStaking::validator_profile(&1),
+            Some(ValidatorProfile {
+                total_nomination: 16,
+                jackpot_account,
+                jackpot_balance: 100,
+                is_active: XStaking::is_active(&1),
+                nominator_count: 2,
+            })
+        );
+    });
+}
+
+#[test]
+fn nominator_count_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        assert_ok!(XStaking::register(Origin::signed(1), b"name".to_vec(),));
+        assert_eq!(XStaking::nominator_count(&1), 0);
+
+        assert_ok!(XStaking::nominate(Origin::signed(2), 1.into(), 5, vec![]));
+        assert_ok!(XStaking::nominate(Origin::signed(2), 1.into(), 5, vec![]));
+        assert_ok!(XStaking::nominate(Origin::signed(3), 1.into(), 5, vec![]));
+        assert_eq!(XStaking::nominator_count(&1), 2);
+
+        assert_ok!(XStaking::unnominate(Origin::signed(2), 1.into(), 4, vec![]));
+        assert_eq!(XStaking::nominator_count(&1), 2);
+        // the nomination goes to zero.
+        assert_ok!(XStaking::unnominate(Origin::signed(2), 1.into(), 6, vec![]));
+        assert_eq!(XStaking::nominator_count(&1), 1);
+
+        // nominate again.
+        assert_ok!(XStaking::nominate(Origin::signed(2), 1.into(), 1, vec![]));
+        assert_eq!(XStaking::nominator_count(&1), 2);
+
+        // backfill the count recorded before NominatorCount existed.
+        <NominatorCount<Test>>::remove(&1);
+        assert_noop!(
+            XStaking::backfill_nominator_count(2, vec![3]),
+            "Cannot backfill the nominator count of non-intention."
+        );
+        // 4 has no nomination to 1, 2 is duplicated.
+        assert_ok!(XStaking::backfill_nominator_count(1, vec![3, 2, 4, 2]));
+        assert_eq!(XStaking::nominator_count(&1), 2);
+    });
+}
+
+#[test]
+fn rejected_nomination_should_emit_event_if_enabled() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        assert_ok!(XStaking::register_with_self_bond(
+            Origin::signed(1),
+            b"name".to_vec(),
+            6
+        ));
+        assert_ok!(XStaking::nominate(Origin::signed(2), 1.into(), 5, vec![]));
+
+        // off by default
+        let events = System::events().len();
+        assert_noop!(
+            XStaking::unnominate(Origin::signed(2), 1.into(), 6, vec![]),
+            "Cannot unnominate if greater than your revokable nomination."
+        );
+        assert_eq!(System::events().len(), events);
+
+        assert_ok!(XStaking::set_nomination_rejection_events(true));
+        assert_eq!(
+            XStaking::unnominate(Origin::signed(2), 1.into(), 6, vec![]),
+            Err("Cannot unnominate if greater than your revokable nomination.")
+        );
+        assert_eq!(System::events().len(), events + 1);
+    });
+}
+
+#[test]
+fn reward_curve_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        let initial_reward = XStaking::initial_reward();
+        assert_eq!(XStaking::this_session_reward(), initial_reward);
+
+        assert_noop!(
+            XStaking::set_reward_curve(vec![(2, 500), (2, 100)]),
+            "The session indexes of the reward curve should be strictly ascending."
+        );
+        assert_ok!(XStaking::set_reward_curve(vec![
+            (0, 1000),
+            (2, 500),
+            (4, 100)
+        ]));
+
+        let reward_at = |index| {
+            xsession::CurrentIndex::<Test>::put(index);
+            XStaking::this_session_reward()
+        };
+        assert_eq!(reward_at(0), 1000);
+        assert_eq!(reward_at(1), 1000);
+        assert_eq!(reward_at(2), 500);
+        assert_eq!(reward_at(3), 500);
+        assert_eq!(reward_at(10), 100);
+
+        // back to the default
+        assert_ok!(XStaking::set_reward_curve(vec![]));
+        assert_eq!(reward_at(10), initial_reward);
+    });
+}
+
+#[test]
+fn nomination_cap_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        assert_ok!(XStaking::register_with_self_bond(
+            Origin::signed(1),
+            b"name".to_vec(),
+            6
+        ));
+        assert_eq!(XStaking::nomination_cap(), None);
+
+        assert_noop!(
+            XStaking::set_max_nomination_per_validator(NominationCap::Fraction(10_001)),
+            "The fraction of nomination cap can not exceed 10_000."
+        );
+
+        let current = XStaking::total_nomination_of(&1);
+        assert_ok!(XStaking::set_max_nomination_per_validator(
+            NominationCap::Absolute(current + 5)
+        ));
+        assert_eq!(XStaking::nomination_cap(), Some(current + 5));
+
+        assert_ok!(XStaking::nominate(Origin::signed(2), 1.into(), 3, vec![]));
+        assert_noop!(
+            XStaking::nominate(Origin::signed(2), 1.into(), 3, vec![]),
+            "Cannot (re)nominate if the total nomination of the target would exceed the cap."
+        );
+        assert_ok!(XStaking::nominate(Origin::signed(2), 1.into(), 2, vec![]));
+        assert_eq!(XStaking::total_nomination_of(&1), current + 5);
+    });
+}
+
+#[test]
+fn uptime_score_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        let validators = XStaking::validators()
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect::<Vec<_>>();
+        assert_eq!(validators, vec![10, 20, 30, 40]);
+        assert_eq!(XStaking::uptime_score(&10), 0);
+
+        // the slot duration is the doubled minimum period.
+        let slot = |i: u64| i * 6;
+        assert_eq!(
+            XStaking::expected_producer_at(&validators, slot(5)),
+            Some(20)
+        );
+        assert_eq!(
+            XStaking::expected_producer_at(&validators, slot(5) + 5),
+            Some(20)
+        );
+        assert_eq!(XStaking::expected_producer_at(&[], slot(5)), None);
+
+        // each validator produces the blocks of its 4 slots in turn.
+        let mut history = (0..16u64)
+            .map(|i| (i + 1, validators[i as usize % 4], slot(i)))
+            .collect::<Vec<_>>();
+        xsystem::RecentBlockProducers::<Test>::put(history.clone());
+        for v in validators.iter() {
+            assert_eq!(XStaking::uptime_score(v), 100);
+        }
+
+        // 10 misses half of its slots, which are produced by 20 out of turn.
+        history[0].1 = 20;
+        history[4].1 = 20;
+        xsystem::RecentBlockProducers::<Test>::put(history.clone());
+        assert_eq!(XStaking::uptime_score(&10), 50);
+        assert_eq!(XStaking::uptime_score(&20), 100);
+        assert_eq!(XStaking::uptime_score(&30), 100);
+
+        // 30 misses one of its slots, which is left empty.
+        history.remove(2);
+        xsystem::RecentBlockProducers::<Test>::put(history);
+        assert_eq!(XStaking::uptime_score(&30), 75);
+        assert_eq!(XStaking::uptime_score(&40), 100);
+
+        // only the latest 8 blocks are counted.
+        assert_ok!(XStaking::set_uptime_window(8.into()));
+        assert_eq!(XStaking::uptime_score(&10), 100);
+    });
+}
+
+#[test]
+fn uptime_score_should_follow_validator_set() {
+    with_externalities(&mut new_test_ext(), || {
+        // 4 validators in the blocks 1-8, only 10 and 20 in the blocks 9-16.
+        ValidatorSetHistory::<Test>::put(vec![(1, vec![10, 20, 30, 40]), (2, vec![10, 20])]);
+        SessionStartBlock::<Test>::insert(1, 1);
+        SessionStartBlock::<Test>::insert(2, 9);
+
+        let mut history = (0..16u64)
+            .map(|i| {
+                let producer = if i < 8 {
+                    [10, 20, 30, 40][i as usize % 4]
+                } else {
+                    [10, 20][i as usize % 2]
+                };
+                (i + 1, producer, i * 6)
+            })
+            .collect::<Vec<_>>();
+        xsystem::RecentBlockProducers::<Test>::put(history.clone());
+        // 30 and 40 are expected to produce in 2 slots only.
+        for v in [10, 20, 30, 40].iter() {
+            assert_eq!(XStaking::uptime_score(v), 100);
+        }
+
+        // 10 produces in 4 of its 6 slots, 2 in 1-8 and 4 in 9-16.
+        history[8].1 = 20;
+        history[10].1 = 20;
+        xsystem::RecentBlockProducers::<Test>::put(history);
+        assert_eq!(XStaking::uptime_score(&10), 66);
+        assert_eq!(XStaking::uptime_score(&20), 100);
+
+        // the blocks before the oldest recorded session are not counted.
+        ValidatorSetHistory::<Test>::put(vec![(2, vec![10, 20])]);
+        SessionStartBlock::<Test>::remove(1);
+        assert_eq!(XStaking::uptime_score(&10), 50);
+        assert_eq!(XStaking::uptime_score(&30), 0);
+    });
+}
@@ -40,6 +40,66 @@ impl WeightType {
     }
 }
 
+/// How the dividend is rounded when it can't be divided exactly.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum DividendRounding {
+    /// Always round down, the residual dust remains in the jackpot.
+    Floor,
+    /// Round to the nearest integer, half rounds up.
+    Nearest,
+}
+
+impl Default for DividendRounding {
+    fn default() -> Self {
+        DividendRounding::Floor
+    }
+}
+
+/// The cap of the total nomination of each validator.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum NominationCap<Balance> {
+    /// No cap.
+    Unlimited,
+    /// The absolute amount of the total nomination.
+    Absolute(Balance),
+    /// The fraction of the total nomination of all the intentions, in parts per 10_000.
+    Fraction(u32),
+}
+
+impl<Balance> Default for NominationCap<Balance> {
+    fn default() -> Self {
+        NominationCap::Unlimited
+    }
+}
+
+/// The misbehavior of a validator which could be slashed.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum OffenceKind {
+    /// Missed blocks when being an active validator.
+    Offline,
+    /// Signed two different blocks at the same height.
+    DoubleSign,
+}
+
+/// Why a nomination attempt is rejected.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub enum NominationRejectReason {
+    ZeroValue,
+    NotIntention,
+    InsufficientBalance,
+    NotNominee,
+    SelfBonded,
+    ExceedRevokable,
+    UpperBoundReached,
+    RenominationNotExpired,
+    UnbondEntriesLimitReached,
+    NominationCapReached,
+}
+
 /// RewardHolder includes intention as well as tokens.
 #[derive(PartialEq, Eq, Clone, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
@@ -266,3 +326,15 @@ pub struct IntentionInfoCommon<AccountId, Balance, SessionKey, BlockNumber> {
     pub self_bonded: Balance,
     pub is_validator: bool,
 }
+
+/// The profile of an intention for the validator detail page.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ValidatorProfile<AccountId, Balance> {
+    pub total_nomination: Balance,
+    pub jackpot_account: AccountId,
+    pub jackpot_balance: Balance,
+    pub is_active: bool,
+    pub nominator_count: u32,
+}
@@ -15,7 +15,18 @@ pub fn compute_dividend<T: Trait>(
 ) -> T::Balance {
     let total_jackpot = xassets::Module::<T>::pcx_free_balance(&claimee_jackpot);
     let dividend = match source_vote_weight.checked_mul(total_jackpot.saturated_into()) {
-        Some(x) => ((x / target_vote_weight) as u64).into(),
+        Some(x) => {
+            let dividend = match Module::<T>::dividend_rounding() {
+                DividendRounding::Floor => x / target_vote_weight,
+                DividendRounding::Nearest => {
+                    let remainder = x % target_vote_weight;
+                    let round_up = remainder >= target_vote_weight - remainder;
+                    x / target_vote_weight + u128::from(round_up)
+                }
+            };
+            // Never distribute more than what the jackpot holds.
+            (dividend.min(u128::from(total_jackpot.saturated_into::<u64>())) as u64).into()
+        }
         None => {
             error!(
                 "[compute_dividvid] overflow: source_vote_weight({:?}) * total_jackpot({:?})",
@@ -67,6 +78,7 @@ impl<T: Trait> Module<T> {
         delta: &Delta,
     ) {
         let key = (source.clone(), target.clone());
+        let old_nomination = Self::revokable_of(&key);
 
         let record_result = Self::try_get_nomination_record(&key);
         if record_result.is_ok() && source_vote_weight <= u128::from(u64::max_value()) {
@@ -88,6 +100,13 @@ impl<T: Trait> Module<T> {
             record_v1.set_state(source_vote_weight, current_block, delta);
             <NominationRecordsV1<T>>::insert(&key, record_v1);
         }
+
+        let new_nomination = Self::revokable_of(&key);
+        if old_nomination.is_zero() && !new_nomination.is_zero() {
+            <NominatorCount<T>>::mutate(target, |count| *count += 1);
+        } else if !old_nomination.is_zero() && new_nomination.is_zero() {
+            <NominatorCount<T>>::mutate(target, |count| *count = count.saturating_sub(1));
+        }
     }
 
     pub(super) fn apply_update_intention_vote_weight(
@@ -43,7 +43,9 @@ impl<T: Trait> OnReward<T::AccountId, T::Balance> for Module<T> {
             value
         );
         let addr = T::DetermineTokenJackpotAccountId::accountid_for_unsafe(token);
-        let _ = xassets::Module::<T>::pcx_issue(&addr, value);
+        if xassets::Module::<T>::pcx_issue(&addr, value).is_ok() && !value.is_zero() {
+            Self::deposit_event(RawEvent::JackpotFunded(token.clone(), value));
+        }
     }
 }
 
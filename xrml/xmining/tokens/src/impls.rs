@@ -139,6 +139,7 @@ impl<T: Trait> Claim<T::AccountId, T::Balance> for Module<T> {
         xtokens::Module::<T>::can_claim(claimer, claimee, dividend, current_block)?;
 
         Self::allocate_dividend(claimer, claimee, &claimee_jackpot, dividend)?;
+        xtokens::Module::<T>::deposit_jackpot_drained_event(claimee, dividend);
 
         xtokens::Module::<T>::apply_state_change_on_claim(
             claimer,
@@ -43,6 +43,16 @@ decl_event!(
         DepositorReward(AccountId, Token, Balance),
         DepositorClaim(AccountId, Token, u64, u64, Balance),
         DepositorClaimV1(AccountId, Token, u128, u128, Balance),
+        /// The total deposit weight of psedu intention is recomputed, (token, old, new).
+        PseduWeightRecomputed(Token, u128, u128),
+        /// All the available staking and cross mining dividends are claimed, with the total amount.
+        EverythingClaimed(AccountId, Balance),
+        /// The psedu intention without any deposit and jackpot balance is removed.
+        PseduIntentionPruned(Token),
+        /// The jackpot of the psedu intention received the reward, (token, amount).
+        JackpotFunded(Token, Balance),
+        /// The reward or dividend is paid out of the jackpot of the psedu intention, (token, amount).
+        JackpotDrained(Token, Balance),
     }
 );
 
@@ -72,6 +82,14 @@ decl_module! {
             <Self as Claim<T::AccountId, T::Balance>>::claim(&who, &token)?;
         }
 
+        /// Claim all the available staking dividends and cross mining dividends at once,
+        /// the ones failed to claim, e.g. restricted, are skipped.
+        fn claim_everything(origin) {
+            let who = system::ensure_signed(origin)?;
+            let claimed = Self::apply_claim_everything(&who);
+            Self::deposit_event(RawEvent::EverythingClaimed(who, claimed));
+        }
+
         /// Set the discount for converting the cross-chain asset to PCX based on the market value.
         fn set_token_discount(token: Token, value: u32) {
             ensure!(value <= 100, "TokenDiscount cannot exceed 100.");
@@ -83,6 +101,11 @@ decl_module! {
             DepositReward::<T>::put(value);
         }
 
+        /// Whether the deposit reward of the token is only granted on the first deposit of each account.
+        fn set_deposit_reward_only_first(token: Token, only_first: bool) {
+            DepositRewardOnlyFirst::<T>::insert(token, only_first);
+        }
+
         fn set_claim_restriction(token: Token, new: (u32, T::BlockNumber)) {
             <ClaimRestrictionOf<T>>::insert(token, new);
         }
@@ -179,6 +202,57 @@ decl_module! {
             }
         }
 
+        /// Recompute the total deposit weight of psedu intention from the deposit records, at most
+        /// `max_depositors` depositors are summed up in a call and the profile is rewritten by the
+        /// call reaching the last one. The depositors must be backfilled completely.
+        fn recompute_psedu_weight(token: Token, max_depositors: u32) {
+            ensure!(
+                Self::psedu_intentions().contains(&token),
+                "Cannot recompute the weight of unsupport token."
+            );
+            ensure!(
+                Self::depositors_backfilled(&token),
+                "Cannot recompute the weight before the depositors are backfilled."
+            );
+            ensure!(max_depositors > 0, "Cannot recompute the weight of zero depositors.");
+            Self::apply_recompute_psedu_weight(&token, max_depositors);
+        }
+
+        /// Index the depositors whose records were created before DepositorAt existed.
+        ///
+        /// Accounts without a deposit record of the token or already indexed are skipped.
+        fn backfill_depositors(token: Token, depositors: Vec<T::AccountId>) {
+            ensure!(
+                Self::psedu_intentions().contains(&token),
+                "Cannot backfill the depositors of unsupport token."
+            );
+            for who in depositors {
+                if Self::has_deposit_record(&who, &token) {
+                    Self::note_depositor(&who, &token);
+                }
+            }
+        }
+
+        /// Mark whether all the depositors of the token are indexed, which `recompute_psedu_weight`
+        /// relies on.
+        fn set_depositors_backfilled(token: Token, backfilled: bool) {
+            if backfilled {
+                <DepositorsBackfilled<T>>::insert(&token, true);
+            } else {
+                <DepositorsBackfilled<T>>::remove(&token);
+            }
+        }
+
+        /// Remove the psedu intention which has no deposit weight and no jackpot balance.
+        fn prune_empty_psedu_intention(token: Token) {
+            ensure!(
+                Self::psedu_intentions().contains(&token),
+                "Cannot prune unsupport token."
+            );
+            Self::apply_prune_empty_psedu_intention(&token)?;
+            Self::deposit_event(RawEvent::PseduIntentionPruned(token));
+        }
+
         /// Add/Update airdrop assets distribution ratio.
         pub fn set_airdrop_distribution_ratio(token: Token, new_shares: u32) {
             ensure!(xassets::AssetInfo::<T>::exists(&token), "Token does not exist!");
@@ -240,6 +314,9 @@ decl_module! {
 /// 302_400 blocks per week.
 pub const BLOCKS_PER_WEEK: u64 = 60 * 60 * 24 * 7 / 2;
 
+/// Maximum number of accounts checked in one `claim_eligibility` query.
+pub const MAX_CLAIM_ELIGIBILITY_ACCOUNTS: usize = 100;
+
 decl_storage! {
     trait Store for Module<T: Trait> as XTokens {
         pub TokenDiscount get(token_discount) build(|config: &GenesisConfig<T>| {
@@ -262,9 +339,30 @@ decl_storage! {
 
         pub DepositRecordsV1 get(deposit_records_v1): map (T::AccountId, Token) => Option<DepositVoteWeightV1<T::BlockNumber>>;
 
+        /// The number of the accounts indexed in `DepositorAt` for the token.
+        pub DepositorCount get(depositor_count): map Token => u32;
+
+        /// The accounts having a deposit record of the token by position, since DepositRecords is not enumerable.
+        pub DepositorAt get(depositor_at): map (Token, u32) => Option<T::AccountId>;
+
+        /// The position of the account in `DepositorAt` of the token.
+        pub DepositorIndexOf get(depositor_index_of): map (T::AccountId, Token) => Option<u32>;
+
+        /// Whether the depositors created before `DepositorAt` existed are all indexed.
+        pub DepositorsBackfilled get(depositors_backfilled): map Token => bool;
+
+        /// The recomputation of the total deposit weight of psedu intention in progress.
+        pub PseduWeightRecomputationOf get(psedu_weight_recomputation_of): map Token => Option<PseduWeightRecomputation<T::BlockNumber>>;
+
         /// when deposit success, reward some pcx to user for claiming. Default is 100000 = 0.001 PCX; 0.001*100000000
         pub DepositReward get(deposit_reward): T::Balance = 100_000.into();
 
+        /// Only reward the first deposit of each account for the token, otherwise reward every deposit.
+        pub DepositRewardOnlyFirst get(deposit_reward_only_first): map Token => bool;
+
+        /// Whether the account has received the deposit reward of the token.
+        pub HasReceivedDepositReward get(has_received_deposit_reward): map (T::AccountId, Token) => bool;
+
         /// (SDOT, 1u32), (LBTC, 1u32) means SDOT:LBTC = 1:1
         pub AirdropDistributionRatioMap get(airdrop_distribution_ratio_map): linked_map Token => u32;
 
@@ -323,6 +421,27 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Reject the claim in the same block as the last deposit of the claimer, which
+    /// makes the vote weight accrual unexploitable by depositing and claiming at once.
+    fn not_deposited_at(
+        who: &T::AccountId,
+        token: &Token,
+        current_block: T::BlockNumber,
+    ) -> Result {
+        let last_update = match Self::try_get_deposit_record(&(who.clone(), token.clone())) {
+            Ok(d) => d.last_deposit_weight_update,
+            Err(d1) => d1.last_deposit_weight_update,
+        };
+        if last_update >= current_block {
+            warn!(
+                "{:?} cannot claim in the same block as the deposit, last deposit: {:?}",
+                who, last_update
+            );
+            return Err("Cannot claim in the same block as the deposit.");
+        }
+        Ok(())
+    }
+
     /// Whether the claimer is able to claim the dividend at the given height.
     fn can_claim(
         who: &T::AccountId,
@@ -330,12 +449,55 @@ impl<T: Trait> Module<T> {
         dividend: T::Balance,
         current_block: T::BlockNumber,
     ) -> Result {
+        Self::not_deposited_at(who, token, current_block)?;
         let (staking_requirement, interval) = Self::claim_restriction_of(token);
         Self::contribute_enough_staking(who, dividend, staking_requirement)?;
         Self::passed_enough_interval(who, token, interval, current_block)?;
         Ok(())
     }
 
+    /// Whether each of the accounts could claim the dividend of the token at present,
+    /// along with the reason if not.
+    ///
+    /// Only the first `MAX_CLAIM_ELIGIBILITY_ACCOUNTS` accounts are checked.
+    pub fn claim_eligibility(
+        token: &Token,
+        accounts: Vec<T::AccountId>,
+    ) -> Vec<(T::AccountId, bool, Option<&'static str>)> {
+        let current_block = <system::Module<T>>::block_number();
+        let claimee_jackpot = T::DetermineTokenJackpotAccountId::accountid_for_unsafe(token);
+
+        accounts
+            .into_iter()
+            .take(MAX_CLAIM_ELIGIBILITY_ACCOUNTS)
+            .map(|who| {
+                let result = <Self as ComputeWeight<T::AccountId>>::settle_weight_on_claim(
+                    &who,
+                    token,
+                    current_block.saturated_into::<u64>(),
+                )
+                .and_then(|((source_vote_weight, _), (target_vote_weight, _))| {
+                    let dividend = xstaking::compute_dividend::<T>(
+                        source_vote_weight,
+                        target_vote_weight,
+                        &claimee_jackpot,
+                    );
+                    Self::can_claim(&who, token, dividend, current_block)
+                });
+                match result {
+                    Ok(()) => (who, true, None),
+                    Err(e) => (who, false, Some(e)),
+                }
+            })
+            .collect()
+    }
+
+    fn deposit_jackpot_drained_event(token: &Token, value: T::Balance) {
+        if !value.is_zero() {
+            Self::deposit_event(RawEvent::JackpotDrained(token.clone(), value));
+        }
+    }
+
     fn deposit_claim_event(
         source_weight_info: (u128, bool),
         target_weight_info: (u128, bool),
@@ -364,6 +526,77 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// Claim from all the nominees and psedu intentions, returns the total amount claimed.
+    pub fn apply_claim_everything(who: &T::AccountId) -> T::Balance {
+        let before = xassets::Module::<T>::pcx_free_balance(who);
+
+        for intention in Self::claimable_intentions(who) {
+            if let Err(_e) =
+                <xstaking::Module<T> as Claim<T::AccountId, T::Balance>>::claim(who, &intention)
+            {
+                debug!(
+                    "[claim_everything] skip claiming from intention: {:?}, reason: {:?}",
+                    intention, _e
+                );
+            }
+        }
+
+        for token in Self::claimable_tokens(who) {
+            if let Err(_e) = <Self as Claim<T::AccountId, T::Balance>>::claim(who, &token) {
+                debug!(
+                    "[claim_everything] skip claiming from token: {:?}, reason: {:?}",
+                    token!(token),
+                    _e
+                );
+            }
+        }
+
+        let after = xassets::Module::<T>::pcx_free_balance(who);
+        if after > before {
+            after - before
+        } else {
+            Zero::zero()
+        }
+    }
+
+    /// The number of the claims `claim_everything` would make for `who`.
+    pub fn claim_everything_count(who: &T::AccountId) -> u32 {
+        (Self::claimable_intentions(who).len() + Self::claimable_tokens(who).len()) as u32
+    }
+
+    /// The intentions nominated by `who`.
+    fn claimable_intentions(who: &T::AccountId) -> Vec<T::AccountId> {
+        xstaking::Module::<T>::intention_set()
+            .into_iter()
+            .filter(|intention| {
+                xstaking::Module::<T>::nomination_record_exists(&(who.clone(), intention.clone()))
+            })
+            .collect()
+    }
+
+    /// The psedu intentions deposited by `who`, except the ones which could not be claimed.
+    fn claimable_tokens(who: &T::AccountId) -> Vec<Token> {
+        let pcx = <xassets::Module<T> as ChainT>::TOKEN.to_vec();
+        let lbtc = <xbitcoin::lockup::Module<T> as ChainT>::TOKEN.to_vec();
+        let sdot = <xsdot::Module<T> as ChainT>::TOKEN.to_vec();
+        Self::psedu_intentions()
+            .into_iter()
+            .filter(|token| *token != pcx && *token != lbtc && *token != sdot)
+            .filter(|token| Self::has_deposit_record(who, token))
+            .collect()
+    }
+
+    /// The sum of the latest vote weight of all the psedu intentions at the current block.
+    pub fn total_outstanding_weight() -> u128 {
+        let current_block = <system::Module<T>>::block_number().saturated_into::<u64>();
+        Self::psedu_intentions()
+            .iter()
+            .map(|token| {
+                <Self as ComputeWeight<T::AccountId>>::settle_claimee_weight(token, current_block).0
+            })
+            .fold(0u128, |acc, weight| acc.saturating_add(weight))
+    }
+
     fn try_get_deposit_record(
         key: &(T::AccountId, Token),
     ) -> result::Result<DepositVoteWeight<T::BlockNumber>, DepositVoteWeightV1<T::BlockNumber>>
@@ -394,9 +627,45 @@ impl<T: Trait> Module<T> {
         token: &Token,
         current_block: T::BlockNumber,
     ) {
-        let key = (who.clone(), token.clone());
-        if !<DepositRecords<T>>::exists(&key) {
+        if !Self::has_deposit_record(who, token) {
+            let key = (who.clone(), token.clone());
             <DepositRecords<T>>::insert(&key, DepositVoteWeight::new(0u64, current_block));
+            Self::note_depositor(who, token);
+        }
+    }
+
+    fn has_deposit_record(who: &T::AccountId, token: &Token) -> bool {
+        let key = (who.clone(), token.clone());
+        <DepositRecords<T>>::exists(&key) || <DepositRecordsV1<T>>::get(&key).is_some()
+    }
+
+    /// Index the depositor of the token when its deposit record is created.
+    fn note_depositor(who: &T::AccountId, token: &Token) {
+        let key = (who.clone(), token.clone());
+        if <DepositorIndexOf<T>>::exists(&key) {
+            return;
+        }
+        let index = Self::depositor_count(token);
+        <DepositorAt<T>>::insert((token.clone(), index), who.clone());
+        <DepositorIndexOf<T>>::insert(&key, index);
+        <DepositorCount<T>>::insert(token, index + 1);
+    }
+
+    /// Restart the recomputation of the psedu intention if the weight of a depositor already
+    /// summed up is changed, since the sum assumes their balances are kept.
+    fn note_depositor_weight_changed(who: &T::AccountId, token: &Token) {
+        if let Some(progress) = Self::psedu_weight_recomputation_of(token) {
+            let summed = Self::depositor_index_of(&(who.clone(), token.clone()))
+                .map(|index| index < progress.next)
+                .unwrap_or(false);
+            if summed {
+                warn!(
+                    "[recompute_psedu_weight] restart since the weight of a summed depositor is changed, token: {:?}, who: {:?}",
+                    token!(token),
+                    who
+                );
+                <PseduWeightRecomputationOf<T>>::remove(token);
+            }
         }
     }
 
@@ -408,9 +677,24 @@ impl<T: Trait> Module<T> {
             token!(token)
         );
 
+        let only_first = Self::deposit_reward_only_first(token);
+        let key = (source.clone(), token.clone());
+        if only_first && Self::has_received_deposit_reward(&key) {
+            return Ok(());
+        }
+
         // when deposit(issue) success, reward some pcx for account to claim
         let reward_value = Self::deposit_reward();
+        let jackpot = T::DetermineTokenJackpotAccountId::accountid_for_unsafe(token);
+        let before = xassets::Module::<T>::pcx_free_balance(&jackpot);
         xbridge_common::Module::<T>::reward_from_jackpot(token, source, reward_value);
+        // the reward is skipped if the jackpot is unable to pay.
+        let drained = before - xassets::Module::<T>::pcx_free_balance(&jackpot);
+        Self::deposit_jackpot_drained_event(token, drained);
+        // the depositor is still rewarded by the next deposit if it's not paid this time.
+        if only_first && !drained.is_zero() {
+            HasReceivedDepositReward::<T>::insert(&key, true);
+        }
 
         Self::deposit_event(RawEvent::DepositorReward(
             source.clone(),
@@ -430,6 +714,88 @@ impl<T: Trait> Module<T> {
         Self::update_psedu_intention_vote_weight(target, current_block);
     }
 
+    /// Sum up the latest weights of the next `max_depositors` depositors, and rewrite the psedu
+    /// intention profile with the sum once all the depositors are summed up. The sum of the
+    /// previous calls is brought to the current block with the balances of those depositors.
+    fn apply_recompute_psedu_weight(token: &Token, max_depositors: u32) {
+        let current_block = <system::Module<T>>::block_number();
+        let mut progress =
+            Self::psedu_weight_recomputation_of(token).unwrap_or(PseduWeightRecomputation {
+                next: 0,
+                weight: 0,
+                balance: 0,
+                block: current_block,
+            });
+        let elapsed = (current_block - progress.block).saturated_into::<u128>();
+        progress.weight = progress
+            .weight
+            .saturating_add(progress.balance.saturating_mul(elapsed));
+        progress.block = current_block;
+
+        let count = Self::depositor_count(token);
+        let end = count.min(progress.next.saturating_add(max_depositors));
+        for index in progress.next..end {
+            if let Some(who) = Self::depositor_at((token.clone(), index)) {
+                let (weight, _) = <Self as ComputeWeight<T::AccountId>>::settle_claimer_weight(
+                    &who,
+                    token,
+                    current_block.saturated_into::<u64>(),
+                );
+                let balance: u64 = xassets::Module::<T>::all_type_asset_balance(&who, token).into();
+                progress.weight = progress.weight.saturating_add(weight);
+                progress.balance = progress.balance.saturating_add(u128::from(balance));
+            }
+        }
+        progress.next = end;
+
+        if end < count {
+            info!(
+                "[recompute_psedu_weight] token: {:?}, summed: {:?}, depositors: {:?}",
+                token!(token),
+                end,
+                count
+            );
+            <PseduWeightRecomputationOf<T>>::insert(token, progress);
+            return;
+        }
+        <PseduWeightRecomputationOf<T>>::remove(token);
+
+        let (old, _) = <Self as ComputeWeight<T::AccountId>>::settle_claimee_weight(
+            token,
+            current_block.saturated_into::<u64>(),
+        );
+        let new = progress.weight;
+
+        info!(
+            "[recompute_psedu_weight] token: {:?}, old: {:?}, new: {:?}",
+            token!(token),
+            old,
+            new
+        );
+        Self::apply_update_psedu_intention_vote_weight(token, new, current_block);
+        Self::deposit_event(RawEvent::PseduWeightRecomputed(token.clone(), old, new));
+    }
+
+    fn apply_prune_empty_psedu_intention(token: &Token) -> Result {
+        let current_block = <system::Module<T>>::block_number().saturated_into::<u64>();
+        let (weight, _) =
+            <Self as ComputeWeight<T::AccountId>>::settle_claimee_weight(token, current_block);
+        ensure!(
+            weight == 0 && xassets::Module::<T>::all_type_total_asset_balance(token).is_zero(),
+            "Cannot prune the psedu intention which still has deposits."
+        );
+
+        let jackpot = T::DetermineTokenJackpotAccountId::accountid_for_unsafe(token);
+        ensure!(
+            xassets::Module::<T>::pcx_free_balance(&jackpot).is_zero(),
+            "Cannot prune the psedu intention which still has jackpot balance."
+        );
+
+        <PseduIntentions<T>>::mutate(|v| v.retain(|t| t != token));
+        info!("[prune_empty_psedu_intention] token: {:?}", token!(token));
+        Ok(())
+    }
+
     #[cfg(feature = "std")]
     pub fn bootstrap_update_vote_weight(source: &T::AccountId, target: &Token) {
         let current_block = <system::Module<T>>::block_number();
@@ -12,7 +12,7 @@ use primitives::{
 };
 use runtime_io::with_externalities;
 use substrate_primitives::{Blake2Hasher, H256};
-use support::impl_outer_origin;
+use support::{impl_outer_event, impl_outer_origin};
 
 use xassets::{Asset, Chain, ChainT, Token};
 use xstaking::Delta;
@@ -21,6 +21,17 @@ impl_outer_origin! {
     pub enum Origin for Test {}
 }
 
+mod xtokens {
+    // This hack is required for `impl_outer_event!`.
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        xtokens<T>,
+    }
+}
+
 // Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Test;
@@ -41,7 +52,7 @@ impl system::Trait for Test {
     type AccountId = u64;
     type Lookup = Indices;
     type Header = Header;
-    type Event = ();
+    type Event = TestEvent;
     type Log = DigestItem;
 }
 
@@ -74,6 +85,7 @@ impl xaccounts::IntentionJackpotAccountIdFor<u64> for DummyDetermineIntentionJac
 impl xassets::Trait for Test {
     type Balance = u64;
     type OnNewAccount = Indices;
+    type OnFreeBalanceZero = ();
     type Event = ();
     type OnAssetChanged = XTokens;
     type OnAssetRegisterOrRevoke = XTokens;
@@ -231,7 +243,7 @@ impl xbridge_common::Trait for Test {
 }
 
 impl Trait for Test {
-    type Event = ();
+    type Event = TestEvent;
 }
 
 pub struct DummyDetermineTokenJackpotAccountId;
@@ -624,6 +624,176 @@ fn claim_has_frequency_limit_should_work() {
     });
 }
 
+#[test]
+fn claim_eligibility_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(3);
+        XSession::check_rotate_session(System::block_number());
+
+        let xbtc = <XBitcoin as ChainT>::TOKEN.to_vec();
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_ok!(XAssets::issue(&xbtc, &101, 100));
+        assert_ok!(XTokens::set_claim_restriction(xbtc.clone(), (0u32, 1)));
+
+        System::set_block_number(4);
+        XSession::check_rotate_session(System::block_number());
+        XTokens::claim(Origin::signed(100), xbtc.clone()).unwrap();
+
+        System::set_block_number(5);
+        XSession::check_rotate_session(System::block_number());
+        assert_eq!(
+            XTokens::claim_eligibility(&xbtc, vec![100, 101, 102]),
+            vec![
+                (
+                    100,
+                    false,
+                    Some("Can only claim once per claim limiting period.")
+                ),
+                (101, true, None),
+                (102, false, Some("the vote weight of claimer is zero.")),
+            ]
+        );
+
+        let accounts = (0..MAX_CLAIM_ELIGIBILITY_ACCOUNTS as u64 + 1).collect::<Vec<_>>();
+        assert_eq!(
+            XTokens::claim_eligibility(&xbtc, accounts).len(),
+            MAX_CLAIM_ELIGIBILITY_ACCOUNTS
+        );
+    });
+}
+
+/// The depositors indexed in `DepositorAt` of the token in order.
+fn depositors(token: &Token) -> Vec<u64> {
+    (0..XTokens::depositor_count(token))
+        .filter_map(|index| XTokens::depositor_at((token.clone(), index)))
+        .collect()
+}
+
+#[test]
+fn recompute_psedu_weight_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(3);
+        XSession::check_rotate_session(System::block_number());
+
+        let xbtc = <XBitcoin as ChainT>::TOKEN.to_vec();
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_ok!(XAssets::issue(&xbtc, &101, 200));
+
+        System::set_block_number(5);
+        XSession::check_rotate_session(System::block_number());
+        assert_ok!(XAssets::issue(&xbtc, &101, 100));
+
+        System::set_block_number(6);
+        XSession::check_rotate_session(System::block_number());
+        let (expected, _) = XTokens::settle_claimee_weight(&xbtc, 6);
+        assert_eq!(expected, 100 * 3 + 200 * 3 + 100);
+
+        // desync the profile artificially.
+        assert_ok!(XTokens::set_psedu_intention_profs(
+            xbtc.clone(),
+            Some(1),
+            None
+        ));
+        assert_ne!(XTokens::settle_claimee_weight(&xbtc, 6).0, expected);
+
+        assert_eq!(depositors(&xbtc), vec![100, 101]);
+        assert_noop!(
+            XTokens::recompute_psedu_weight(xbtc.clone(), 1),
+            "Cannot recompute the weight before the depositors are backfilled."
+        );
+        assert_ok!(XTokens::set_depositors_backfilled(xbtc.clone(), true));
+        assert_noop!(
+            XTokens::recompute_psedu_weight(b"PCX".to_vec(), 1),
+            "Cannot recompute the weight of unsupport token."
+        );
+
+        // one depositor a page, the profile is rewritten by the last page.
+        assert_ok!(XTokens::recompute_psedu_weight(xbtc.clone(), 1));
+        assert_eq!(
+            XTokens::psedu_weight_recomputation_of(&xbtc),
+            Some(PseduWeightRecomputation {
+                next: 1,
+                weight: 100 * 3,
+                balance: 100,
+                block: 6
+            })
+        );
+        assert_ne!(XTokens::settle_claimee_weight(&xbtc, 6).0, expected);
+
+        System::set_block_number(7);
+        XSession::check_rotate_session(System::block_number());
+        assert_ok!(XTokens::recompute_psedu_weight(xbtc.clone(), 1));
+        assert_eq!(XTokens::psedu_weight_recomputation_of(&xbtc), None);
+        assert_eq!(
+            XTokens::psedu_intention_profiles(&xbtc),
+            PseduIntentionVoteWeight {
+                last_total_deposit_weight: (100 * 4 + 200 * 4 + 100 * 2) as u64,
+                last_total_deposit_weight_update: 7
+            }
+        );
+    });
+}
+
+#[test]
+fn recompute_psedu_weight_should_restart_on_summed_depositor_change() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        let xbtc = <XBitcoin as ChainT>::TOKEN.to_vec();
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_ok!(XAssets::issue(&xbtc, &101, 200));
+        assert_ok!(XTokens::set_depositors_backfilled(xbtc.clone(), true));
+
+        System::set_block_number(2);
+        XSession::check_rotate_session(System::block_number());
+        assert_ok!(XTokens::recompute_psedu_weight(xbtc.clone(), 1));
+
+        // the depositor not summed yet is summed up with the latest weight later.
+        assert_ok!(XAssets::issue(&xbtc, &101, 100));
+        assert!(XTokens::psedu_weight_recomputation_of(&xbtc).is_some());
+
+        // the balance of the summed depositor is changed.
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_eq!(XTokens::psedu_weight_recomputation_of(&xbtc), None);
+    });
+}
+
+#[test]
+fn backfill_depositors_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        XSession::check_rotate_session(System::block_number());
+
+        let xbtc = <XBitcoin as ChainT>::TOKEN.to_vec();
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_ok!(XAssets::issue(&xbtc, &101, 200));
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_eq!(depositors(&xbtc), vec![100, 101]);
+        assert_eq!(XTokens::depositor_index_of(&(101, xbtc.clone())), Some(1));
+
+        // records created before the index existed.
+        for (index, who) in [100, 101].iter().enumerate() {
+            <DepositorAt<Test>>::remove(&(xbtc.clone(), index as u32));
+            <DepositorIndexOf<Test>>::remove(&(*who, xbtc.clone()));
+        }
+        <DepositorCount<Test>>::remove(&xbtc);
+
+        assert_noop!(
+            XTokens::backfill_depositors(b"PCX".to_vec(), vec![100]),
+            "Cannot backfill the depositors of unsupport token."
+        );
+        assert_ok!(XTokens::backfill_depositors(xbtc.clone(), vec![101]));
+        // 102 has no deposit record, 101 is indexed already.
+        assert_ok!(XTokens::backfill_depositors(
+            xbtc.clone(),
+            vec![100, 102, 101, 100]
+        ));
+        assert_eq!(depositors(&xbtc), vec![101, 100]);
+        assert_eq!(XTokens::depositor_count(&xbtc), 2);
+    });
+}
+
 #[test]
 fn switch_to_u128_when_overflow() {
     with_externalities(&mut new_test_ext(), || {
@@ -712,3 +882,254 @@ fn switch_to_u128_when_overflow() {
         );
     });
 }
+
+#[test]
+fn claim_everything_should_work() {
+    let setup = || {
+        System::set_block_number(3);
+        XSession::check_rotate_session(System::block_number());
+
+        let xbtc = <XBitcoin as ChainT>::TOKEN.to_vec();
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_ok!(XTokens::set_claim_restriction(xbtc.clone(), (0u32, 1)));
+        assert_ok!(XAssets::pcx_issue(&100, 1_000_000));
+        assert_ok!(XStaking::nominate(
+            Origin::signed(100),
+            1.into(),
+            1_000_000,
+            vec![]
+        ));
+
+        System::set_block_number(4);
+        XSession::check_rotate_session(System::block_number());
+        System::set_block_number(5);
+        XSession::check_rotate_session(System::block_number());
+        xbtc
+    };
+
+    // claim one by one
+    let separate = with_externalities(&mut new_test_ext(), || {
+        let xbtc = setup();
+        let before = XAssets::pcx_free_balance(&100);
+        assert_ok!(XStaking::claim(Origin::signed(100), 1.into()));
+        let staking = XAssets::pcx_free_balance(&100) - before;
+        assert_ok!(XTokens::claim(Origin::signed(100), xbtc));
+        let total = XAssets::pcx_free_balance(&100) - before;
+        assert!(staking > 0 && total > staking);
+        total
+    });
+
+    with_externalities(&mut new_test_ext(), || {
+        assert_eq!(XTokens::claim_everything_count(&100), 0);
+        setup();
+        // one nominee and one psedu intention.
+        assert_eq!(XTokens::claim_everything_count(&100), 2);
+        let before = XAssets::pcx_free_balance(&100);
+        assert_ok!(XTokens::claim_everything(Origin::signed(100)));
+        assert_eq!(XAssets::pcx_free_balance(&100) - before, separate);
+    });
+}
+
+#[test]
+fn total_outstanding_weight_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        let (sdot, lbtc, xbtc) = tokens();
+
+        System::set_block_number(2);
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_ok!(XAssets::issue(&sdot, &101, 30));
+        System::set_block_number(4);
+        assert_ok!(XAssets::issue(&xbtc, &101, 50));
+        assert_ok!(XAssets::issue(&lbtc, &102, 20));
+
+        System::set_block_number(10);
+        // xbtc: 100 * (4 - 2) + 150 * (10 - 4) = 1100
+        // sdot: 30 * (10 - 2) = 240
+        // lbtc: 20 * (10 - 4) = 120
+        assert_eq!(XTokens::total_outstanding_weight(), 1100 + 240 + 120);
+
+        System::set_block_number(12);
+        // every token accumulates its total issuance for the 2 more blocks
+        assert_eq!(
+            XTokens::total_outstanding_weight(),
+            1460 + (150 + 30 + 20) * 2
+        );
+    });
+}
+
+#[test]
+fn prune_empty_psedu_intention_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        let (sdot, _, xbtc) = tokens();
+
+        System::set_block_number(2);
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        System::set_block_number(3);
+
+        assert_noop!(
+            XTokens::prune_empty_psedu_intention(xbtc.clone()),
+            "Cannot prune the psedu intention which still has deposits."
+        );
+        assert!(XTokens::psedu_intentions().contains(&xbtc));
+
+        assert_ok!(XTokens::prune_empty_psedu_intention(sdot.clone()));
+        assert!(!XTokens::psedu_intentions().contains(&sdot));
+        assert_noop!(
+            XTokens::prune_empty_psedu_intention(sdot),
+            "Cannot prune unsupport token."
+        );
+    });
+}
+
+#[test]
+fn claim_in_the_same_block_as_deposit_should_fail() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(3);
+        XSession::check_rotate_session(System::block_number());
+
+        let xbtc = <XBitcoin as ChainT>::TOKEN.to_vec();
+        assert_ok!(XTokens::set_claim_restriction(xbtc.clone(), (0u32, 0)));
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+
+        System::set_block_number(4);
+        XSession::check_rotate_session(System::block_number());
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_noop!(
+            XTokens::claim(Origin::signed(100), xbtc.clone()),
+            "Cannot claim in the same block as the deposit."
+        );
+
+        System::set_block_number(5);
+        XSession::check_rotate_session(System::block_number());
+        assert_ok!(XTokens::claim(Origin::signed(100), xbtc.clone()));
+    });
+}
+
+#[test]
+fn deposit_reward_only_first_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+
+        let (_, _, xbtc) = tokens();
+        let (_, _, xbtc_jackpot) = token_jackpot_accountids();
+        assert_ok!(XAssets::pcx_issue(&xbtc_jackpot, 1_000_000_000));
+        let reward = XTokens::deposit_reward();
+
+        // reward every deposit by default
+        let before = XAssets::pcx_free_balance(&100);
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_eq!(XAssets::pcx_free_balance(&100), before + reward * 2);
+
+        assert_ok!(XTokens::set_deposit_reward_only_first(xbtc.clone(), true));
+        let before = XAssets::pcx_free_balance(&101);
+        assert_ok!(XAssets::issue(&xbtc, &101, 100));
+        assert_eq!(XAssets::pcx_free_balance(&101), before + reward);
+        assert!(XTokens::has_received_deposit_reward(&(101, xbtc.clone())));
+
+        // the second deposit earns no reward
+        assert_ok!(XAssets::issue(&xbtc, &101, 100));
+        assert_eq!(XAssets::pcx_free_balance(&101), before + reward);
+    });
+}
+
+#[test]
+fn deposit_reward_only_first_should_wait_for_paid_reward() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+
+        let (_, _, xbtc) = tokens();
+        let (_, _, xbtc_jackpot) = token_jackpot_accountids();
+        assert_ok!(XTokens::set_deposit_reward_only_first(xbtc.clone(), true));
+        let reward = XTokens::deposit_reward();
+
+        // the empty jackpot is unable to pay, the first reward is not consumed
+        assert_eq!(XAssets::pcx_free_balance(&xbtc_jackpot), 0);
+        let before = XAssets::pcx_free_balance(&101);
+        assert_ok!(XAssets::issue(&xbtc, &101, 100));
+        assert_eq!(XAssets::pcx_free_balance(&101), before);
+        assert!(!XTokens::has_received_deposit_reward(&(101, xbtc.clone())));
+
+        // rewarded by the next deposit once the jackpot could pay
+        assert_ok!(XAssets::pcx_issue(&xbtc_jackpot, 1_000_000_000));
+        assert_ok!(XAssets::issue(&xbtc, &101, 100));
+        assert_eq!(XAssets::pcx_free_balance(&101), before + reward);
+        assert!(XTokens::has_received_deposit_reward(&(101, xbtc.clone())));
+
+        assert_ok!(XAssets::issue(&xbtc, &101, 100));
+        assert_eq!(XAssets::pcx_free_balance(&101), before + reward);
+    });
+}
+
+#[test]
+fn jackpot_events_should_work() {
+    use xstaking::OnReward;
+
+    let jackpot_events_since = |n: usize| {
+        System::events()
+            .into_iter()
+            .skip(n)
+            .filter_map(|record| match record.event {
+                TestEvent::xtokens(e @ RawEvent::JackpotFunded(..))
+                | TestEvent::xtokens(e @ RawEvent::JackpotDrained(..)) => Some(e),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(3);
+        XSession::check_rotate_session(System::block_number());
+
+        let (_, _, xbtc) = tokens();
+        let (_, _, xbtc_jackpot) = token_jackpot_accountids();
+        assert_ok!(XAssets::pcx_issue(&xbtc_jackpot, 1_000_000_000));
+        let event_count = System::events().len();
+        let before = XAssets::pcx_free_balance(&xbtc_jackpot);
+        <XTokens as OnReward<u64, u64>>::reward(&xbtc, 1000);
+        assert_eq!(XAssets::pcx_free_balance(&xbtc_jackpot), before + 1000);
+        assert_eq!(
+            jackpot_events_since(event_count),
+            vec![RawEvent::JackpotFunded(xbtc.clone(), 1000)]
+        );
+
+        // the zero reward funds nothing.
+        let event_count = System::events().len();
+        <XTokens as OnReward<u64, u64>>::reward(&xbtc, 0);
+        assert!(jackpot_events_since(event_count).is_empty());
+
+        // the deposit reward drains the jackpot.
+        let event_count = System::events().len();
+        let before = XAssets::pcx_free_balance(&xbtc_jackpot);
+        assert_ok!(XAssets::issue(&xbtc, &100, 100));
+        assert_eq!(
+            XAssets::pcx_free_balance(&xbtc_jackpot),
+            before - XTokens::deposit_reward()
+        );
+        assert_eq!(
+            jackpot_events_since(event_count),
+            vec![RawEvent::JackpotDrained(
+                xbtc.clone(),
+                XTokens::deposit_reward()
+            )]
+        );
+
+        // the claim drains the jackpot by the dividend.
+        assert_ok!(XTokens::set_claim_restriction(xbtc.clone(), (0u32, 0)));
+        System::set_block_number(4);
+        XSession::check_rotate_session(System::block_number());
+        let before = XAssets::pcx_free_balance(&xbtc_jackpot);
+        let (source_weight, _) = XTokens::settle_claimer_weight(&100, &xbtc, 4);
+        let (target_weight, _) = XTokens::settle_claimee_weight(&xbtc, 4);
+        let dividend =
+            xstaking::compute_dividend::<Test>(source_weight, target_weight, &xbtc_jackpot);
+        let event_count = System::events().len();
+        assert_ok!(XTokens::claim(Origin::signed(100), xbtc.clone()));
+        assert!(dividend > 0);
+        assert_eq!(XAssets::pcx_free_balance(&xbtc_jackpot), before - dividend);
+        assert_eq!(
+            jackpot_events_since(event_count),
+            vec![RawEvent::JackpotDrained(xbtc.clone(), dividend)]
+        );
+    });
+}
@@ -187,3 +187,17 @@ impl<BlockNumber: Default> From<DepositVoteWeight<BlockNumber>>
         }
     }
 }
+
+/// The progress of recomputing the total deposit weight of a psedu intention page by page.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct PseduWeightRecomputation<BlockNumber: Default> {
+    /// The position of the next depositor in `DepositorAt` to sum up.
+    pub next: u32,
+    /// The sum of the weights of the depositors before `next` at `block`.
+    pub weight: u128,
+    /// The sum of the balances of the depositors before `next`.
+    pub balance: u128,
+    pub block: BlockNumber,
+}
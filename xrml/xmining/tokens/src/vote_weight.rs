@@ -27,6 +27,11 @@ impl<T: Trait> Module<T> {
     ) {
         let key = (from.clone(), target.clone());
 
+        if !Self::has_deposit_record(from, target) {
+            Self::note_depositor(from, target);
+        }
+        Self::note_depositor_weight_changed(from, target);
+
         let record_result = Self::try_get_deposit_record(&key);
 
         if record_result.is_ok() && new_deposit_weight <= u128::from(u64::max_value()) {
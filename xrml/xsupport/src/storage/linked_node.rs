@@ -100,6 +100,11 @@ where
     K: Codec + Clone + Eq + PartialEq + Default,
     T: NodeT,
 {
+    /// Build the index directly, only for translating the storage written by a legacy version.
+    pub fn new(multi_key: K, index: T::Index) -> Self {
+        MultiNodeIndex { multi_key, index }
+    }
+
     pub fn key(&self) -> K {
         self.multi_key.clone()
     }
@@ -179,6 +184,12 @@ impl<T: NodeT + Codec> Node<T> {
         }
     }
 
+    /// Build the node with the given links, only for translating the storage written by a
+    /// legacy version, the links are not checked.
+    pub fn with_links(prev: Option<T::Index>, next: Option<T::Index>, data: T) -> Node<T> {
+        Node::<T> { prev, next, data }
+    }
+
     pub fn add_option_before<C: LinkedNodeCollection>(&mut self, mut node: Node<T>) -> Result
     where
         C::Header: StorageValue<NodeIndex<T>>,
@@ -28,7 +28,10 @@ pub type AddressType = u32;
 
 pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"producer";
 
-pub trait Trait: system::Trait {
+/// Max number of recent blocks whose producer is kept in `RecentBlockProducers`.
+pub const MAX_RECENT_BLOCK_PRODUCERS: usize = 256;
+
+pub trait Trait: system::Trait + timestamp::Trait {
     type ValidatorList: ValidatorList<Self::AccountId>;
     type Validator: Validator<Self::AccountId>;
 }
@@ -66,6 +69,7 @@ decl_module! {
         }
 
         fn on_finalize(_n: T::BlockNumber) {
+            Self::note_block_producer();
             BlockProducer::<T>::kill();
             if let Some(height) = Self::blocking_height() {
                 let current_height = system::Module::<T>::block_number();
@@ -96,6 +100,8 @@ fn blocking() {
 decl_storage! {
     trait Store for Module<T: Trait> as XSystem {
         pub BlockProducer get(block_producer): Option<T::AccountId>;
+        /// (height, producer, timestamp) of the recent blocks, the oldest comes first.
+        pub RecentBlockProducers get(recent_block_producers): Vec<(T::BlockNumber, T::AccountId, T::Moment)>;
 
         pub NetworkProps get(network_props) config(): (NetworkType, AddressType);
 
@@ -112,6 +118,28 @@ impl<T: Trait> Module<T> {
     pub fn address_type() -> u8 {
         Self::network_props().1 as u8
     }
+
+    fn note_block_producer() {
+        if let Some(producer) = Self::block_producer() {
+            let height = system::Module::<T>::block_number();
+            let now = timestamp::Module::<T>::now();
+            RecentBlockProducers::<T>::mutate(|v| {
+                v.push((height, producer, now));
+                if v.len() > MAX_RECENT_BLOCK_PRODUCERS {
+                    let overflow = v.len() - MAX_RECENT_BLOCK_PRODUCERS;
+                    v.drain(..overflow);
+                }
+            });
+        }
+    }
+
+    /// Return the height, producer and timestamp of the last `n` blocks, the oldest comes first.
+    /// `n` is bounded by the length of the stored history.
+    pub fn recent_production(n: u32) -> Vec<(T::BlockNumber, T::AccountId, T::Moment)> {
+        let mut history = Self::recent_block_producers();
+        let n = rstd::cmp::min(n as usize, history.len());
+        history.split_off(history.len() - n)
+    }
 }
 
 impl<T: Trait> ProvideInherent for Module<T> {
@@ -1,3 +1,88 @@
 // Copyright 2018-2019 Chainpool.
 
 #![cfg(test)]
+
+use super::*;
+
+use primitives::testing::{Digest, DigestItem, Header, UintAuthorityId};
+use primitives::traits::{BlakeTwo256, IdentityLookup};
+use primitives::BuildStorage;
+use substrate_primitives::{Blake2Hasher, H256};
+use support::impl_outer_origin;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+
+impl system::Trait for Test {
+    type Origin = Origin;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type Digest = Digest;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<u64>;
+    type Header = Header;
+    type Event = ();
+    type Log = DigestItem;
+}
+
+impl consensus::Trait for Test {
+    type Log = DigestItem;
+    type SessionKey = UintAuthorityId;
+    type InherentOfflineReport = ();
+}
+
+impl timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+}
+
+impl Trait for Test {
+    type ValidatorList = MockValidatorList;
+    type Validator = MockValidator;
+}
+
+pub struct MockValidatorList;
+
+impl ValidatorList<u64> for MockValidatorList {
+    fn validator_list() -> Vec<u64> {
+        vec![10, 20, 30]
+    }
+}
+
+pub struct MockValidator;
+
+impl Validator<u64> for MockValidator {
+    fn get_validator_by_name(_name: &[u8]) -> Option<u64> {
+        None
+    }
+    fn get_validator_name(_: &u64) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+pub type System = system::Module<Test>;
+pub type Timestamp = timestamp::Module<Test>;
+pub type XSystem = Module<Test>;
+
+pub fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+    let mut r = system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap()
+        .0;
+    r.extend(
+        GenesisConfig::<Test> {
+            network_props: (NetworkType::Testnet, 42),
+            _genesis_phantom_data: Default::default(),
+        }
+        .build_storage()
+        .unwrap()
+        .0,
+    );
+    r.into()
+}
@@ -1,3 +1,33 @@
 // Copyright 2018-2019 Chainpool.
 
 #![cfg(test)]
+
+use super::mock::*;
+use super::*;
+
+use runtime_io::with_externalities;
+
+#[test]
+fn recent_production_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_eq!(XSystem::recent_production(10), vec![]);
+
+        for i in 1..=3 {
+            System::set_block_number(i);
+            Timestamp::set_timestamp(i * 1000);
+            BlockProducer::<Test>::put(i * 10);
+            XSystem::note_block_producer();
+        }
+
+        assert_eq!(
+            XSystem::recent_production(2),
+            vec![(2, 20, 2000), (3, 30, 3000)]
+        );
+        // bounded by the stored history
+        assert_eq!(
+            XSystem::recent_production(10),
+            vec![(1, 10, 1000), (2, 20, 2000), (3, 30, 3000)]
+        );
+        assert_eq!(XSystem::recent_production(0), vec![]);
+    })
+}